@@ -1,6 +1,6 @@
 //! Alert detection and notification system
 
-use git_bench_core::CompareReport;
+use git_bench_core::{format_speedup, CompareReport};
 
 /// Alert configuration
 #[derive(Debug, Clone, Default)]
@@ -13,33 +13,57 @@ pub struct AlertConfig {
 
 /// Generate an alert message for a comparison report
 pub fn generate_alert_message(report: &CompareReport, config: &AlertConfig) -> Option<String> {
-    if report.alerts.is_empty() {
+    if report.alerts.is_empty() && !report.has_exceeded_budgets() {
         return None;
     }
 
     let mut message = String::new();
 
     message.push_str("# ⚠️ Performance Alert\n\n");
-    message.push_str("The following benchmarks show significant performance regressions:\n\n");
 
-    message.push_str("| Benchmark | Previous | Current | Ratio | Change |\n");
-    message.push_str("|-----------|----------|---------|-------|--------|\n");
+    if !report.alerts.is_empty() {
+        message.push_str("The following benchmarks show significant performance regressions:\n\n");
 
-    for alert in &report.alerts {
+        message.push_str("| Benchmark | Previous | Current | Ratio | Change |\n");
+        message.push_str("|-----------|----------|---------|-------|--------|\n");
+
+        for alert in &report.alerts {
+            message.push_str(&format!(
+                "| {} | {:.2} {} | {:.2} {} | {:.2}x | +{:.1}% |\n",
+                alert.name,
+                alert.previous,
+                alert.unit,
+                alert.current,
+                alert.unit,
+                alert.ratio,
+                alert.percentage_change
+            ));
+        }
+
+        message.push('\n');
+    }
+
+    for budget in report.budgets.iter().filter(|b| b.is_exceeded) {
         message.push_str(&format!(
-            "| {} | {:.2} {} | {:.2} {} | {:.2}x | +{:.1}% |\n",
-            alert.name,
-            alert.previous,
-            alert.unit,
-            alert.current,
-            alert.unit,
-            alert.ratio,
-            alert.percentage_change
+            "Regression budget **{}** exceeded: {:+.1}% since {}. Contributing commits:\n\n",
+            budget.budget,
+            budget.percentage_change,
+            &budget.baseline_commit[..budget.baseline_commit.len().min(7)]
         ));
+        for commit in &budget.contributing_commits {
+            let commit_name = if commit.commit_id.is_empty() {
+                "this run".to_string()
+            } else {
+                commit.commit_id[..commit.commit_id.len().min(7)].to_string()
+            };
+            message.push_str(&format!(
+                "- {}: {:+.1}%\n",
+                commit_name, commit.percentage_change
+            ));
+        }
+        message.push('\n');
     }
 
-    message.push('\n');
-
     if let Some(cc_users) = &config.alert_comment_cc_users {
         message.push_str(&format!("cc: {}\n", cc_users));
     }
@@ -75,13 +99,48 @@ pub fn format_github_actions_alert(report: &CompareReport) -> String {
         ));
     }
 
+    for budget in report.budgets.iter().filter(|b| b.is_exceeded) {
+        output.push_str(&format!(
+            "::error title=Regression Budget Exceeded::Budget '{}' exceeded: suite is {:+.1}% slower than at {} across {} benchmark(s)\n",
+            budget.budget,
+            budget.percentage_change,
+            &budget.baseline_commit[..budget.baseline_commit.len().min(7)],
+            budget.benchmarks
+        ));
+    }
+
+    if let Some(coverage) = report.coverage.as_ref().filter(|c| c.is_alert) {
+        output.push_str(&format!(
+            "::{} title=Missing Benchmarks::{} of {} expected benchmark(s) ran ({:.1}% coverage); missing: {}\n",
+            if coverage.is_failure { "error" } else { "warning" },
+            coverage.present,
+            coverage.expected,
+            coverage.percentage,
+            coverage.missing.join(", ")
+        ));
+    }
+
+    for suspicious in &report.suspicious_improvements {
+        output.push_str(&format!(
+            "::notice title=Suspicious Performance Improvement::Benchmark '{}' improved suspiciously: {} ({:.2} {} → {:.2} {}); check that it still does its work\n",
+            suspicious.name,
+            format_speedup(suspicious),
+            suspicious.previous,
+            suspicious.unit,
+            suspicious.current,
+            suspicious.unit
+        ));
+    }
+
     output
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use git_bench_core::{BenchmarkResult, ComparisonResult};
+    use git_bench_core::{
+        BenchmarkResult, BudgetContribution, BudgetResult, ComparisonResult, Coverage,
+    };
     use std::collections::HashMap;
 
     fn make_comparison(name: &str, prev: f64, curr: f64) -> ComparisonResult {
@@ -107,9 +166,7 @@ mod tests {
         let report = CompareReport {
             comparisons: vec![make_comparison("test", 100.0, 250.0)],
             alerts: vec![make_comparison("test", 100.0, 250.0)],
-            failures: vec![],
-            new_benchmarks: vec![],
-            removed_benchmarks: vec![],
+            ..Default::default()
         };
 
         let config = AlertConfig {
@@ -128,9 +185,7 @@ mod tests {
         let report = CompareReport {
             comparisons: vec![make_comparison("test", 100.0, 100.0)],
             alerts: vec![],
-            failures: vec![],
-            new_benchmarks: vec![],
-            removed_benchmarks: vec![],
+            ..Default::default()
         };
 
         let config = AlertConfig::default();
@@ -141,11 +196,8 @@ mod tests {
     #[test]
     fn test_should_fail() {
         let report = CompareReport {
-            comparisons: vec![],
-            alerts: vec![],
             failures: vec![make_comparison("test", 100.0, 300.0)],
-            new_benchmarks: vec![],
-            removed_benchmarks: vec![],
+            ..Default::default()
         };
 
         let config_fail = AlertConfig {
@@ -165,16 +217,101 @@ mod tests {
     #[test]
     fn test_format_github_actions_alert() {
         let report = CompareReport {
-            comparisons: vec![],
             alerts: vec![make_comparison("slow_function", 100.0, 200.0)],
-            failures: vec![],
-            new_benchmarks: vec![],
-            removed_benchmarks: vec![],
+            ..Default::default()
         };
 
         let output = format_github_actions_alert(&report);
         assert!(output.contains("::warning"));
         assert!(output.contains("slow_function"));
     }
-}
 
+    #[test]
+    fn test_exceeded_budget_fails_and_annotates() {
+        let report = CompareReport {
+            budgets: vec![BudgetResult {
+                budget: "110% since 30 days ago".to_string(),
+                baseline_commit: "abc1234def".to_string(),
+                baseline_date: chrono::Utc::now(),
+                benchmarks: 3,
+                ratio: 1.12,
+                percentage_change: 12.0,
+                mean_delta_ns: None,
+                history_too_short: false,
+                is_exceeded: true,
+                contributing_commits: vec![BudgetContribution {
+                    commit_id: "fff0000aaa".to_string(),
+                    commit_message: "Slow things down".to_string(),
+                    percentage_change: 8.0,
+                }],
+            }],
+            ..Default::default()
+        };
+
+        let config = AlertConfig {
+            fail_on_alert: true,
+            ..Default::default()
+        };
+        assert!(should_fail(&report, &config));
+
+        let message = generate_alert_message(&report, &config).unwrap();
+        assert!(message.contains("**110% since 30 days ago** exceeded: +12.0% since abc1234"));
+        assert!(message.contains("- fff0000: +8.0%"));
+
+        let output = format_github_actions_alert(&report);
+        assert!(output.starts_with("::error title=Regression Budget Exceeded::"));
+    }
+
+    #[test]
+    fn test_missing_benchmarks_fail_and_annotate() {
+        let mut coverage = Coverage {
+            expected: 4,
+            present: 2,
+            percentage: 50.0,
+            missing: vec!["b".to_string(), "c".to_string()],
+            min_coverage: None,
+            is_alert: true,
+            is_failure: false,
+        };
+        let config = AlertConfig {
+            fail_on_alert: true,
+            ..Default::default()
+        };
+
+        let report = CompareReport {
+            coverage: Some(coverage.clone()),
+            ..Default::default()
+        };
+        assert!(!should_fail(&report, &config));
+        assert!(format_github_actions_alert(&report)
+            .starts_with("::warning title=Missing Benchmarks::2 of 4 expected benchmark(s) ran"));
+
+        coverage.is_failure = true;
+        let report = CompareReport {
+            coverage: Some(coverage),
+            ..Default::default()
+        };
+        assert!(should_fail(&report, &config));
+        assert!(format_github_actions_alert(&report).contains("missing: b, c"));
+    }
+
+    #[test]
+    fn test_format_github_actions_notice_for_suspicious_improvement() {
+        let report = CompareReport {
+            suspicious_improvements: vec![make_comparison("noop", 1000.0, 10.0)],
+            ..Default::default()
+        };
+
+        let output = format_github_actions_alert(&report);
+        assert!(output.starts_with("::notice title=Suspicious Performance Improvement::"));
+        assert!(output.contains("'noop' improved suspiciously: 100.00x faster"));
+
+        let report = CompareReport {
+            suspicious_improvements: vec![make_comparison("noop", 1000.0, 0.0)],
+            ..Default::default()
+        };
+        let output = format_github_actions_alert(&report);
+        assert!(output.contains("'noop' improved suspiciously: dropped to zero"));
+        assert!(!output.contains("inf"));
+    }
+}
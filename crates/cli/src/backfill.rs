@@ -0,0 +1,321 @@
+//! Selecting, benchmarking and tracking historical commits
+
+use crate::bench;
+use crate::error::{Error, Result};
+use crate::git;
+use git_bench_core::{parse_from_string, BenchmarkData, BenchmarkRun};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use tracing::{info, warn};
+
+/// Which commits of a range to benchmark
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selection {
+    /// Every commit
+    All,
+    /// Every Nth commit, counted back from the newest so it is always included
+    Every(usize),
+    /// Only tagged commits
+    Tags(HashSet<String>),
+}
+
+/// Pick the commits to benchmark from a range (oldest first)
+pub fn select_commits(commits: &[String], selection: &Selection) -> Vec<String> {
+    let newest = commits.len().saturating_sub(1);
+    commits
+        .iter()
+        .enumerate()
+        .filter(|(i, commit)| match selection {
+            Selection::All => true,
+            Selection::Every(n) => (newest - i).is_multiple_of((*n).max(1)),
+            Selection::Tags(tagged) => tagged.contains(*commit),
+        })
+        .map(|(_, commit)| commit.clone())
+        .collect()
+}
+
+/// Progress kept next to the data file so an interrupted backfill can resume
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BackfillState {
+    /// Commits that could not be benchmarked, with the reason
+    #[serde(default)]
+    pub skipped: BTreeMap<String, String>,
+}
+
+impl BackfillState {
+    /// Load the state, starting fresh if the file does not exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            Error::Other(format!(
+                "Failed to read backfill state '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            Error::Other(format!(
+                "Invalid backfill state '{}': {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Save the state
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Other(format!("Failed to serialize backfill state: {}", e)))?;
+        std::fs::write(path, content).map_err(|source| Error::FileWrite {
+            path: path.display().to_string(),
+            source,
+        })
+    }
+}
+
+/// What to backfill and where to store it
+pub struct BackfillOptions<'a> {
+    pub repo_path: &'a Path,
+    /// Commits to benchmark, `FROM..TO` like `git log`
+    pub range: &'a str,
+    pub selection: &'a Selection,
+    /// Number of commits benchmarked at the same time, each in its own worktree
+    pub jobs: usize,
+    /// Retry commits that failed to benchmark in an earlier backfill
+    pub retry_skipped: bool,
+    pub data_file: &'a Path,
+    pub state_file: &'a Path,
+    pub suite: &'a str,
+    /// Shell command replacing `cargo bench`
+    pub bench_command: Option<&'a str>,
+    /// Arguments passed through to the benchmark command
+    pub cargo_args: &'a [String],
+    /// Where the output of sequential runs is echoed
+    pub echo: bench::Echo,
+}
+
+/// What a backfill did
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackfillSummary {
+    /// Runs stored by this backfill
+    pub stored: usize,
+    /// Commits this backfill tried to benchmark
+    pub total: usize,
+    /// Commits skipped so far, including earlier backfills
+    pub skipped: usize,
+}
+
+/// Benchmark the selected commits of a range at their own worktrees and store
+/// the runs with the commits' timestamps. Progress is saved after every
+/// commit, so an interrupted backfill resumes where it stopped.
+pub fn run_backfill(options: &BackfillOptions<'_>) -> Result<BackfillSummary> {
+    let (from, to) = options
+        .range
+        .split_once("..")
+        .ok_or_else(|| Error::Validation("--range must look like FROM..TO".to_string()))?;
+    let commits = git::commits_between(options.repo_path, from, to).map_err(|e| {
+        Error::Other(format!(
+            "Failed to list the commits in {}: {}",
+            options.range, e
+        ))
+    })?;
+    let selected = select_commits(&commits, options.selection);
+
+    let mut data = BenchmarkData::load_from_file(options.data_file).unwrap_or_else(|_| {
+        info!("Creating new benchmark data file");
+        BenchmarkData::new()
+    });
+    let mut state = BackfillState::load(options.state_file)?;
+
+    // Resume: leave out commits that already have data or failed before
+    let pending: VecDeque<String> = selected
+        .iter()
+        .filter(|id| data.get_run_by_commit(options.suite, id).is_none())
+        .filter(|id| options.retry_skipped || !state.skipped.contains_key(*id))
+        .cloned()
+        .collect();
+    info!(
+        "{} of {} selected commit(s) to benchmark ({} in range)",
+        pending.len(),
+        selected.len(),
+        commits.len()
+    );
+    let total = pending.len();
+    if pending.is_empty() {
+        return Ok(BackfillSummary {
+            stored: 0,
+            total,
+            skipped: state.skipped.len(),
+        });
+    }
+
+    let jobs = options.jobs.clamp(1, pending.len());
+    let target_dir = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| options.repo_path.join("target"));
+    let queue = Mutex::new(pending);
+    let (sender, receiver) = mpsc::channel();
+
+    let mut stored = 0;
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|worker| {
+                let sender = sender.clone();
+                let (queue, target_dir) = (&queue, &target_dir);
+                scope.spawn(move || -> Result<()> {
+                    let Some(first) = queue.lock().unwrap().front().cloned() else {
+                        return Ok(());
+                    };
+                    let worktree = git::Worktree::add(
+                        options.repo_path,
+                        &std::env::temp_dir().join(format!(
+                            "git-bench-backfill-{}-{}",
+                            std::process::id(),
+                            worker
+                        )),
+                        &first,
+                    )?;
+                    // Parallel builds each need their own target dir, cargo would serialize them
+                    let target_dir = if jobs > 1 {
+                        target_dir.join(format!("git-bench-backfill-{}", worker))
+                    } else {
+                        target_dir.clone()
+                    };
+
+                    loop {
+                        let Some(id) = queue.lock().unwrap().pop_front() else {
+                            return Ok(());
+                        };
+                        let commit = git::get_commit_info(options.repo_path, Some(&id))?;
+                        let result = worktree
+                            .checkout(&id)
+                            .and_then(|_| {
+                                let mut command =
+                                    bench::bench_command(options.bench_command, options.cargo_args);
+                                command
+                                    .current_dir(worktree.path())
+                                    .env("CARGO_TARGET_DIR", &target_dir);
+                                if jobs > 1 {
+                                    bench::run_quietly(command)
+                                } else {
+                                    bench::run_and_capture(command, None, options.echo)
+                                }
+                            })
+                            .map_err(|e| e.to_string())
+                            .and_then(|output| {
+                                parse_from_string(&output)
+                                    .ok()
+                                    .filter(|benches| !benches.is_empty())
+                                    .ok_or_else(|| "no benchmark results in output".to_string())
+                            });
+                        if sender.send((commit, result)).is_err() {
+                            return Ok(());
+                        }
+                    }
+                })
+            })
+            .collect();
+        drop(sender);
+
+        // Store results as they come in, so an interrupted backfill keeps its progress
+        for (done, (commit, result)) in receiver.iter().enumerate() {
+            let short_id = &commit.id[..7];
+            match result {
+                Ok(benches) => {
+                    info!(
+                        "[{}/{}] {} {}: {} benchmark(s)",
+                        done + 1,
+                        total,
+                        short_id,
+                        commit.message,
+                        benches.len()
+                    );
+                    state.skipped.remove(&commit.id);
+                    data.insert_run(
+                        options.suite,
+                        BenchmarkRun {
+                            date: commit.timestamp,
+                            commit,
+                            tool: "cargo".to_string(),
+                            benches,
+                        },
+                    );
+                    data.save_to_file(options.data_file)?;
+                    stored += 1;
+                }
+                Err(reason) => {
+                    warn!("[{}/{}] Skipping {}: {}", done + 1, total, short_id, reason);
+                    state.skipped.insert(commit.id, reason);
+                }
+            }
+            state.save(options.state_file)?;
+        }
+
+        workers
+            .into_iter()
+            .map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|_| Err(Error::Other("Backfill worker panicked".to_string())))
+            })
+            .collect::<Result<Vec<()>>>()
+    })?;
+
+    Ok(BackfillSummary {
+        stored,
+        total,
+        skipped: state.skipped.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn commits(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("c{}", i)).collect()
+    }
+
+    #[test]
+    fn test_select_commits() {
+        let range = commits(7);
+        assert_eq!(select_commits(&range, &Selection::All).len(), 7);
+        assert_eq!(
+            select_commits(&range, &Selection::Every(3)),
+            vec!["c0", "c3", "c6"]
+        );
+        assert_eq!(
+            select_commits(&range, &Selection::Every(4)),
+            vec!["c2", "c6"]
+        );
+
+        let tags = ["c1", "c5", "elsewhere"].map(String::from).into();
+        assert_eq!(
+            select_commits(&range, &Selection::Tags(tags)),
+            vec!["c1", "c5"]
+        );
+        assert!(select_commits(&[], &Selection::Every(2)).is_empty());
+    }
+
+    #[test]
+    fn test_backfill_state_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.json");
+        assert_eq!(
+            BackfillState::load(&path).unwrap(),
+            BackfillState::default()
+        );
+
+        let mut state = BackfillState::default();
+        state
+            .skipped
+            .insert("abc".to_string(), "build failed".to_string());
+        state.save(&path).unwrap();
+        assert_eq!(BackfillState::load(&path).unwrap(), state);
+    }
+}
@@ -0,0 +1,269 @@
+//! Running `cargo bench` (or a configured benchmark command) and capturing its output
+
+use crate::error::{Error, Result};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+
+/// Build a `cargo bench` command with passthrough arguments (packages,
+/// features, `--bench` filters, ...). Uses `$CARGO` when set, e.g. when
+/// running as a cargo subcommand.
+pub fn cargo_bench_command(args: &[String]) -> Command {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo"));
+    let mut command = Command::new(cargo);
+    command.arg("bench").args(args);
+    command
+}
+
+/// Build the benchmark command: `cargo bench`, or `custom` run through the
+/// shell when a suite configures its own command. The passthrough arguments
+/// are appended either way.
+pub fn bench_command(custom: Option<&str>, args: &[String]) -> Command {
+    let Some(custom) = custom else {
+        return cargo_bench_command(args);
+    };
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(custom).args(args);
+        command
+    } else {
+        let script = std::iter::once(custom.to_string())
+            .chain(
+                args.iter()
+                    .map(|arg| format!("'{}'", arg.replace('\'', "'\\''"))),
+            )
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    }
+}
+
+/// How a command is named in errors: `cargo bench`, or the shell command line
+fn command_label(command: &Command) -> String {
+    let program = command.get_program().to_string_lossy();
+    let args: Vec<_> = command
+        .get_args()
+        .map(|arg| arg.to_string_lossy())
+        .collect();
+    match args.as_slice() {
+        [flag, script, ..] if flag == "-c" || flag == "/C" => script.to_string(),
+        [first, ..] => format!("{} {}", program, first),
+        [] => program.into_owned(),
+    }
+}
+
+/// Captured output, optionally mirrored to a file
+struct Capture {
+    output: Vec<u8>,
+    tee: Option<(File, String)>,
+}
+
+impl Capture {
+    fn push(&mut self, line: &[u8]) -> Result<()> {
+        self.output.extend_from_slice(line);
+        if let Some((file, path)) = &mut self.tee {
+            file.write_all(line).map_err(|source| Error::FileWrite {
+                path: path.clone(),
+                source,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a benchmark command's output is echoed while it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Echo {
+    /// Not at all
+    Quiet,
+    /// To our stdout and stderr, stream for stream
+    Live,
+    /// All to our stderr, keeping stdout for machine-readable output
+    Stderr,
+}
+
+/// Run a benchmark command, echoing its stdout and stderr as `echo` says while
+/// capturing both (like `2>&1 | tee`). The combined output is also written to
+/// `tee` if given. Fails if the command cannot start or does not succeed.
+pub fn run_and_capture(command: Command, tee: Option<&Path>, echo: Echo) -> Result<String> {
+    run(command, tee, echo)
+}
+
+/// Like [`run_and_capture`] without echoing the output, for commands running in parallel
+pub fn run_quietly(command: Command) -> Result<String> {
+    run(command, None, Echo::Quiet)
+}
+
+fn run(mut command: Command, tee: Option<&Path>, echo: Echo) -> Result<String> {
+    let tee = tee
+        .map(|path| {
+            File::create(path)
+                .map(|file| (file, path.display().to_string()))
+                .map_err(|source| Error::FileWrite {
+                    path: path.display().to_string(),
+                    source,
+                })
+        })
+        .transpose()?;
+    let capture = Mutex::new(Capture {
+        output: Vec::new(),
+        tee,
+    });
+
+    let program = command_label(&command);
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::Bench(format!("Failed to start `{}`: {}", program, e)))?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+
+    let echo_out: Option<Box<dyn Write>> = match echo {
+        Echo::Quiet => None,
+        Echo::Live => Some(Box::new(std::io::stdout())),
+        Echo::Stderr => Some(Box::new(std::io::stderr())),
+    };
+    thread::scope(|scope| {
+        let errors = scope.spawn(|| {
+            forward(
+                stderr,
+                (echo != Echo::Quiet).then(std::io::stderr),
+                &capture,
+            )
+        });
+        let out = forward(stdout, echo_out, &capture);
+        errors
+            .join()
+            .unwrap_or_else(|_| Err(Error::Bench("stderr reader panicked".to_string())))
+            .and(out)
+    })?;
+
+    let status = child
+        .wait()
+        .map_err(|e| Error::Bench(format!("Failed to wait for `{}`: {}", program, e)))?;
+    let output = String::from_utf8_lossy(
+        &capture
+            .into_inner()
+            .unwrap_or_else(|e| e.into_inner())
+            .output,
+    )
+    .into_owned();
+
+    if !status.success() {
+        return Err(Error::Bench(
+            if output.contains("error: could not compile") {
+                // Quiet runs point at the first compiler error instead of the echoed output
+                let detail = match output.lines().find(|line| line.starts_with("error")) {
+                    Some(first_error) if echo == Echo::Quiet => first_error.to_string(),
+                    _ => "see the compiler errors above".to_string(),
+                };
+                format!("the benchmarks failed to build ({}); {}", status, detail)
+            } else {
+                format!("`{}` exited with {}", program, status)
+            },
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Copy lines from a child's pipe to the capture and, if given, our own stream
+fn forward(
+    reader: impl Read,
+    mut writer: Option<impl Write>,
+    capture: &Mutex<Capture>,
+) -> Result<()> {
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .map_err(|e| Error::Bench(format!("Failed to read benchmark output: {}", e)))?;
+        if read == 0 {
+            return Ok(());
+        }
+        // Our own stream going away (e.g. a closed pipe) must not stop the benchmarks
+        if let Some(writer) = writer.as_mut() {
+            let _ = writer.write_all(&line).and_then(|_| writer.flush());
+        }
+        capture
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(&line)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    }
+
+    #[test]
+    fn test_run_and_capture_tees_both_streams() {
+        let dir = TempDir::new().unwrap();
+        let tee = dir.path().join("output.txt");
+
+        let output = run_and_capture(
+            shell("echo 'test bench_a ... bench: 10 ns/iter (+/- 1)'; echo Compiling >&2"),
+            Some(&tee),
+            Echo::Live,
+        )
+        .unwrap();
+
+        assert!(output.contains("bench_a"));
+        assert!(output.contains("Compiling"));
+        assert_eq!(std::fs::read_to_string(&tee).unwrap(), output);
+    }
+
+    #[test]
+    fn test_run_and_capture_reports_build_failures() {
+        let err = run_and_capture(
+            shell("echo 'error: could not compile `x`' >&2; exit 101"),
+            None,
+            Echo::Live,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("failed to build"));
+
+        let err = run_quietly(shell(
+            "echo 'error[E0308]: mismatched types' >&2; echo 'error: could not compile `x`' >&2; exit 101",
+        ))
+        .unwrap_err();
+        assert!(err.to_string().ends_with("error[E0308]: mismatched types"));
+
+        let err = run_quietly(shell("exit 3")).unwrap_err();
+        assert!(err.to_string().contains("exited with"));
+    }
+
+    #[test]
+    fn test_cargo_bench_command_passes_args_through() {
+        let command = cargo_bench_command(&["-p".to_string(), "core".to_string()]);
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, vec!["bench", "-p", "core"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_custom_bench_command() {
+        let command = bench_command(Some("echo"), &["it's".to_string(), "a b".to_string()]);
+        assert_eq!(command_label(&command), "echo 'it'\\''s' 'a b'");
+        assert_eq!(run_quietly(command).unwrap(), "it's a b\n");
+
+        let err = run_quietly(bench_command(Some("exit 3"), &[])).unwrap_err();
+        assert!(err.to_string().contains("`exit 3` exited with"));
+    }
+}
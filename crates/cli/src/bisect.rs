@@ -0,0 +1,372 @@
+//! Binary search for the commit that introduced a regression
+
+use crate::bench;
+use crate::error::{Error, Result};
+use crate::git;
+use chrono::Utc;
+use git_bench_core::{
+    compare_runs, merge_repetitions, parse_from_string, BenchmarkData, BenchmarkRun, CommitInfo,
+    CompareConfig,
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Classification of a measured commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// Performs like the good commit
+    Good,
+    /// Regressed against the good commit
+    Bad,
+    /// Could not be measured (build failure, benchmark missing)
+    Skip,
+}
+
+impl Verdict {
+    /// Lowercase name for output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Good => "good",
+            Verdict::Bad => "bad",
+            Verdict::Skip => "skip",
+        }
+    }
+}
+
+/// Outcome of a bisection
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bisection {
+    /// Index of the first bad candidate
+    pub first_bad: usize,
+    /// Untestable candidates right before it; any of them may be the culprit
+    pub untestable: Vec<usize>,
+}
+
+/// What to bisect and how to tell good commits from bad ones
+pub struct BisectOptions<'a> {
+    pub repo_path: &'a Path,
+    /// Last known good commit
+    pub good: &'a str,
+    /// First known bad commit
+    pub bad: &'a str,
+    /// Benchmark to bisect, by its full name (also passed to the harness as a filter)
+    pub bench: &'a str,
+    /// Runs per commit, averaged
+    pub repetitions: usize,
+    /// Arguments passed through to `cargo bench`
+    pub cargo_args: &'a [String],
+    /// Thresholds a commit must exceed against the good commit to be bad
+    pub config: &'a CompareConfig,
+    /// Where the benchmark output is echoed
+    pub echo: bench::Echo,
+}
+
+/// Where every measured run is stored
+pub struct BisectStore<'a> {
+    pub data: &'a mut BenchmarkData,
+    pub data_file: &'a Path,
+    pub suite: &'a str,
+}
+
+/// A measured commit
+#[derive(Debug, Clone, Serialize)]
+pub struct BisectStep {
+    /// Commit SHA
+    pub commit: String,
+    pub message: String,
+    pub verdict: Verdict,
+    /// Change of the benchmark against the good commit
+    pub percentage_change: Option<f64>,
+}
+
+/// Result of `git-bench bisect`
+#[derive(Debug, Clone, Serialize)]
+pub struct BisectReport {
+    pub benchmark: String,
+    /// SHA of the good commit
+    pub good: String,
+    pub first_bad_commit: CommitInfo,
+    /// Change of the benchmark at the first bad commit
+    pub percentage_change: Option<f64>,
+    /// Untestable commits right before the first bad one; any of them may be the culprit
+    pub untestable: Vec<String>,
+    /// Number of commits in the searched range
+    #[serde(skip)]
+    pub candidates: usize,
+    /// Measured commits in the order they were tested
+    pub steps: Vec<BisectStep>,
+}
+
+impl BisectReport {
+    /// Human-readable summary of the search
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!(
+            "## Bisected '{}' over {} commit(s) in {} step(s)\n",
+            self.benchmark,
+            self.candidates,
+            self.steps.len()
+        )];
+        for step in &self.steps {
+            lines.push(format!(
+                "  {} {:<4} {:>8}  {}",
+                &step.commit[..7],
+                step.verdict.as_str(),
+                step.percentage_change
+                    .map(|pct| format!("{:+.1}%", pct))
+                    .unwrap_or_else(|| "-".to_string()),
+                step.message
+            ));
+        }
+        lines.push(format!(
+            "\nFirst bad commit: {} {}",
+            self.first_bad_commit.id, self.first_bad_commit.message
+        ));
+        if !self.untestable.is_empty() {
+            lines.push(format!(
+                "Could not benchmark {} commit(s) right before it, any of them may be the culprit:",
+                self.untestable.len()
+            ));
+            for id in &self.untestable {
+                lines.push(format!("  {}", id));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Benchmark the commits between `good` and `bad` in a temporary worktree and
+/// find the one that made the benchmark regress
+pub fn run_bisection(
+    options: &BisectOptions<'_>,
+    mut store: Option<BisectStore<'_>>,
+) -> Result<BisectReport> {
+    let candidates = git::commits_between(options.repo_path, options.good, options.bad)?;
+    let good = git::get_commit_info(options.repo_path, Some(options.good))?;
+
+    // The benchmark name doubles as the harness filter
+    let mut bench_args = options.cargo_args.to_vec();
+    if !bench_args.iter().any(|arg| arg == "--") {
+        bench_args.push("--".to_string());
+    }
+    bench_args.push(options.bench.to_string());
+
+    // Share build artifacts with the main checkout so every step is an incremental build
+    let target_dir = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| options.repo_path.join("target"));
+    let worktree_path =
+        std::env::temp_dir().join(format!("git-bench-bisect-{}", std::process::id()));
+    let worktree = git::Worktree::add(options.repo_path, &worktree_path, &good.id)?;
+
+    let mut measure = |commit: &CommitInfo| -> Result<Option<BenchmarkRun>> {
+        info!("Benchmarking {} {}", &commit.id[..7], commit.message);
+        worktree.checkout(&commit.id)?;
+
+        let mut repetitions = Vec::new();
+        for _ in 0..options.repetitions.max(1) {
+            let mut command = bench::cargo_bench_command(&bench_args);
+            command
+                .current_dir(worktree.path())
+                .env("CARGO_TARGET_DIR", &target_dir);
+            match bench::run_and_capture(command, None, options.echo) {
+                Ok(output) => repetitions.push(parse_from_string(&output).unwrap_or_default()),
+                Err(e) => {
+                    warn!("Skipping {}: {}", &commit.id[..7], e);
+                    return Ok(None);
+                }
+            }
+        }
+
+        let run = BenchmarkRun {
+            commit: commit.clone(),
+            date: Utc::now(),
+            tool: "cargo".to_string(),
+            benches: merge_repetitions(&repetitions),
+        };
+        if let Some(store) = store.as_mut() {
+            store.data.insert_run(store.suite, run.clone());
+            store.data.save_to_file(store.data_file)?;
+        }
+
+        let benches: Vec<_> = run
+            .benches
+            .into_iter()
+            .filter(|b| b.name == options.bench)
+            .collect();
+        if benches.is_empty() {
+            warn!(
+                "Skipping {}: no benchmark named '{}'",
+                &commit.id[..7],
+                options.bench
+            );
+            return Ok(None);
+        }
+        Ok(Some(BenchmarkRun { benches, ..run }))
+    };
+
+    let good_run = measure(&good)?.ok_or_else(|| {
+        Error::Bench(format!(
+            "Could not benchmark '{}' at the good commit",
+            options.bench
+        ))
+    })?;
+
+    let mut steps: Vec<BisectStep> = Vec::new();
+    let mut classify = |index: usize| -> Result<Verdict> {
+        let commit = git::get_commit_info(options.repo_path, Some(&candidates[index]))?;
+        let (verdict, percentage_change) = match measure(&commit)? {
+            Some(run) => {
+                let report = compare_runs(&good_run, &run, options.config);
+                let verdict = if report.alerts.is_empty() {
+                    Verdict::Good
+                } else {
+                    Verdict::Bad
+                };
+                (
+                    verdict,
+                    report.comparisons.first().map(|c| c.percentage_change),
+                )
+            }
+            None => (Verdict::Skip, None),
+        };
+        info!(
+            "{} is {}{}",
+            &commit.id[..7],
+            verdict.as_str(),
+            percentage_change
+                .map(|pct| format!(" ({:+.1}%)", pct))
+                .unwrap_or_default()
+        );
+        steps.push(BisectStep {
+            commit: commit.id,
+            message: commit.message,
+            verdict,
+            percentage_change,
+        });
+        Ok(verdict)
+    };
+
+    // Make sure the range actually contains a regression before searching it
+    match classify(candidates.len() - 1)? {
+        Verdict::Bad => {}
+        Verdict::Good => {
+            return Err(Error::Validation(format!(
+                "'{}' does not regress against '{}' at the current thresholds",
+                options.bad, options.good
+            )))
+        }
+        Verdict::Skip => {
+            return Err(Error::Bench(format!(
+                "Could not benchmark '{}' at the bad commit",
+                options.bench
+            )))
+        }
+    }
+    let result = bisect(candidates.len(), &mut classify)?;
+    drop(worktree);
+
+    let first_bad_id = &candidates[result.first_bad];
+    let percentage_change = steps
+        .iter()
+        .find(|step| &step.commit == first_bad_id)
+        .expect("the first bad commit was measured")
+        .percentage_change;
+
+    Ok(BisectReport {
+        benchmark: options.bench.to_string(),
+        good: good.id,
+        first_bad_commit: git::get_commit_info(options.repo_path, Some(first_bad_id))?,
+        percentage_change,
+        untestable: result
+            .untestable
+            .iter()
+            .map(|&i| candidates[i].clone())
+            .collect(),
+        candidates: candidates.len(),
+        steps,
+    })
+}
+
+/// Find the first bad commit among `candidates` (oldest first). All commits
+/// before the first candidate are good and the last candidate is known to be
+/// bad, so it is never classified. Skipped candidates are stepped around like
+/// `git bisect skip`.
+pub fn bisect<E>(
+    candidates: usize,
+    mut classify: impl FnMut(usize) -> std::result::Result<Verdict, E>,
+) -> std::result::Result<Bisection, E> {
+    assert!(candidates > 0, "bisect needs at least the bad commit");
+
+    let mut good = 0; // candidates[..good] are good
+    let mut bad = candidates - 1; // candidates[bad] is bad
+    let mut skipped = BTreeSet::new();
+
+    while good < bad {
+        let middle = good + (bad - good) / 2;
+        // Probe the testable candidate closest to the middle
+        let Some(probe) = (good..bad)
+            .filter(|i| !skipped.contains(i))
+            .min_by_key(|i| i.abs_diff(middle))
+        else {
+            break;
+        };
+
+        match classify(probe)? {
+            Verdict::Good => good = probe + 1,
+            Verdict::Bad => bad = probe,
+            Verdict::Skip => {
+                skipped.insert(probe);
+            }
+        }
+    }
+
+    Ok(Bisection {
+        first_bad: bad,
+        untestable: skipped.range(good..bad).copied().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(verdicts: &[Verdict]) -> (Bisection, Vec<usize>) {
+        let mut probed = Vec::new();
+        let result = bisect::<()>(verdicts.len(), |i| {
+            probed.push(i);
+            Ok(verdicts[i])
+        })
+        .unwrap();
+        (result, probed)
+    }
+
+    #[test]
+    fn test_bisect_finds_first_bad() {
+        use Verdict::*;
+        let (result, probed) = run(&[Good, Good, Good, Bad, Bad, Bad, Bad, Bad]);
+        assert_eq!(result.first_bad, 3);
+        assert!(result.untestable.is_empty());
+        assert!(probed.len() <= 3);
+        assert!(!probed.contains(&7));
+
+        let (result, probed) = run(&[Bad]);
+        assert_eq!(result.first_bad, 0);
+        assert!(probed.is_empty());
+    }
+
+    #[test]
+    fn test_bisect_steps_around_skipped_commits() {
+        use Verdict::*;
+        let (result, _) = run(&[Good, Skip, Good, Bad, Bad]);
+        assert_eq!(result.first_bad, 3);
+        assert!(result.untestable.is_empty());
+
+        // The culprit is hidden behind untestable commits
+        let (result, _) = run(&[Good, Skip, Skip, Bad]);
+        assert_eq!(result.first_bad, 3);
+        assert_eq!(result.untestable, vec![1, 2]);
+    }
+}
@@ -0,0 +1,553 @@
+//! Project configuration from `git-bench.toml` or Cargo.toml metadata
+//!
+//! Top-level keys and the `[deploy]` table are defaults for command flags,
+//! named like the flag (`alert_threshold`, `threshold_rules`,
+//! `gh_pages_branch`, ...). A `[suites.<name>]` table overrides them for the
+//! suite selected with `--name`. Flags given on the command line always win:
+//! the configuration is applied by adding the flags the user did not give
+//! before the arguments are parsed. Boolean flags get a `--no-<flag>`
+//! counterpart to turn off what the configuration turns on.
+
+use anyhow::{bail, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsString;
+use std::path::Path;
+use toml::{Table, Value};
+
+/// Name of the configuration file
+pub const CONFIG_FILE: &str = "git-bench.toml";
+
+/// Project configuration
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    /// Where the configuration was read from
+    pub source: String,
+    /// Flag defaults for every command
+    pub defaults: Table,
+    /// Deploy flag defaults (`gh_pages_branch`, `auto_push`, ...)
+    pub deploy: Table,
+    /// Per-suite overrides, keyed by suite name
+    pub suites: BTreeMap<String, Table>,
+}
+
+impl Config {
+    /// Build a configuration from its TOML table
+    pub fn from_table(mut table: Table, source: String) -> Result<Self> {
+        let deploy = match table.remove("deploy") {
+            None => Table::new(),
+            Some(Value::Table(deploy)) => deploy,
+            Some(_) => bail!("`deploy` in {} must be a table", source),
+        };
+        let suites = match table.remove("suites") {
+            None => BTreeMap::new(),
+            Some(Value::Table(suites)) => suites
+                .into_iter()
+                .map(|(name, value)| match value {
+                    Value::Table(suite) => Ok((name, suite)),
+                    _ => bail!("`suites.{}` in {} must be a table", name, source),
+                })
+                .collect::<Result<_>>()?,
+            Some(_) => bail!("`suites` in {} must be a table", source),
+        };
+
+        Ok(Self {
+            source,
+            defaults: table,
+            deploy,
+            suites,
+        })
+    }
+
+    /// Load the configuration from an explicit file, or look for `git-bench.toml`
+    /// and then a `[package.metadata.git-bench]` or `[workspace.metadata.git-bench]`
+    /// table in the Cargo.toml of `dir`
+    pub fn load(explicit: Option<&Path>, dir: &Path) -> Result<Option<Self>> {
+        if let Some(path) = explicit {
+            return Self::read(path).map(Some);
+        }
+
+        let file = dir.join(CONFIG_FILE);
+        if file.exists() {
+            return Self::read(&file).map(Some);
+        }
+
+        let manifest = dir.join("Cargo.toml");
+        if !manifest.exists() {
+            return Ok(None);
+        }
+        let mut cargo = read_table(&manifest)?;
+        for section in ["package", "workspace"] {
+            let table = cargo
+                .get_mut(section)
+                .and_then(|section| section.get_mut("metadata"))
+                .and_then(Value::as_table_mut)
+                .and_then(|metadata| metadata.remove("git-bench"));
+            match table {
+                Some(Value::Table(table)) => {
+                    let source = format!("{} [{}.metadata.git-bench]", manifest.display(), section);
+                    return Self::from_table(table, source).map(Some);
+                }
+                Some(_) => bail!(
+                    "`{}.metadata.git-bench` in {} must be a table",
+                    section,
+                    manifest.display()
+                ),
+                None => {}
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        Self::from_table(read_table(path)?, path.display().to_string())
+    }
+
+    /// Settings for a suite: the defaults and deploy settings, overridden by the
+    /// suite's own table
+    pub fn settings(&self, suite: Option<&str>) -> Table {
+        let mut settings = self.defaults.clone();
+        settings.extend(self.deploy.clone());
+        if let Some(table) = suite.and_then(|suite| self.suites.get(suite)) {
+            settings.extend(table.clone());
+        }
+        settings
+    }
+
+    /// Check that every key names a flag of some command
+    pub fn validate(&self, cli: &Command) -> Result<()> {
+        let tables = [
+            (String::new(), &self.defaults),
+            ("deploy.".to_string(), &self.deploy),
+        ]
+        .into_iter()
+        .chain(
+            self.suites
+                .iter()
+                .map(|(name, suite)| (format!("suites.{}.", name), suite)),
+        );
+
+        for (prefix, table) in tables {
+            for key in table.keys() {
+                if !cli.get_subcommands().any(|c| find_arg(c, key).is_some()) {
+                    bail!("Unknown setting `{}{}` in {}", prefix, key, self.source);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the configured flags the user did not give to the command line
+    pub fn apply(&self, argv: Vec<OsString>, cli: &Command) -> Result<Vec<OsString>> {
+        let Some(position) = subcommand_position(&argv, cli) else {
+            return Ok(argv);
+        };
+        let Some(command) = cli.find_subcommand(argv[position].to_string_lossy().as_ref()) else {
+            return Ok(argv);
+        };
+
+        let user_args: Vec<String> = argv[position + 1..]
+            .iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let passthrough = user_args.iter().position(|arg| arg == "--");
+        let flags = &user_args[..passthrough.unwrap_or(user_args.len())];
+        let given = given_args(command, flags);
+
+        let suite = find_arg(command, "name")
+            .and_then(|arg| flag_value(arg, flags))
+            .or_else(|| {
+                self.defaults
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .map(String::from)
+            });
+        let settings = self.settings(suite.as_deref());
+
+        let mut added: Vec<String> = Vec::new();
+        let mut trailing: Vec<String> = Vec::new();
+        for (key, value) in &settings {
+            let Some(arg) = find_arg(command, key) else {
+                continue;
+            };
+            let from_env = arg
+                .get_env()
+                .is_some_and(|env| std::env::var_os(env).is_some());
+            let negated = format!("no_{}", arg.get_id());
+            if given.contains(arg.get_id().as_str()) || given.contains(&negated) || from_env {
+                continue;
+            }
+
+            if arg.is_positional() {
+                // Only passthrough arguments (`-- ...`) can be configured
+                if arg.is_last_set() && passthrough.is_none() {
+                    trailing = self.values(key, value)?;
+                }
+                continue;
+            }
+            let Some(long) = arg.get_long() else {
+                continue;
+            };
+
+            if arg.get_action().takes_values() {
+                for value in self.values(key, value)? {
+                    added.push(format!("--{}={}", long, value));
+                }
+            } else {
+                match value {
+                    Value::Boolean(true) => added.push(format!("--{}", long)),
+                    Value::Boolean(false) => {}
+                    _ => bail!("`{}` in {} must be true or false", key, self.source),
+                }
+            }
+        }
+
+        let mut result: Vec<OsString> = argv[..=position].to_vec();
+        result.extend(added.into_iter().map(OsString::from));
+        result.extend(argv[position + 1..].iter().cloned());
+        if !trailing.is_empty() {
+            result.push("--".into());
+            result.extend(trailing.into_iter().map(OsString::from));
+        }
+        Ok(result)
+    }
+
+    /// Flag values for a setting (arrays repeat the flag)
+    fn values(&self, key: &str, value: &Value) -> Result<Vec<String>> {
+        match value {
+            Value::Array(values) => values.iter().map(|value| self.scalar(key, value)).collect(),
+            value => Ok(vec![self.scalar(key, value)?]),
+        }
+    }
+
+    fn scalar(&self, key: &str, value: &Value) -> Result<String> {
+        match value {
+            Value::String(s) => Ok(s.clone()),
+            Value::Integer(i) => Ok(i.to_string()),
+            Value::Float(f) => Ok(f.to_string()),
+            Value::Boolean(b) => Ok(b.to_string()),
+            _ => bail!(
+                "`{}` in {} must be a string, number or boolean",
+                key,
+                self.source
+            ),
+        }
+    }
+}
+
+/// Add a `--no-<flag>` counterpart to the boolean flags of every subcommand,
+/// so that a flag turned on by the configuration can be turned off again
+pub fn with_negations(cli: Command) -> Command {
+    let names: Vec<String> = cli
+        .get_subcommands()
+        .map(|command| command.get_name().to_string())
+        .collect();
+    names.iter().fold(cli, |cli, name| {
+        cli.mut_subcommand(name, |command| {
+            let negations: Vec<Arg> = command
+                .get_arguments()
+                .filter(|arg| {
+                    matches!(arg.get_action(), ArgAction::SetTrue) && !arg.is_global_set()
+                })
+                // Flags that default to on have nothing to turn off
+                .filter(|arg| arg.get_default_values().iter().all(|value| value != "true"))
+                .filter_map(|arg| {
+                    let long = arg.get_long()?;
+                    Some(
+                        Arg::new(format!("no_{}", arg.get_id()))
+                            .long(format!("no-{}", long))
+                            .action(ArgAction::SetTrue)
+                            .overrides_with(arg.get_id().clone())
+                            .help(format!(
+                                "Turn off --{} (e.g. when the configuration sets it)",
+                                long
+                            )),
+                    )
+                })
+                .collect();
+            command.args(negations)
+        })
+    })
+}
+
+fn read_table(path: &Path) -> Result<Table> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    toml::from_str(&content).with_context(|| format!("Invalid TOML in {:?}", path))
+}
+
+/// Find the flag a setting refers to, by field name or long flag name
+fn find_arg<'a>(command: &'a Command, key: &str) -> Option<&'a Arg> {
+    let key = key.replace('-', "_");
+    command.get_arguments().find(|arg| {
+        arg.get_id().as_str() == key
+            || arg
+                .get_long()
+                .is_some_and(|long| long.replace('-', "_") == key)
+    })
+}
+
+/// Index of the subcommand in the arguments, skipping global flags
+fn subcommand_position(argv: &[OsString], cli: &Command) -> Option<usize> {
+    // Global flags given before the subcommand, whose value is the next argument
+    let takes_value = |arg: &str| {
+        cli.get_arguments()
+            .filter(|a| a.is_global_set() && a.get_action().takes_values())
+            .any(|a| {
+                a.get_long()
+                    .is_some_and(|long| arg.strip_prefix("--") == Some(long))
+                    || a.get_short()
+                        .is_some_and(|short| arg == format!("-{}", short))
+            })
+    };
+    let mut args = argv.iter().enumerate().skip(1);
+    while let Some((i, arg)) = args.next() {
+        let arg = arg.to_string_lossy();
+        if takes_value(&arg) {
+            args.next();
+        } else if !arg.starts_with('-') {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Ids of the flags present on the command line
+fn given_args(command: &Command, flags: &[String]) -> HashSet<String> {
+    let mut given = HashSet::new();
+    for flag in flags {
+        if let Some(long) = flag.strip_prefix("--") {
+            let long = long.split('=').next().unwrap_or(long);
+            if let Some(arg) = command.get_arguments().find(|a| a.get_long() == Some(long)) {
+                given.insert(arg.get_id().to_string());
+            }
+        } else if let Some(shorts) = flag.strip_prefix('-') {
+            for short in shorts.chars() {
+                let Some(arg) = command
+                    .get_arguments()
+                    .find(|a| a.get_short() == Some(short))
+                else {
+                    break;
+                };
+                given.insert(arg.get_id().to_string());
+                if arg.get_action().takes_values() {
+                    break;
+                }
+            }
+        }
+    }
+    given
+}
+
+/// Value of a flag on the command line (`--name x`, `--name=x`, `-n x`, `-nx`)
+fn flag_value(arg: &Arg, flags: &[String]) -> Option<String> {
+    let long = arg.get_long().map(|long| format!("--{}", long));
+    let short = arg.get_short().map(|short| format!("-{}", short));
+
+    let mut value = None;
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        for name in long.iter().chain(short.iter()) {
+            if flag == name {
+                value = flags.next().cloned();
+            } else if let Some(rest) = flag.strip_prefix(name.as_str()) {
+                if let Some(inline) = rest.strip_prefix('=') {
+                    value = Some(inline.to_string());
+                } else if !rest.is_empty() && !name.starts_with("--") {
+                    value = Some(rest.to_string());
+                }
+            }
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn cli() -> Command {
+        Command::new("git-bench")
+            .arg(
+                Arg::new("verbose")
+                    .short('v')
+                    .long("verbose")
+                    .action(ArgAction::SetTrue)
+                    .global(true),
+            )
+            .arg(Arg::new("config").long("config").global(true))
+            .arg(Arg::new("output").long("output").global(true))
+            .subcommand(
+                Command::new("run")
+                    .arg(Arg::new("name").short('n').long("name"))
+                    .arg(Arg::new("alert_threshold").long("alert-threshold"))
+                    .arg(
+                        Arg::new("threshold_rules")
+                            .long("threshold-rule")
+                            .action(ArgAction::Append),
+                    )
+                    .arg(
+                        Arg::new("auto_push")
+                            .long("auto-push")
+                            .action(ArgAction::SetTrue),
+                    )
+                    .arg(Arg::new("max_items").long("max-items"))
+                    .arg(Arg::new("cargo_args").last(true).num_args(0..)),
+            )
+            .subcommand(Command::new("history").arg(Arg::new("data_file").long("data-file")))
+    }
+
+    fn config(toml: &str) -> Config {
+        Config::from_table(toml::from_str(toml).unwrap(), "test.toml".to_string()).unwrap()
+    }
+
+    fn apply(config: &Config, args: &[&str]) -> Vec<String> {
+        let argv = args.iter().map(OsString::from).collect();
+        config
+            .apply(argv, &with_negations(cli()))
+            .unwrap()
+            .into_iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    const CONFIG: &str = r#"
+        alert_threshold = "150%"
+        threshold_rules = ["io/**=300%", "slow::*=ignore"]
+        data_file = "bench.json"
+
+        [deploy]
+        auto_push = true
+
+        [suites.parser]
+        alert-threshold = "110%"
+        max_items = 50
+        cargo_args = ["-p", "parser"]
+    "#;
+
+    #[test]
+    fn test_apply_adds_missing_flags() {
+        let config = config(CONFIG);
+        assert_eq!(
+            apply(
+                &config,
+                &["git-bench", "-v", "run", "--alert-threshold", "300%"]
+            ),
+            vec![
+                "git-bench",
+                "-v",
+                "run",
+                "--auto-push",
+                "--threshold-rule=io/**=300%",
+                "--threshold-rule=slow::*=ignore",
+                "--alert-threshold",
+                "300%",
+            ]
+        );
+        assert_eq!(
+            apply(&config, &["git-bench", "history"]),
+            vec!["git-bench", "history", "--data-file=bench.json"]
+        );
+    }
+
+    #[test]
+    fn test_apply_skips_global_flag_values() {
+        let config = config(CONFIG);
+        assert_eq!(
+            apply(
+                &config,
+                &[
+                    "git-bench",
+                    "--output",
+                    "json",
+                    "--config",
+                    "x.toml",
+                    "history"
+                ]
+            ),
+            vec![
+                "git-bench",
+                "--output",
+                "json",
+                "--config",
+                "x.toml",
+                "history",
+                "--data-file=bench.json"
+            ]
+        );
+        assert_eq!(
+            apply(&config, &["git-bench", "--output=json", "history"]),
+            vec![
+                "git-bench",
+                "--output=json",
+                "history",
+                "--data-file=bench.json"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_suite_settings() {
+        let config = config(CONFIG);
+        let args = apply(&config, &["git-bench", "run", "-n", "parser"]);
+        assert!(args.contains(&"--alert-threshold=110%".to_string()));
+        assert!(args.contains(&"--max-items=50".to_string()));
+        assert!(args.ends_with(&["--".to_string(), "-p".to_string(), "parser".to_string()]));
+
+        // Passthrough arguments on the command line replace the suite's
+        let args = apply(
+            &config,
+            &["git-bench", "run", "--name=parser", "--", "-p", "lexer"],
+        );
+        assert!(args.ends_with(&["--".to_string(), "-p".to_string(), "lexer".to_string()]));
+        assert_eq!(args.iter().filter(|arg| *arg == "--").count(), 1);
+    }
+
+    #[test]
+    fn test_negated_flags_override_the_configuration() {
+        let config = config(CONFIG);
+        let args = apply(&config, &["git-bench", "run", "--no-auto-push"]);
+        assert!(!args.contains(&"--auto-push".to_string()));
+
+        let matches = with_negations(cli())
+            .try_get_matches_from(["git-bench", "run", "--auto-push", "--no-auto-push"])
+            .unwrap();
+        let run = matches.subcommand_matches("run").unwrap();
+        assert!(!run.get_flag("auto_push"));
+        assert!(with_negations(cli())
+            .try_get_matches_from(["git-bench", "run", "--no-verbose"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_settings() {
+        assert!(config(CONFIG).validate(&cli()).is_ok());
+
+        let err = config("[suites.x]\nalert_treshold = \"1x\"")
+            .validate(&cli())
+            .unwrap_err();
+        assert!(err.to_string().contains("`suites.x.alert_treshold`"));
+    }
+
+    #[test]
+    fn test_load_from_cargo_metadata() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(Config::load(None, dir.path()).unwrap(), None);
+
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = []\n\n[workspace.metadata.git-bench]\nalert_threshold = \"120%\"\n",
+        )
+        .unwrap();
+        let loaded = Config::load(None, dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.defaults["alert_threshold"].as_str(), Some("120%"));
+        assert!(loaded.source.ends_with("[workspace.metadata.git-bench]"));
+
+        // git-bench.toml takes precedence over Cargo.toml
+        std::fs::write(dir.path().join(CONFIG_FILE), "alert_threshold = \"130%\"\n").unwrap();
+        let loaded = Config::load(None, dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.defaults["alert_threshold"].as_str(), Some("130%"));
+    }
+}
@@ -0,0 +1,548 @@
+//! Checks of the local setup for `git-bench doctor`
+//!
+//! Looks at the data file, the project configuration, the git repository and
+//! its gh-pages branch, and the GitHub token. Every problem comes with a
+//! suggestion on how to fix it.
+
+use crate::config::Config;
+use crate::git::read_from_gh_pages;
+use crate::github::{parse_github_repo, GitHubActionsEnv, GitHubClient};
+use crate::validation::{
+    validate_alert_threshold, validate_branch_name, validate_data_json, validate_dir_writable,
+    validate_file_exists, validate_github_token, validate_max_items,
+};
+use git2::Repository;
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+use toml::Table;
+
+/// Outcome of a check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    /// Works, but probably not as intended
+    Warning,
+    /// Will make some command fail
+    Error,
+    /// Could not be checked
+    Skipped,
+}
+
+/// One finding
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Check {
+    pub section: &'static str,
+    pub status: Status,
+    pub message: String,
+    /// How to fix the problem
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn new(section: &'static str, status: Status, message: impl Into<String>) -> Self {
+        Self {
+            section,
+            status,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// What to check
+pub struct DoctorOptions<'a> {
+    pub data_file: &'a Path,
+    /// Explicit configuration file (`--config`)
+    pub config: Option<&'a Path>,
+    pub repo_path: &'a Path,
+    pub gh_pages_branch: &'a str,
+    pub data_dir: &'a str,
+    pub remote: &'a str,
+    pub github_token: Option<&'a str>,
+    /// Skip the checks that need the network
+    pub offline: bool,
+}
+
+/// Run every check; `cli` is the command the configuration keys must belong to
+pub fn run_checks(options: &DoctorOptions, cli: &clap::Command) -> Vec<Check> {
+    let mut checks = check_data_file(options.data_file);
+    checks.extend(check_config(options.config, options.repo_path, cli));
+    checks.extend(check_repository(options));
+    checks.extend(check_token(options));
+    checks
+}
+
+fn check_data_file(path: &Path) -> Vec<Check> {
+    const SECTION: &str = "Data file";
+    if !path.exists() {
+        return vec![Check::new(
+            SECTION,
+            Status::Skipped,
+            format!("{} does not exist yet", path.display()),
+        )
+        .fix("`git-bench store` or `git-bench run` creates it")];
+    }
+    if let Err(e) = validate_file_exists(path, "Data file") {
+        return vec![Check::new(SECTION, Status::Error, e.to_string())
+            .fix("Point --data-file at a JSON file")];
+    }
+
+    let mut checks = Vec::new();
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    if let Err(e) = validate_dir_writable(dir, "Data file directory") {
+        checks.push(
+            Check::new(SECTION, Status::Error, e.to_string())
+                .fix("`git-bench store` rewrites the data file there; fix the permissions"),
+        );
+    }
+
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            return vec![Check::new(
+                SECTION,
+                Status::Error,
+                format!("Cannot read {}: {}", path.display(), e),
+            )
+            .fix("Check the path given with --data-file and its permissions")];
+        }
+    };
+
+    checks.extend(data_checks(SECTION, &path.display().to_string(), &content));
+    checks
+}
+
+/// Findings for the content of a data file named `source`
+fn data_checks(section: &'static str, source: &str, content: &str) -> Vec<Check> {
+    let issues = validate_data_json(content);
+    if issues.is_empty() {
+        let runs = serde_json::from_str::<git_bench_core::BenchmarkData>(content)
+            .map(|data| {
+                (
+                    data.entries.len(),
+                    data.entries.values().map(Vec::len).sum(),
+                )
+            })
+            .unwrap_or((0, 0usize));
+        return vec![Check::new(
+            section,
+            Status::Ok,
+            format!("{}: {} suites, {} runs", source, runs.0, runs.1),
+        )];
+    }
+    issues
+        .into_iter()
+        .map(|issue| {
+            Check::new(
+                section,
+                Status::Error,
+                format!("{}: {}", source, issue.message),
+            )
+            .fix(issue.fix)
+        })
+        .collect()
+}
+
+fn check_config(explicit: Option<&Path>, dir: &Path, cli: &clap::Command) -> Vec<Check> {
+    const SECTION: &str = "Configuration";
+    let config = match Config::load(explicit, dir) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            return vec![Check::new(
+                SECTION,
+                Status::Ok,
+                "No configuration file; using the command line defaults",
+            )]
+        }
+        Err(e) => {
+            return vec![Check::new(SECTION, Status::Error, format!("{:#}", e))
+                .fix("Fix the TOML syntax; `git-bench config show` prints what was understood")]
+        }
+    };
+
+    let mut checks = Vec::new();
+    if let Err(e) = config.validate(cli) {
+        checks.push(Check::new(SECTION, Status::Error, e.to_string()).fix(
+            "Settings are named like the command flags, with `_` for `-` (`alert_threshold`)",
+        ));
+    }
+
+    // Values the commands could not take as flags (e.g. a table for a boolean)
+    let mut apply_errors: Vec<String> = cli
+        .get_subcommands()
+        .filter_map(|command| {
+            let argv = vec!["git-bench".into(), command.get_name().into()];
+            config.apply(argv, cli).err().map(|e| e.to_string())
+        })
+        .collect();
+    apply_errors.sort();
+    apply_errors.dedup();
+    for error in apply_errors {
+        checks.push(Check::new(SECTION, Status::Error, error).fix("Correct the setting's type"));
+    }
+
+    let tables = [
+        (String::new(), &config.defaults),
+        ("deploy.".to_string(), &config.deploy),
+    ]
+    .into_iter()
+    .chain(
+        config
+            .suites
+            .iter()
+            .map(|(name, suite)| (format!("suites.{}.", name), suite)),
+    );
+    for (prefix, table) in tables {
+        for (key, problem) in setting_problems(table) {
+            checks.push(
+                Check::new(
+                    SECTION,
+                    Status::Error,
+                    format!("`{}{}` in {}: {}", prefix, key, config.source, problem),
+                )
+                .fix(format!("Correct `{}{}`", prefix, key)),
+            );
+        }
+    }
+
+    if checks.is_empty() {
+        checks.push(Check::new(
+            SECTION,
+            Status::Ok,
+            format!("{} is valid", config.source),
+        ));
+    }
+    checks
+}
+
+/// Settings whose value the commands would reject
+fn setting_problems(table: &Table) -> Vec<(String, String)> {
+    let mut problems = Vec::new();
+    for (key, value) in table {
+        let result = match (key.as_str(), value) {
+            ("alert_threshold" | "fail_threshold", toml::Value::String(threshold)) => {
+                validate_alert_threshold(threshold)
+            }
+            ("gh_pages_branch", toml::Value::String(branch)) => validate_branch_name(branch),
+            ("max_items_in_chart" | "max_items", toml::Value::Integer(max)) => {
+                validate_max_items(Some(usize::try_from(*max).unwrap_or(0)))
+            }
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            problems.push((key.clone(), e.to_string()));
+        }
+    }
+    problems
+}
+
+fn check_repository(options: &DoctorOptions) -> Vec<Check> {
+    const SECTION: &str = "Repository";
+    let repo = match Repository::discover(options.repo_path) {
+        Ok(repo) => repo,
+        Err(_) => {
+            return vec![
+                Check::new(SECTION, Status::Error, "Not inside a git repository")
+                    .fix("Run git-bench from the repository you benchmark"),
+            ]
+        }
+    };
+
+    let mut checks = Vec::new();
+    let config = repo.config().ok();
+    let setting = |key: &str| config.as_ref().and_then(|c| c.get_string(key).ok());
+    match (setting("user.name"), setting("user.email")) {
+        (Some(name), Some(email)) => checks.push(Check::new(
+            SECTION,
+            Status::Ok,
+            format!("git user: {} <{}>", name, email),
+        )),
+        (name, email) => {
+            let missing: Vec<&str> = [("user.name", &name), ("user.email", &email)]
+                .into_iter()
+                .filter(|(_, value)| value.is_none())
+                .map(|(key, _)| key)
+                .collect();
+            checks.push(
+                Check::new(
+                    SECTION,
+                    Status::Warning,
+                    format!(
+                        "git {} not set; gh-pages commits will be made as git-bench",
+                        missing.join(" and ")
+                    ),
+                )
+                .fix(
+                    "git config user.name \"github-actions[bot]\" && \
+                     git config user.email \"github-actions[bot]@users.noreply.github.com\"",
+                ),
+            );
+        }
+    }
+
+    if let Err(e) = validate_branch_name(options.gh_pages_branch) {
+        checks.push(
+            Check::new(SECTION, Status::Error, e.to_string())
+                .fix("Pass a valid branch name with --gh-pages-branch"),
+        );
+        return checks;
+    }
+    if repo.find_remote(options.remote).is_err() {
+        checks.push(
+            Check::new(
+                SECTION,
+                Status::Error,
+                format!("No remote named '{}'", options.remote),
+            )
+            .fix(format!("git remote add {} <url>", options.remote)),
+        );
+        return checks;
+    }
+    if options.offline {
+        checks.push(Check::new(
+            SECTION,
+            Status::Skipped,
+            format!("{} branch not fetched (--offline)", options.gh_pages_branch),
+        ));
+        return checks;
+    }
+
+    let workdir = repo.workdir().unwrap_or_else(|| repo.path());
+    let output = Command::new("git")
+        .args(["ls-remote", "--exit-code", "--heads", options.remote])
+        .arg(options.gh_pages_branch)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .current_dir(workdir)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            checks.push(Check::new(
+                SECTION,
+                Status::Ok,
+                format!(
+                    "{} branch is fetchable from {}",
+                    options.gh_pages_branch, options.remote
+                ),
+            ));
+            let file = format!("{}/data.json", options.data_dir);
+            match read_from_gh_pages(workdir, options.gh_pages_branch, &file, options.remote) {
+                Some(content) => checks.extend(data_checks(
+                    SECTION,
+                    &format!("{}:{}", options.gh_pages_branch, file),
+                    &content,
+                )),
+                None => checks.push(Check::new(
+                    SECTION,
+                    Status::Skipped,
+                    format!("No {} on {} yet", file, options.gh_pages_branch),
+                )),
+            }
+        }
+        // `--exit-code` exits with 2 when nothing matched
+        Ok(output) if output.status.code() == Some(2) => checks.push(
+            Check::new(
+                SECTION,
+                Status::Warning,
+                format!(
+                    "{} has no {} branch yet",
+                    options.remote, options.gh_pages_branch
+                ),
+            )
+            .fix("The first `git-bench run --auto-push` creates it"),
+        ),
+        Ok(output) => checks.push(
+            Check::new(
+                SECTION,
+                Status::Error,
+                format!(
+                    "Cannot fetch from {}: {}",
+                    options.remote,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            )
+            .fix("Check the remote URL (`git remote -v`) and your git credentials"),
+        ),
+        Err(e) => checks.push(
+            Check::new(SECTION, Status::Error, format!("Cannot run git: {}", e))
+                .fix("Install git and make sure it is on the PATH"),
+        ),
+    }
+    checks
+}
+
+fn check_token(options: &DoctorOptions) -> Vec<Check> {
+    const SECTION: &str = "GitHub";
+    let Some(token) = options.github_token else {
+        return vec![Check::new(
+            SECTION,
+            Status::Skipped,
+            "No token (--github-token or GITHUB_TOKEN); only needed for commit comments",
+        )];
+    };
+    if let Err(e) = validate_github_token(token) {
+        return vec![Check::new(SECTION, Status::Error, e.to_string())
+            .fix("Pass the token itself, without quotes or whitespace; in Actions use `${{ secrets.GITHUB_TOKEN }}`")];
+    }
+
+    let remote_url = Repository::discover(options.repo_path)
+        .ok()
+        .and_then(|repo| {
+            let remote = repo.find_remote(options.remote).ok()?;
+            remote.url().map(String::from)
+        });
+    let owner_repo = GitHubActionsEnv::from_env()
+        .get_owner_repo()
+        .or_else(|| remote_url.and_then(|url| parse_github_repo(&url).ok()));
+    let Some((owner, repo)) = owner_repo else {
+        return vec![Check::new(
+            SECTION,
+            Status::Skipped,
+            "Token permissions not checked: the remote is not a GitHub repository",
+        )];
+    };
+    if options.offline {
+        return vec![Check::new(
+            SECTION,
+            Status::Skipped,
+            "Token permissions not checked (--offline)",
+        )];
+    }
+
+    let permissions = GitHubClient::new(Some(token.to_string()))
+        .and_then(|client| client.repository_permissions(&owner, &repo));
+    let check = match permissions {
+        Ok(Some(permissions)) if permissions.push => Check::new(
+            SECTION,
+            Status::Ok,
+            format!("Token can push to {}/{}", owner, repo),
+        ),
+        Ok(Some(_)) => Check::new(
+            SECTION,
+            Status::Error,
+            format!("Token cannot push to {}/{}", owner, repo),
+        )
+        .fix("Give the token write access to Contents (fine-grained) or the `repo` scope (classic)"),
+        Ok(None) => Check::new(
+            SECTION,
+            Status::Ok,
+            format!(
+                "Token accepted for {}/{}; make sure the workflow grants `permissions: contents: write`",
+                owner, repo
+            ),
+        ),
+        Err(e) => Check::new(SECTION, Status::Error, e.to_string())
+            .fix("Create a token with access to the repository, or use the workflow's GITHUB_TOKEN"),
+    };
+    vec![check]
+}
+
+/// Render the findings grouped by section
+pub fn format_checks(checks: &[Check]) -> String {
+    let mut out = String::new();
+    let mut section = "";
+    for check in checks {
+        if check.section != section {
+            if !section.is_empty() {
+                out.push('\n');
+            }
+            section = check.section;
+            out.push_str(section);
+            out.push('\n');
+        }
+        let mark = match check.status {
+            Status::Ok => "✓",
+            Status::Warning => "!",
+            Status::Error => "✗",
+            Status::Skipped => "-",
+        };
+        out.push_str(&format!("  {} {}\n", mark, check.message));
+        if let Some(fix) = &check.fix {
+            out.push_str(&format!("    fix: {}\n", fix));
+        }
+    }
+
+    let count = |status: Status| checks.iter().filter(|c| c.status == status).count();
+    let (errors, warnings) = (count(Status::Error), count(Status::Warning));
+    out.push('\n');
+    out.push_str(&if errors + warnings == 0 {
+        "No problems found".to_string()
+    } else {
+        format!("{} problem(s), {} warning(s)", errors, warnings)
+    });
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn cli() -> clap::Command {
+        clap::Command::new("git-bench").subcommand(
+            clap::Command::new("compare")
+                .arg(clap::Arg::new("alert_threshold").long("alert-threshold")),
+        )
+    }
+
+    #[test]
+    fn test_check_config() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(check_config(None, dir.path(), &cli())[0].status, Status::Ok);
+
+        std::fs::write(
+            dir.path().join("git-bench.toml"),
+            "alert_threshold = \"0%\"\nalert_treshold = \"150%\"\n\n[suites.cargo]\nalert_threshold = \"150%\"\n",
+        )
+        .unwrap();
+        let checks = check_config(None, dir.path(), &cli());
+        let messages: Vec<&str> = checks.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(checks.len(), 2);
+        assert!(messages[0].starts_with("Unknown setting `alert_treshold`"));
+        assert!(messages[1].starts_with("`alert_threshold` in"));
+        assert!(checks
+            .iter()
+            .all(|c| c.status == Status::Error && c.fix.is_some()));
+
+        let cli = cli().subcommand(
+            clap::Command::new("run").arg(
+                clap::Arg::new("offline")
+                    .long("offline")
+                    .action(clap::ArgAction::SetTrue),
+            ),
+        );
+        std::fs::write(dir.path().join("git-bench.toml"), "offline = \"yes\"\n").unwrap();
+        let checks = check_config(None, dir.path(), &cli);
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].message.starts_with("`offline` in"));
+        assert!(checks[0].message.ends_with("must be true or false"));
+    }
+
+    #[test]
+    fn test_check_data_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.json");
+        assert_eq!(check_data_file(&path)[0].status, Status::Skipped);
+
+        std::fs::write(&path, r#"{"entries": {}}"#).unwrap();
+        let checks = check_data_file(&path);
+        assert_eq!(checks[0].status, Status::Ok);
+        assert!(checks[0].message.ends_with("0 suites, 0 runs"));
+
+        std::fs::write(&path, "{\"entries\": NaN}").unwrap();
+        let output = format_checks(&check_data_file(&path));
+        assert!(output.starts_with("Data file\n  ✗ "));
+        assert!(output.contains("    fix: Restore the file"));
+        assert!(output.ends_with("\n1 problem(s), 0 warning(s)\n"));
+    }
+}
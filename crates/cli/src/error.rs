@@ -30,6 +30,12 @@ pub enum Error {
         source: std::io::Error,
     },
 
+    #[error("Benchmark run failed: {0}")]
+    Bench(String),
+
+    #[error("{0}")]
+    Validation(String),
+
     #[error("{0}")]
     Other(String),
 }
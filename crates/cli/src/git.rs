@@ -2,9 +2,13 @@
 
 use crate::error::{Error, Result};
 use chrono::{TimeZone, Utc};
-use git2::{BranchType, Repository};
-use git_bench_core::{AuthorInfo, BenchmarkData, CommitInfo};
-use std::path::Path;
+use git2::{BranchType, Repository, Sort};
+use git_bench_core::{
+    record_suspicious_threshold, AuthorInfo, BenchmarkData, CommitInfo, Threshold,
+    DEFAULT_SUSPICIOUS_SPEEDUP,
+};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 /// Try to extract GitHub username from email or git name
 fn extract_github_username(email: &Option<String>, name: &str) -> Option<String> {
@@ -107,6 +111,161 @@ pub fn get_commit_info(repo_path: &Path, commit_ref: Option<&str>) -> Result<Com
     })
 }
 
+/// A git reference resolved to a commit with stored benchmark data
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkedCommit {
+    /// Commit the reference points to
+    pub commit_id: String,
+    /// Nearest commit (the reference itself or an ancestor) with stored data
+    pub benchmarked_id: String,
+    /// Number of commits walked back to find it (0 for an exact match)
+    pub distance: usize,
+}
+
+/// How many commits [`find_benchmarked_commit`] walks back at most
+pub const MAX_ANCESTOR_DISTANCE: usize = 1000;
+
+/// Resolve a reference (SHA, short SHA, tag or branch name) and walk its history
+/// until a commit for which `has_data` returns true. Returns `None` when none of
+/// the [`MAX_ANCESTOR_DISTANCE`] nearest ancestors has data.
+pub fn find_benchmarked_commit(
+    repo_path: &Path,
+    reference: &str,
+    has_data: impl Fn(&str) -> bool,
+) -> Result<Option<BenchmarkedCommit>> {
+    let repo = Repository::open(repo_path)?;
+    let commit = repo.revparse_single(reference)?.peel_to_commit()?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    revwalk.push(commit.id())?;
+
+    for (distance, oid) in revwalk.enumerate().take(MAX_ANCESTOR_DISTANCE + 1) {
+        let oid = oid?.to_string();
+        if has_data(&oid) {
+            return Ok(Some(BenchmarkedCommit {
+                commit_id: commit.id().to_string(),
+                benchmarked_id: oid,
+                distance,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// List the commits after `good` up to and including `bad`, oldest first,
+/// following first parents. Fails unless `good` is an ancestor of `bad`.
+pub fn commits_between(repo_path: &Path, good: &str, bad: &str) -> Result<Vec<String>> {
+    let repo = Repository::open(repo_path)?;
+    let good_id = repo.revparse_single(good)?.peel_to_commit()?.id();
+    let bad_id = repo.revparse_single(bad)?.peel_to_commit()?.id();
+
+    if good_id == bad_id || !repo.graph_descendant_of(bad_id, good_id)? {
+        return Err(Error::Other(format!(
+            "'{}' is not an ancestor of '{}'",
+            good, bad
+        )));
+    }
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    revwalk.simplify_first_parent()?;
+    revwalk.push(bad_id)?;
+    revwalk.hide(good_id)?;
+
+    revwalk
+        .map(|oid| Ok(oid?.to_string()))
+        .collect::<Result<Vec<_>>>()
+}
+
+/// Ids of all commits pointed to by a tag
+pub fn tagged_commits(repo_path: &Path) -> Result<HashSet<String>> {
+    let repo = Repository::open(repo_path)?;
+    let names = repo.tag_names(None)?;
+
+    let mut commits = HashSet::new();
+    for name in names.iter().flatten() {
+        let commit = repo
+            .revparse_single(&format!("refs/tags/{}", name))?
+            .peel_to_commit()?;
+        commits.insert(commit.id().to_string());
+    }
+    Ok(commits)
+}
+
+/// A detached `git worktree`, removed again when dropped
+pub struct Worktree {
+    repo_path: PathBuf,
+    path: PathBuf,
+}
+
+impl Worktree {
+    /// Add a detached worktree at `path` with `commit` checked out
+    pub fn add(repo_path: &Path, path: &Path, commit: &str) -> Result<Self> {
+        run_git(
+            repo_path,
+            &[
+                "worktree",
+                "add",
+                "--detach",
+                &path.to_string_lossy(),
+                commit,
+            ],
+        )?;
+        Ok(Self {
+            repo_path: repo_path.to_path_buf(),
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Directory of the worktree
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Check out another commit, keeping build artifacts around
+    pub fn checkout(&self, commit: &str) -> Result<()> {
+        run_git(&self.path, &["checkout", "--quiet", "--detach", commit])
+    }
+}
+
+impl Drop for Worktree {
+    fn drop(&mut self) {
+        let removed = run_git(
+            &self.repo_path,
+            &[
+                "worktree",
+                "remove",
+                "--force",
+                &self.path.to_string_lossy(),
+            ],
+        );
+        if let Err(e) = removed {
+            tracing::warn!("Failed to remove worktree {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Run a git command, failing with its stderr
+fn run_git(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| Error::Other(format!("Failed to run git {}: {}", args[0], e)))?;
+
+    if !output.status.success() {
+        return Err(Error::Other(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(())
+}
+
 /// Check out a branch
 pub fn checkout_branch(repo_path: &Path, branch_name: &str) -> Result<()> {
     let repo = Repository::open(repo_path)?;
@@ -179,6 +338,8 @@ fn collect_dir_files_recursive(
     Ok(())
 }
 
+static DEFAULT_SUSPICIOUS_THRESHOLD: Threshold = Threshold::Ratio(DEFAULT_SUSPICIOUS_SPEEDUP);
+
 /// Configuration for GitHub Pages deployment
 pub struct GhPagesConfig<'a> {
     pub branch: &'a str,
@@ -186,6 +347,10 @@ pub struct GhPagesConfig<'a> {
     pub remote: &'a str,
     pub skip_fetch: bool,
     pub dashboard_dir: Option<&'a Path>,
+    /// Benchmark renames (old name, new name) to record in the deployed data
+    pub aliases: &'a [(String, String)],
+    /// Suspicious-speedup threshold the run was compared with (`None` when disabled)
+    pub suspicious_threshold: Option<&'a Threshold>,
 }
 
 impl Default for GhPagesConfig<'_> {
@@ -196,10 +361,35 @@ impl Default for GhPagesConfig<'_> {
             remote: "origin",
             skip_fetch: false,
             dashboard_dir: None,
+            aliases: &[],
+            suspicious_threshold: Some(&DEFAULT_SUSPICIOUS_THRESHOLD),
         }
     }
 }
 
+/// Fetch the remote gh-pages branch and read a file from it.
+/// Returns `None` if the branch or file doesn't exist.
+pub fn read_from_gh_pages(
+    repo_path: &Path,
+    branch: &str,
+    file_path: &str,
+    remote: &str,
+) -> Option<String> {
+    // Try to fetch the remote branch first
+    let _ = fetch_remote(repo_path, remote, branch);
+
+    let output = std::process::Command::new("git")
+        .args(["show", &format!("{}/{}:{}", remote, branch, file_path)])
+        .current_dir(repo_path)
+        .output()
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
 /// Fetch existing benchmark data from gh-pages branch.
 /// Returns empty data if the branch or file doesn't exist.
 pub fn fetch_data_from_gh_pages(
@@ -208,37 +398,29 @@ pub fn fetch_data_from_gh_pages(
     data_dir: &str,
     remote: &str,
 ) -> BenchmarkData {
-    // Try to fetch the remote branch first
-    let _ = fetch_remote(repo_path, remote, branch);
-
-    // Try to read data.json using git show
-    let file_path = format!("{}/data.json", data_dir);
     let ref_spec = format!("{}/{}", remote, branch);
 
-    let output = std::process::Command::new("git")
-        .args(["show", &format!("{}:{}", ref_spec, file_path)])
-        .current_dir(repo_path)
-        .output();
-
-    match output {
-        Ok(output) if output.status.success() => {
-            let content = String::from_utf8_lossy(&output.stdout);
-            match serde_json::from_str::<BenchmarkData>(&content) {
-                Ok(data) => {
-                    tracing::info!(
-                        "Loaded existing benchmark data from {}: {} entries",
-                        ref_spec,
-                        data.entries.values().map(|v| v.len()).sum::<usize>()
-                    );
-                    data
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to parse existing data.json: {}", e);
-                    BenchmarkData::new()
-                }
+    match read_from_gh_pages(
+        repo_path,
+        branch,
+        &format!("{}/data.json", data_dir),
+        remote,
+    ) {
+        Some(content) => match serde_json::from_str::<BenchmarkData>(&content) {
+            Ok(data) => {
+                tracing::info!(
+                    "Loaded existing benchmark data from {}: {} entries",
+                    ref_spec,
+                    data.entries.values().map(|v| v.len()).sum::<usize>()
+                );
+                data
             }
-        }
-        _ => {
+            Err(e) => {
+                tracing::warn!("Failed to parse existing data.json: {}", e);
+                BenchmarkData::new()
+            }
+        },
+        None => {
             tracing::info!(
                 "No existing data.json found on {} (this is normal for first run)",
                 ref_spec
@@ -351,6 +533,11 @@ pub fn deploy_to_gh_pages(
         git_bench_core::BenchmarkData::new()
     };
 
+    for (from, to) in config.aliases {
+        data.add_alias(from, to);
+    }
+    record_suspicious_threshold(&mut data, suite_name, config.suspicious_threshold);
+
     // Add the new run to the existing data (this preserves history)
     data.add_run(suite_name, new_run.clone(), max_items);
 
@@ -485,6 +672,16 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    fn commit_empty(repo: &Repository, message: &str) -> String {
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &[&parent])
+            .unwrap()
+            .to_string()
+    }
+
     fn init_test_repo() -> TempDir {
         let dir = TempDir::new().unwrap();
         let repo = Repository::init(dir.path()).unwrap();
@@ -509,4 +706,93 @@ mod tests {
         assert_eq!(info.message, "Initial commit");
         assert!(!info.id.is_empty());
     }
+
+    #[test]
+    fn test_find_benchmarked_commit() {
+        let dir = init_test_repo();
+        let repo = Repository::open(dir.path()).unwrap();
+        let benchmarked = repo
+            .head()
+            .unwrap()
+            .peel_to_commit()
+            .unwrap()
+            .id()
+            .to_string();
+        let tagged = commit_empty(&repo, "Second commit");
+        repo.tag_lightweight("v1.0", &repo.revparse_single(&tagged).unwrap(), false)
+            .unwrap();
+        let head = commit_empty(&repo, "Third commit");
+
+        let has_data = |id: &str| id == benchmarked || id == tagged;
+
+        let found = find_benchmarked_commit(dir.path(), "HEAD", has_data)
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.commit_id, head);
+        assert_eq!(found.benchmarked_id, tagged);
+        assert_eq!(found.distance, 1);
+
+        let found = find_benchmarked_commit(dir.path(), "v1.0", has_data)
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.benchmarked_id, tagged);
+        assert_eq!(found.distance, 0);
+
+        let found = find_benchmarked_commit(dir.path(), &head[..7], |id| id == benchmarked)
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.distance, 2);
+
+        assert!(find_benchmarked_commit(dir.path(), "HEAD", |_| false)
+            .unwrap()
+            .is_none());
+        assert!(find_benchmarked_commit(dir.path(), "no-such-ref", has_data).is_err());
+    }
+
+    #[test]
+    fn test_commits_between_and_worktree() {
+        let dir = init_test_repo();
+        let repo = Repository::open(dir.path()).unwrap();
+        let first = repo
+            .head()
+            .unwrap()
+            .peel_to_commit()
+            .unwrap()
+            .id()
+            .to_string();
+        let second = commit_empty(&repo, "Second commit");
+        let third = commit_empty(&repo, "Third commit");
+
+        let commits = commits_between(dir.path(), &first, "HEAD").unwrap();
+        assert_eq!(commits, vec![second.clone(), third.clone()]);
+        assert!(commits_between(dir.path(), &third, &first).is_err());
+        assert!(commits_between(dir.path(), &third, &third).is_err());
+
+        repo.tag_lightweight("v1.0", &repo.revparse_single(&second).unwrap(), false)
+            .unwrap();
+        assert_eq!(
+            tagged_commits(dir.path()).unwrap(),
+            HashSet::from([second.clone()])
+        );
+
+        let worktree_dir = TempDir::new().unwrap();
+        let path = worktree_dir.path().join("bisect");
+        {
+            let worktree = Worktree::add(dir.path(), &path, &second).unwrap();
+            let head = |path: &Path| {
+                Repository::open(path)
+                    .unwrap()
+                    .head()
+                    .unwrap()
+                    .peel_to_commit()
+                    .unwrap()
+                    .id()
+                    .to_string()
+            };
+            assert_eq!(head(worktree.path()), second);
+            worktree.checkout(&first).unwrap();
+            assert_eq!(head(worktree.path()), first);
+        }
+        assert!(!path.exists());
+    }
 }
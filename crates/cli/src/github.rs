@@ -8,7 +8,6 @@ use serde::Deserialize;
 pub struct GitHubClient {
     client: reqwest::blocking::Client,
     token: Option<String>,
-    #[allow(dead_code)]
     api_base: String,
 }
 
@@ -75,6 +74,33 @@ impl GitHubClient {
 
         Ok(response.html_url)
     }
+
+    /// The token's permissions on a repository. GitHub App tokens, such as the
+    /// Actions `GITHUB_TOKEN`, get no permissions in the response (`None`).
+    pub fn repository_permissions(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Option<RepoPermissions>> {
+        let url = format!("{}/repos/{}/{}", self.api_base, owner, repo);
+        let response = self.client.get(&url).send()?;
+        match response.status().as_u16() {
+            401 => Err(Error::GitHub("the token was rejected (401)".to_string())),
+            403 | 404 => Err(Error::GitHub(format!(
+                "{}/{} is not visible with this token ({})",
+                owner,
+                repo,
+                response.status()
+            ))),
+            _ => {
+                let response: RepositoryResponse = response
+                    .error_for_status()
+                    .map_err(|e| Error::GitHub(format!("Failed to read repository: {}", e)))?
+                    .json()?;
+                Ok(response.permissions)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -82,6 +108,19 @@ struct CommentResponse {
     html_url: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct RepositoryResponse {
+    #[serde(default)]
+    permissions: Option<RepoPermissions>,
+}
+
+/// What a token may do in a repository
+#[derive(Debug, Clone, Deserialize)]
+pub struct RepoPermissions {
+    #[serde(default)]
+    pub push: bool,
+}
+
 /// Parse a GitHub repository URL or string into owner and repo
 pub fn parse_github_repo(repo: &str) -> Result<(String, String)> {
     let repo = repo.trim();
@@ -2,20 +2,39 @@
 
 use anyhow::{Context, Result};
 use chrono::Utc;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use tracing::{debug, error, info, warn};
 
 mod alert;
+mod backfill;
+mod bench;
+mod bisect;
+mod config;
+mod doctor;
 mod error;
 mod git;
 mod github;
+mod output;
+mod render;
+mod report;
+mod serve;
+mod tui;
+mod validation;
 
 use alert::AlertConfig;
 use git_bench_core::{
-    compare_with_previous, parse_from_string, BenchmarkData, BenchmarkRun, CompareConfig,
+    check_budgets, compare_runs, compare_with_history, compare_with_history_at,
+    detect_suite_change_points, export_rows, format_export, import_github_action_benchmark,
+    merge_repetitions, parse_from_string, parse_from_string_as, parse_percentage,
+    record_suspicious_threshold, suite_flakiness, BaselineMode, BenchmarkData, BenchmarkPattern,
+    BenchmarkResult, BenchmarkRun, BudgetBaseline, ChangePoint, ChangePointConfig, CommitInfo,
+    CompareConfig, CompareReport, CoveragePolicy, ExportFilter, ExportFormat, Flakiness,
+    FlakinessConfig, OutputFormat, RegressionBudget, Threshold, ThresholdRule,
 };
 use github::{GitHubActionsEnv, GitHubClient};
+use output::OutputMode;
 
 /// git-bench: Continuous benchmarking for cargo projects
 #[derive(Parser, Debug)]
@@ -25,6 +44,14 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Configuration file (defaults to git-bench.toml, then Cargo.toml metadata)
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Print results as text or json (logs always go to stderr)
+    #[arg(long, global = true, default_value = "text")]
+    output: String,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -39,12 +66,185 @@ enum Commands {
     Compare(CompareArgs),
     /// Show benchmark history
     History(HistoryArgs),
+    /// Detect distribution shifts across the stored history
+    Changepoints(ChangepointsArgs),
+    /// Compare two raw benchmark outputs without stored history
+    Diff(DiffArgs),
+    /// Score how noisy each benchmark is over the stored history
+    Flaky(FlakyArgs),
+    /// Find the commit that introduced a regression by benchmarking a commit range
+    Bisect(BisectArgs),
+    /// Benchmark past commits to fill in the history
+    Backfill(BackfillArgs),
+    /// Inspect the project configuration
+    Config(ConfigArgs),
+    /// Export the stored history as CSV, TSV, JSON lines or OpenMetrics
+    Export(ExportArgs),
+    /// Import history recorded by another tool
+    Import(ImportArgs),
+    /// Serve the dashboard locally, reloading it when the data changes
+    Serve(ServeArgs),
+    /// Explore the history interactively in the terminal
+    Tui(TuiArgs),
+    /// Write a self-contained HTML report
+    Report(ReportArgs),
+    /// Check the data file, configuration, repository and token for problems
+    Doctor(DoctorArgs),
+}
+
+/// Regression thresholds shared by the commands that compare benchmarks
+#[derive(Parser, Debug)]
+struct ThresholdArgs {
+    /// Regression alert threshold (e.g. '200%', '1.5x', '+5%', '+20ns', '+5% & +100ns')
+    #[arg(long, default_value = "200%")]
+    alert_threshold: String,
+
+    /// Per-benchmark threshold override, first match wins (e.g. 'parser::*=110%',
+    /// 'io/**=300%:400%', 'experimental::*=ignore')
+    #[arg(long = "threshold-rule", value_name = "PATTERN=VALUE")]
+    threshold_rules: Vec<String>,
+
+    /// Only alert on changes that are statistically significant
+    #[arg(long, default_value = "false")]
+    require_significance: bool,
+
+    /// Significance level (p-value cutoff) for the regression test
+    #[arg(long, default_value = "0.05", value_parser = parse_significance_level)]
+    significance_level: f64,
+}
+
+impl ThresholdArgs {
+    /// Build the comparison configuration, with a fail threshold for commands that have one
+    fn compare_config(&self, fail_threshold: Option<&str>) -> Result<CompareConfig> {
+        Ok(CompareConfig {
+            significance_level: self.significance_level,
+            require_significance: self.require_significance,
+            rules: parse_threshold_rules(&self.threshold_rules)?,
+            ..CompareConfig::from_thresholds(&self.alert_threshold, fail_threshold)
+                .map_err(|e| anyhow::anyhow!("Invalid threshold configuration: {}", e))?
+        })
+    }
+}
+
+/// How `run` and `compare` compare a run against the stored history
+#[derive(Parser, Debug)]
+struct ComparisonArgs {
+    #[command(flatten)]
+    thresholds: ThresholdArgs,
+
+    /// Compare against the median of the last N stored runs instead of the latest run
+    #[arg(long, value_name = "N")]
+    baseline_window: Option<usize>,
+
+    /// With --baseline-window, regressions must also exceed the median by this many MADs
+    #[arg(long, default_value = "3.0")]
+    mad_threshold: f64,
+
+    /// Alert/fail threshold for a group's geometric-mean score, first match wins
+    /// (e.g. 'core/parsing=103%', '**=105%:110%')
+    #[arg(long = "group-threshold", value_name = "PATTERN=VALUE")]
+    group_thresholds: Vec<String>,
+
+    /// Fail when the suite drifts past a limit relative to the stored history,
+    /// e.g. '+10%@30d' (vs 30 days ago), '+5%@v1.0' (vs a tag or commit) or
+    /// '+2ms@30d' (mean slowdown of time benchmarks)
+    #[arg(long = "budget", value_name = "LIMIT@BASELINE")]
+    budgets: Vec<String>,
+
+    /// What to do when benchmarks of the baseline are missing: fail, warn or ignore
+    #[arg(long, default_value = "ignore")]
+    missing_benchmarks: String,
+
+    /// Fail when fewer than this percentage of the baseline's benchmarks ran (e.g. '90%')
+    #[arg(long, value_name = "PERCENT", value_parser = parse_min_coverage)]
+    min_coverage: Option<f64>,
+
+    /// Benchmark expected to go away, as a glob or 're:' regex pattern like
+    /// --threshold-rule's (repeatable)
+    #[arg(long = "allow-removed", value_name = "PATTERN")]
+    allow_removed: Vec<String>,
+
+    /// Include change points detected across the stored history in the report
+    #[arg(long, default_value = "false")]
+    change_points: bool,
+
+    /// Report speedups reaching this threshold as improvements (e.g. '1.5x', '+1ms')
+    #[arg(long, value_name = "THRESHOLD")]
+    improvement_threshold: Option<String>,
+
+    /// Flag speedups reaching this threshold as suspicious ('off' disables)
+    #[arg(long, default_value = "10x", value_name = "THRESHOLD")]
+    suspicious_improvement_threshold: String,
+
+    /// Record that benchmark OLD was renamed to NEW (e.g. one of the report's
+    /// "possible renames"), so that its history carries over; repeatable
+    #[arg(long = "accept-rename", num_args = 2, value_names = ["OLD", "NEW"])]
+    accept_renames: Vec<String>,
+
+    /// Score each benchmark's flakiness over the stored history and show it in the report
+    #[arg(long, default_value = "false")]
+    flakiness: bool,
+
+    /// Benchmarks with at least this flakiness score (0-1) only alert, never fail
+    /// (implies --flakiness)
+    #[arg(long, value_name = "SCORE", value_parser = parse_flakiness_score)]
+    flaky_warn_only: Option<f64>,
+}
+
+impl ComparisonArgs {
+    /// Build the comparison configuration for `suite`, whose budgets resolve
+    /// their baselines in `data`
+    fn compare_config(
+        &self,
+        data: &BenchmarkData,
+        suite: &str,
+        fail_threshold: Option<&str>,
+    ) -> Result<CompareConfig> {
+        Ok(CompareConfig {
+            baseline: baseline_mode(self.baseline_window, self.mad_threshold),
+            group_rules: parse_group_rules(&self.group_thresholds)?,
+            budgets: parse_budgets(data, suite, &self.budgets)?,
+            coverage: coverage_policy(
+                &self.missing_benchmarks,
+                self.min_coverage,
+                &self.allow_removed,
+            )?,
+            flakiness: flakiness_config(self.flakiness, self.flaky_warn_only),
+            improvement_threshold: self
+                .improvement_threshold
+                .as_deref()
+                .map(parse_threshold)
+                .transpose()?,
+            suspicious_improvement_threshold: parse_optional_threshold(
+                &self.suspicious_improvement_threshold,
+            )?,
+            ..self.thresholds.compare_config(fail_threshold)?
+        })
+    }
 }
 
 #[derive(Parser, Debug)]
 struct RunArgs {
+    /// Read benchmark output from this file instead of running `cargo bench`
     #[arg(short, long, value_name = "FILE")]
-    output_file: PathBuf,
+    output_file: Option<PathBuf>,
+
+    /// Also write the output of `cargo bench` to this file
+    #[arg(long, value_name = "FILE", conflicts_with = "output_file")]
+    save_output: Option<PathBuf>,
+
+    /// Format of the benchmark output: auto, libtest or criterion
+    #[arg(long, default_value = "auto")]
+    parser: String,
+
+    /// Arguments passed through to `cargo bench` (e.g. `-- -p core --bench parse`)
+    #[arg(last = true, value_name = "CARGO_ARGS")]
+    cargo_args: Vec<String>,
+
+    /// Shell command that runs the benchmarks instead of `cargo bench`, with the
+    /// passthrough arguments appended (e.g. 'make bench')
+    #[arg(long, value_name = "COMMAND")]
+    bench_command: Option<String>,
 
     #[arg(short, long, default_value = "cargo")]
     name: String,
@@ -76,8 +276,8 @@ struct RunArgs {
     #[arg(long, default_value = "true")]
     save_data_file: bool,
 
-    #[arg(long, default_value = "200%")]
-    alert_threshold: String,
+    #[command(flatten)]
+    comparison: ComparisonArgs,
 
     #[arg(long, default_value = "false")]
     comment_on_alert: bool,
@@ -107,6 +307,10 @@ struct StoreArgs {
     #[arg(short, long, value_name = "FILE")]
     output_file: PathBuf,
 
+    /// Format of the benchmark output: auto, libtest or criterion
+    #[arg(long, default_value = "auto")]
+    parser: String,
+
     #[arg(short, long, default_value = "cargo")]
     name: String,
 
@@ -122,8 +326,22 @@ struct StoreArgs {
 
 #[derive(Parser, Debug)]
 struct CompareArgs {
-    #[arg(short, long, value_name = "FILE")]
-    output_file: PathBuf,
+    /// Fresh benchmark output to compare (not needed with --base/--head)
+    #[arg(short, long, value_name = "FILE", required_unless_present = "base")]
+    output_file: Option<PathBuf>,
+
+    /// Format of the benchmark output: auto, libtest or criterion
+    #[arg(long, default_value = "auto")]
+    parser: String,
+
+    /// Stored run to compare against: a commit SHA, short SHA, tag or branch.
+    /// Falls back to the nearest benchmarked ancestor.
+    #[arg(long, value_name = "REF")]
+    base: Option<String>,
+
+    /// Stored run to compare instead of an output file (defaults to HEAD with --base)
+    #[arg(long, value_name = "REF", conflicts_with = "output_file")]
+    head: Option<String>,
 
     #[arg(long, default_value = "benchmark-data.json")]
     data_file: PathBuf,
@@ -131,11 +349,27 @@ struct CompareArgs {
     #[arg(short, long, default_value = "cargo")]
     name: String,
 
-    #[arg(long, default_value = "200%")]
-    alert_threshold: String,
+    #[command(flatten)]
+    comparison: ComparisonArgs,
 
     #[arg(long, default_value = "markdown")]
     format: String,
+
+    /// Order of the text table: severity, change or name
+    #[arg(long, default_value = "severity")]
+    sort_by: String,
+
+    /// Group the text table by benchmark hierarchy
+    #[arg(long, default_value = "false")]
+    group: bool,
+
+    /// Colour the text table: auto (only on a terminal), always or never
+    #[arg(long, default_value = "auto")]
+    color: String,
+
+    /// Number of stored values in each sparkline of the text table (0 hides them)
+    #[arg(long, default_value = "20")]
+    sparkline: usize,
 }
 
 #[derive(Parser, Debug)]
@@ -150,8 +384,359 @@ struct HistoryArgs {
     limit: usize,
 }
 
+#[derive(Parser, Debug)]
+struct ChangepointsArgs {
+    #[arg(long, default_value = "benchmark-data.json")]
+    data_file: PathBuf,
+
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// Minimum confidence (0-1) for a shift to be reported
+    #[arg(long, default_value = "0.95")]
+    confidence: f64,
+
+    /// Minimum number of runs on each side of a change point
+    #[arg(long, default_value = "3")]
+    min_segment: usize,
+
+    /// Minimum absolute change of the mean, in percent
+    #[arg(long, default_value = "1.0")]
+    min_change: f64,
+
+    #[arg(long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Parser, Debug)]
+struct DiffArgs {
+    /// Before and after outputs (`git-bench diff before.txt after.txt`)
+    #[arg(value_name = "FILE", num_args = 0..=2)]
+    files: Vec<PathBuf>,
+
+    /// Additional "before" output; repeat to average repetitions
+    #[arg(short, long = "before", value_name = "FILE")]
+    before: Vec<PathBuf>,
+
+    /// Additional "after" output; repeat to average repetitions
+    #[arg(short, long = "after", value_name = "FILE")]
+    after: Vec<PathBuf>,
+
+    #[command(flatten)]
+    thresholds: ThresholdArgs,
+
+    /// Alert/fail threshold for a group's geometric-mean score, first match wins
+    /// (e.g. 'core/parsing=103%', '**=105%:110%')
+    #[arg(long = "group-threshold", value_name = "PATTERN=VALUE")]
+    group_thresholds: Vec<String>,
+
+    /// Output format: text, markdown or json
+    #[arg(long, default_value = "text")]
+    format: String,
+
+    /// Order of the text table: severity, change or name
+    #[arg(long, default_value = "severity")]
+    sort_by: String,
+
+    /// Group the text table by benchmark hierarchy
+    #[arg(long, default_value = "false")]
+    group: bool,
+
+    /// Colour the text table: auto (only on a terminal), always or never
+    #[arg(long, default_value = "auto")]
+    color: String,
+}
+
+#[derive(Parser, Debug)]
+struct FlakyArgs {
+    #[arg(long, default_value = "benchmark-data.json")]
+    data_file: PathBuf,
+
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// Minimum number of stored runs for a benchmark to be scored
+    #[arg(long, default_value = "5")]
+    min_runs: usize,
+
+    /// Threshold used to count the runs that would have alerted
+    #[arg(long, default_value = "200%")]
+    alert_threshold: String,
+
+    /// Per-benchmark threshold override, first match wins
+    #[arg(long = "threshold-rule", value_name = "PATTERN=VALUE")]
+    threshold_rules: Vec<String>,
+
+    /// Sort by: score, cv, flips, alerts or name
+    #[arg(long, default_value = "score")]
+    sort: String,
+
+    /// Only show the N flakiest benchmarks per suite
+    #[arg(long)]
+    limit: Option<usize>,
+
+    #[arg(long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Parser, Debug)]
+struct BisectArgs {
+    /// Last known good commit (SHA, tag or branch)
+    #[arg(long, value_name = "REF")]
+    good: String,
+
+    /// First known bad commit
+    #[arg(long, value_name = "REF", default_value = "HEAD")]
+    bad: String,
+
+    /// Benchmark to bisect, by its full name (also passed to the harness as a filter)
+    #[arg(long, value_name = "NAME")]
+    bench: String,
+
+    /// Run the benchmark this many times per commit and average the results
+    #[arg(long, default_value = "1")]
+    repetitions: usize,
+
+    #[command(flatten)]
+    thresholds: ThresholdArgs,
+
+    /// Store every measured run in the data file
+    #[arg(long, default_value = "false")]
+    store: bool,
+
+    #[arg(long, default_value = "benchmark-data.json")]
+    data_file: PathBuf,
+
+    #[arg(short, long, default_value = "cargo")]
+    name: String,
+
+    #[arg(long, default_value = "text")]
+    format: String,
+
+    /// Arguments passed through to `cargo bench` (e.g. `-- -p core --bench parse`)
+    #[arg(last = true, value_name = "CARGO_ARGS")]
+    cargo_args: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct BackfillArgs {
+    /// Commits to benchmark, `FROM..TO` like `git log` (FROM itself is excluded)
+    #[arg(long, value_name = "FROM..TO")]
+    range: String,
+
+    /// Only benchmark every Nth commit (the newest is always included)
+    #[arg(long, value_name = "N", conflicts_with = "tags_only")]
+    every: Option<usize>,
+
+    /// Only benchmark tagged commits
+    #[arg(long, default_value = "false")]
+    tags_only: bool,
+
+    /// Number of commits benchmarked at the same time, each in its own worktree.
+    /// Parallel runs compete for the CPU, so their numbers are noisier.
+    #[arg(short, long, default_value = "1")]
+    jobs: usize,
+
+    /// Retry commits that failed to benchmark in an earlier backfill
+    #[arg(long, default_value = "false")]
+    retry_skipped: bool,
+
+    #[arg(long, default_value = "benchmark-data.json")]
+    data_file: PathBuf,
+
+    /// Where skipped commits are recorded (defaults to `<data file>.backfill.json`)
+    #[arg(long)]
+    state_file: Option<PathBuf>,
+
+    #[arg(short, long, default_value = "cargo")]
+    name: String,
+
+    /// Arguments passed through to `cargo bench` (e.g. `-- -p core --bench parse`)
+    #[arg(last = true, value_name = "CARGO_ARGS")]
+    cargo_args: Vec<String>,
+
+    /// Shell command that runs the benchmarks instead of `cargo bench`, with the
+    /// passthrough arguments appended (e.g. 'make bench')
+    #[arg(long, value_name = "COMMAND")]
+    bench_command: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ExportArgs {
+    /// Output format: csv, tsv, jsonl or openmetrics
+    #[arg(long, default_value = "csv")]
+    format: String,
+
+    /// Write to this file instead of stdout (`--output` is the global output mode)
+    #[arg(short = 'o', long, value_name = "FILE")]
+    out_file: Option<PathBuf>,
+
+    #[arg(long, default_value = "benchmark-data.json")]
+    data_file: PathBuf,
+
+    /// Only export this suite (repeatable)
+    #[arg(short, long = "name", value_name = "SUITE")]
+    names: Vec<String>,
+
+    /// Only export benchmarks matching this glob or 're:' regex pattern, like
+    /// --threshold-rule's (repeatable)
+    #[arg(long = "bench", value_name = "PATTERN")]
+    benches: Vec<String>,
+
+    /// Only export runs from this date on (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_name = "DATE")]
+    since: Option<String>,
+
+    /// Only export runs up to this date, inclusive (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_name = "DATE")]
+    until: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ImportArgs {
+    /// File to import; read from the gh-pages branch when omitted
+    #[arg(value_name = "FILE")]
+    file: Option<PathBuf>,
+
+    /// Tool that recorded the history (github-action-benchmark)
+    #[arg(long, default_value = "github-action-benchmark")]
+    from: String,
+
+    #[arg(long, default_value = "benchmark-data.json")]
+    data_file: PathBuf,
+
+    /// Store an imported suite under another name, e.g. 'Rust Benchmark=cargo' (repeatable)
+    #[arg(long = "rename-suite", value_name = "FROM=TO")]
+    rename_suites: Vec<String>,
+
+    /// Replace stored runs of the same commits instead of keeping them
+    #[arg(long, default_value = "false")]
+    overwrite: bool,
+
+    #[arg(long, default_value = "gh-pages")]
+    gh_pages_branch: String,
+
+    /// Directory of data.js on the gh-pages branch
+    #[arg(long, default_value = "dev/bench")]
+    benchmark_data_dir_path: String,
+}
+
+#[derive(Parser, Debug)]
+struct ServeArgs {
+    /// Built dashboard directory (see `make build`)
+    #[arg(long, default_value = "dist")]
+    dashboard_dir: PathBuf,
+
+    #[arg(long, default_value = "benchmark-data.json")]
+    data_file: PathBuf,
+
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    #[arg(short, long, default_value = "8080")]
+    port: u16,
+}
+
+#[derive(Parser, Debug)]
+struct TuiArgs {
+    #[arg(long, default_value = "benchmark-data.json")]
+    data_file: PathBuf,
+
+    /// Suite to open initially
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// Threshold for alerts when comparing two commits
+    #[arg(long, default_value = "200%")]
+    alert_threshold: String,
+}
+
+#[derive(Parser, Debug)]
+struct ReportArgs {
+    /// HTML file to write
+    #[arg(long, value_name = "FILE")]
+    html: PathBuf,
+
+    #[arg(long, default_value = "benchmark-data.json")]
+    data_file: PathBuf,
+
+    /// Only report this suite
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// Number of recent runs charted per suite
+    #[arg(long, default_value = "30")]
+    runs: usize,
+
+    /// Threshold for alerts in the latest comparison
+    #[arg(long, default_value = "200%")]
+    alert_threshold: String,
+}
+
+#[derive(Parser, Debug)]
+struct DoctorArgs {
+    #[arg(long, default_value = "benchmark-data.json")]
+    data_file: PathBuf,
+
+    #[arg(long, default_value = "gh-pages")]
+    gh_pages_branch: String,
+
+    #[arg(long, default_value = "dev/bench")]
+    benchmark_data_dir_path: String,
+
+    #[arg(long, env = "GITHUB_TOKEN")]
+    github_token: Option<String>,
+
+    /// Skip the checks that need the network (gh-pages fetch, token permissions)
+    #[arg(long, default_value = "false")]
+    offline: bool,
+}
+
+#[derive(Parser, Debug)]
+struct ConfigArgs {
+    #[command(subcommand)]
+    action: ConfigAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Print the resolved configuration
+    Show(ConfigShowArgs),
+}
+
+#[derive(Parser, Debug)]
+struct ConfigShowArgs {
+    /// Show the settings in effect for this suite
+    #[arg(short, long)]
+    suite: Option<String>,
+
+    /// Output format: toml or json
+    #[arg(long, default_value = "toml")]
+    format: String,
+}
+
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let argv: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    let configured = load_config(&argv).and_then(|config| {
+        let argv = match &config {
+            Some(config) => config.apply(argv.clone(), &config::with_negations(Cli::command()))?,
+            None => argv.clone(),
+        };
+        Ok((config, argv))
+    });
+    let (config, argv) = match configured {
+        Ok(configured) => configured,
+        Err(e) => {
+            // doctor runs without the configuration and reports its problems itself
+            let matches = config::with_negations(Cli::command()).try_get_matches_from(&argv);
+            match matches {
+                Ok(matches) if matches.subcommand_name() == Some("doctor") => (None, argv),
+                _ => return Err(e),
+            }
+        }
+    };
+    let matches = config::with_negations(Cli::command()).get_matches_from(argv);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     let log_level = if cli.verbose { "debug" } else { "info" };
     tracing_subscriber::fmt()
@@ -159,17 +744,155 @@ fn main() -> Result<()> {
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(log_level)),
         )
+        .with_writer(std::io::stderr)
         .init();
 
+    let output: OutputMode = cli.output.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+    // Commands with their own --format follow --output json
+    let format = |format: String| {
+        if output.is_json() {
+            "json".to_string()
+        } else {
+            format
+        }
+    };
+
+    if output.is_json()
+        && matches!(
+            cli.command,
+            Commands::Export(_) | Commands::Serve(_) | Commands::Tui(_) | Commands::Report(_)
+        )
+    {
+        anyhow::bail!(
+            "`{}` has no JSON output; drop --output json",
+            matches.subcommand_name().unwrap_or_default()
+        );
+    }
+
     match cli.command {
-        Commands::Run(args) => run_command(args),
-        Commands::Store(args) => store_command(args),
-        Commands::Compare(args) => compare_command(args),
-        Commands::History(args) => history_command(args),
+        Commands::Run(args) => run_command(args, output),
+        Commands::Store(args) => store_command(args, output),
+        Commands::Compare(args) => compare_command(CompareArgs {
+            format: format(args.format),
+            ..args
+        }),
+        Commands::History(args) => history_command(args, output),
+        Commands::Changepoints(args) => changepoints_command(ChangepointsArgs {
+            format: format(args.format),
+            ..args
+        }),
+        Commands::Diff(args) => diff_command(DiffArgs {
+            format: format(args.format),
+            ..args
+        }),
+        Commands::Flaky(args) => flaky_command(FlakyArgs {
+            format: format(args.format),
+            ..args
+        }),
+        Commands::Bisect(args) => bisect_command(BisectArgs {
+            format: format(args.format),
+            ..args
+        }),
+        Commands::Backfill(args) => backfill_command(args, output),
+        Commands::Export(args) => export_command(args),
+        Commands::Import(args) => import_command(args, output),
+        Commands::Serve(args) => serve_command(args),
+        Commands::Tui(args) => tui_command(args),
+        Commands::Report(args) => report_command(args),
+        Commands::Doctor(args) => doctor_command(args, cli.config.as_deref(), output),
+        Commands::Config(args) => match args.action {
+            ConfigAction::Show(show) => config_show_command(
+                config,
+                ConfigShowArgs {
+                    format: format(show.format),
+                    ..show
+                },
+            ),
+        },
+    }
+}
+
+/// Load the configuration, honouring `--config` before the arguments are parsed
+fn load_config(argv: &[std::ffi::OsString]) -> Result<Option<config::Config>> {
+    // Arguments after `--` are passed through, not ours
+    let argv = match argv.iter().position(|arg| arg == "--") {
+        Some(end) => &argv[..end],
+        None => argv,
+    };
+    let explicit = argv.iter().enumerate().find_map(|(i, arg)| {
+        let arg = arg.to_string_lossy();
+        match arg.strip_prefix("--config") {
+            Some("") => argv.get(i + 1).map(PathBuf::from),
+            Some(path) => path.strip_prefix('=').map(PathBuf::from),
+            None => None,
+        }
+    });
+    let config = config::Config::load(explicit.as_deref(), &std::env::current_dir()?)?;
+    if let Some(config) = &config {
+        config.validate(&Cli::command())?;
+    }
+    Ok(config)
+}
+
+fn config_show_command(config: Option<config::Config>, args: ConfigShowArgs) -> Result<()> {
+    let Some(config) = config else {
+        let message = format!(
+            "No configuration found (looked for {} and [package.metadata.git-bench] or \
+             [workspace.metadata.git-bench] in Cargo.toml)",
+            config::CONFIG_FILE
+        );
+        // An empty document would read as an empty configuration
+        if args.format == "json" {
+            anyhow::bail!(message);
+        }
+        println!("{}", message);
+        return Ok(());
+    };
+
+    let table = match &args.suite {
+        Some(suite) => {
+            if !config.suites.contains_key(suite) {
+                anyhow::bail!("Suite '{}' is not configured in {}", suite, config.source);
+            }
+            config.settings(Some(suite))
+        }
+        None => {
+            let mut table = config.defaults.clone();
+            if !config.deploy.is_empty() {
+                table.insert("deploy".to_string(), config.deploy.clone().into());
+            }
+            if !config.suites.is_empty() {
+                let suites: toml::Table = config
+                    .suites
+                    .iter()
+                    .map(|(name, suite)| (name.clone(), suite.clone().into()))
+                    .collect();
+                table.insert("suites".to_string(), suites.into());
+            }
+            table
+        }
+    };
+
+    match args.format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&table)?),
+        _ => {
+            match &args.suite {
+                Some(suite) => println!("# Settings for suite '{}' from {}", suite, config.source),
+                None => println!("# Configuration from {}", config.source),
+            }
+            print!("{}", toml::to_string_pretty(&table)?);
+        }
     }
+    Ok(())
+}
+
+fn parse_output_format(format: &str) -> Result<OutputFormat> {
+    format
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Invalid parser: {}", e))
 }
 
-fn run_command(args: RunArgs) -> Result<()> {
+fn run_command(args: RunArgs, output: OutputMode) -> Result<()> {
     info!("Running benchmark workflow");
 
     let gh_env = GitHubActionsEnv::from_env();
@@ -179,22 +902,42 @@ fn run_command(args: RunArgs) -> Result<()> {
         debug!("Running in GitHub Actions environment");
     }
 
-    info!("Parsing benchmark output from {:?}", args.output_file);
-    let output = std::fs::read_to_string(&args.output_file)
-        .with_context(|| format!("Failed to read benchmark output file: {:?}", args.output_file))?;
-
-    let results = match parse_from_string(&output) {
-        Ok(r) => r,
-        Err(_) => {
-            info!("No benchmark results found in output, skipping");
-            return Ok(());
+    let bench_output = match &args.output_file {
+        Some(output_file) => {
+            if !args.cargo_args.is_empty() {
+                warn!(
+                    "Ignoring cargo arguments, reading benchmark output from {:?}",
+                    output_file
+                );
+            }
+            info!("Parsing benchmark output from {:?}", output_file);
+            std::fs::read_to_string(output_file).with_context(|| {
+                format!("Failed to read benchmark output file: {:?}", output_file)
+            })?
+        }
+        None => {
+            let command = bench::bench_command(args.bench_command.as_deref(), &args.cargo_args);
+            info!("Running {:?}", command);
+            bench::run_and_capture(command, args.save_output.as_deref(), output.bench_echo())?
         }
     };
 
-    if results.is_empty() {
+    let no_results = || {
         info!("No benchmark results found, skipping");
-        return Ok(());
-    }
+        if output.is_json() {
+            output::print_json(&output::RunOutput {
+                comparison: None,
+                store: None,
+                comment_url: None,
+                failed: false,
+            })?;
+        }
+        Ok(())
+    };
+    let results = match parse_from_string_as(&bench_output, parse_output_format(&args.parser)?) {
+        Ok(r) if !r.is_empty() => r,
+        _ => return no_results(),
+    };
 
     info!("Parsed {} benchmark results", results.len());
 
@@ -230,19 +973,30 @@ fn run_command(args: RunArgs) -> Result<()> {
         })
     };
 
-    let compare_config = CompareConfig::from_percentages(
-        &args.alert_threshold,
-        args.fail_threshold.as_deref(),
-    )
-    .map_err(|e| anyhow::anyhow!("Invalid threshold configuration: {}", e))?;
-
-    let previous_run = data.get_latest_run(&args.name);
-    let report = compare_with_previous(&results, previous_run, &compare_config);
+    let compare_config =
+        args.comparison
+            .compare_config(&data, &args.name, args.fail_threshold.as_deref())?;
+
+    // Accepted renames apply before comparing, so the renamed benchmarks are not missing
+    let accepted_renames = accept_renames(&mut data, &args.comparison.accept_renames);
+    let history = data.resolved();
+    let mut report = compare_with_history(&results, history.get_runs(&args.name), &compare_config);
+    if args.comparison.change_points {
+        report.change_points =
+            detect_suite_change_points(history.get_runs(&args.name), &ChangePointConfig::default());
+    }
 
-    println!("{}", report.summary());
+    if !output.is_json() {
+        println!("{}", report.summary());
+    }
 
     if is_github_actions {
-        print!("{}", alert::format_github_actions_alert(&report));
+        // The runner reads workflow commands from stderr too, keeping stdout parseable
+        if output.is_json() {
+            eprint!("{}", alert::format_github_actions_alert(&report));
+        } else {
+            print!("{}", alert::format_github_actions_alert(&report));
+        }
     }
 
     let alert_config = AlertConfig {
@@ -250,6 +1004,7 @@ fn run_command(args: RunArgs) -> Result<()> {
         alert_comment_cc_users: args.alert_comment_cc_users.clone(),
     };
 
+    let mut comment_url = None;
     if (args.comment_always || (args.comment_on_alert && report.has_alerts()))
         && args.github_token.is_some()
     {
@@ -259,17 +1014,30 @@ fn run_command(args: RunArgs) -> Result<()> {
                 info!("Creating commit comment on GitHub");
                 match create_github_comment(&owner, &repo, &commit.id, &report, &alert_config, token)
                 {
-                    Ok(url) => info!("Created comment: {}", url),
+                    Ok(url) => {
+                        info!("Created comment: {}", url);
+                        comment_url = Some(url);
+                    }
                     Err(e) => warn!("Failed to create comment: {}", e),
                 }
             }
         }
     }
 
+    let mut store = None;
+    let mut deploy_error = None;
     if args.save_data_file {
         let mut commit_with_url = commit.clone();
         commit_with_url.url = gh_env.commit_url(&commit.id);
 
+        let mut stored = output::StoreOutput {
+            suite: args.name.clone(),
+            stored: true,
+            commit: Some(commit.id.clone()),
+            benchmarks: results.len(),
+            data_file: None,
+            deploy: None,
+        };
         let run = BenchmarkRun {
             commit: commit_with_url,
             date: Utc::now(),
@@ -291,6 +1059,8 @@ fn run_command(args: RunArgs) -> Result<()> {
                 remote: "origin",
                 skip_fetch: args.skip_fetch_gh_pages,
                 dashboard_dir: args.dashboard_dir.as_deref(),
+                aliases: &accepted_renames,
+                suspicious_threshold: compare_config.suspicious_improvement_threshold.as_ref(),
             };
 
             match git::deploy_to_gh_pages(
@@ -301,33 +1071,65 @@ fn run_command(args: RunArgs) -> Result<()> {
                 &gh_config,
             ) {
                 Ok(commit_id) => {
-                    if commit_id == "No changes" {
+                    let deployed = if commit_id == "No changes" {
                         info!("No changes to deploy - benchmark data already up to date");
+                        None
                     } else {
                         info!(
                             "Successfully deployed to gh-pages: {}",
                             &commit_id[..7.min(commit_id.len())]
                         );
-                    }
+                        Some(commit_id)
+                    };
+                    stored.deploy = Some(output::DeployOutput {
+                        branch: args.gh_pages_branch.clone(),
+                        commit: deployed,
+                        error: None,
+                    });
                 }
                 Err(e) => {
                     warn!("Failed to deploy to GitHub Pages: {}", e);
+                    stored.deploy = Some(output::DeployOutput {
+                        branch: args.gh_pages_branch.clone(),
+                        commit: None,
+                        error: Some(e.to_string()),
+                    });
                     if args.fail_on_alert {
-                        return Err(e.into());
+                        deploy_error = Some(e);
                     }
                 }
             }
         } else {
             // Local save only (for development/testing)
             data.add_run(&args.name, run, args.max_items_in_chart);
+            record_suspicious_threshold(
+                &mut data,
+                &args.name,
+                compare_config.suspicious_improvement_threshold.as_ref(),
+            );
             data.save_to_file(&data_file)
                 .with_context(|| "Failed to save benchmark data")?;
             info!("Saved benchmark data to {:?}", data_file);
             info!("To view dashboard, use the Dioxus dashboard: cd crates/dashboard && ./build.sh");
+            stored.data_file = Some(data_file);
         }
+        store = Some(stored);
+    }
+
+    let failed = alert::should_fail(&report, &alert_config);
+    if output.is_json() {
+        output::print_json(&output::RunOutput {
+            comparison: Some(output::CompareOutput::new(&report)),
+            store,
+            comment_url,
+            failed,
+        })?;
+    }
+    if let Some(e) = deploy_error {
+        return Err(e.into());
     }
 
-    if alert::should_fail(&report, &alert_config) {
+    if failed {
         error!("Benchmark alert triggered - failing workflow");
         std::process::exit(1);
     }
@@ -335,16 +1137,31 @@ fn run_command(args: RunArgs) -> Result<()> {
     Ok(())
 }
 
-fn store_command(args: StoreArgs) -> Result<()> {
+fn store_command(args: StoreArgs, output: OutputMode) -> Result<()> {
     info!("Storing benchmark results");
 
-    let output = std::fs::read_to_string(&args.output_file)
-        .with_context(|| format!("Failed to read benchmark output file: {:?}", args.output_file))?;
-
-    let results = match parse_from_string(&output) {
+    let bench_output = std::fs::read_to_string(&args.output_file).with_context(|| {
+        format!(
+            "Failed to read benchmark output file: {:?}",
+            args.output_file
+        )
+    })?;
+
+    let mut stored = output::StoreOutput {
+        suite: args.name.clone(),
+        stored: false,
+        commit: None,
+        benchmarks: 0,
+        data_file: None,
+        deploy: None,
+    };
+    let results = match parse_from_string_as(&bench_output, parse_output_format(&args.parser)?) {
         Ok(r) if !r.is_empty() => r,
         _ => {
             info!("No benchmark results found, skipping");
+            if output.is_json() {
+                output::print_json(&stored)?;
+            }
             return Ok(());
         }
     };
@@ -354,6 +1171,8 @@ fn store_command(args: StoreArgs) -> Result<()> {
     let repo_path = std::env::current_dir()?;
     let commit = git::get_commit_info(&repo_path, args.git_ref.as_deref())
         .with_context(|| "Failed to get commit info")?;
+    stored.commit = Some(commit.id.clone());
+    stored.benchmarks = results.len();
 
     let mut data =
         BenchmarkData::load_from_file(&args.data_file).unwrap_or_else(|_| BenchmarkData::new());
@@ -371,68 +1190,93 @@ fn store_command(args: StoreArgs) -> Result<()> {
 
     info!("Stored benchmark data to {:?}", args.data_file);
 
+    if output.is_json() {
+        stored.stored = true;
+        stored.data_file = Some(args.data_file);
+        output::print_json(&stored)?;
+    }
     Ok(())
 }
 
 fn compare_command(args: CompareArgs) -> Result<()> {
-    let output = std::fs::read_to_string(&args.output_file)
-        .with_context(|| format!("Failed to read benchmark output file: {:?}", args.output_file))?;
+    let mut data = BenchmarkData::load_from_file(&args.data_file)
+        .with_context(|| "Failed to load benchmark data")?;
 
-    let results = match parse_from_string(&output) {
-        Ok(r) if !r.is_empty() => r,
-        _ => {
-            info!("No benchmark results found, skipping comparison");
-            return Ok(());
+    let config = args.comparison.compare_config(&data, &args.name, None)?;
+
+    // Accepted renames apply before comparing, so the renamed benchmarks are not missing
+    let accepted_renames = accept_renames(&mut data, &args.comparison.accept_renames);
+    let resolved = data.resolved();
+    let runs = resolved.get_runs(&args.name);
+    // With --base the history ends at the base run, so that the baseline window
+    // ends there too
+    let base_history = match args.base.as_deref() {
+        Some(reference) => {
+            let base = resolve_stored_run(&resolved, &args.name, reference)?;
+            let base_end = runs
+                .iter()
+                .position(|run| std::ptr::eq(run, base))
+                .map_or(runs.len(), |position| position + 1);
+            Some(&runs[..base_end])
         }
+        None => None,
     };
 
-    let data = BenchmarkData::load_from_file(&args.data_file)
-        .with_context(|| "Failed to load benchmark data")?;
-
-    let config = CompareConfig::from_percentages(&args.alert_threshold, None)
-        .map_err(|e| anyhow::anyhow!("Invalid threshold: {}", e))?;
-
-    let previous_run = data.get_latest_run(&args.name);
-    let report = compare_with_previous(&results, previous_run, &config);
+    let (mut report, history) = if let Some(output_file) = &args.output_file {
+        let output = std::fs::read_to_string(output_file)
+            .with_context(|| format!("Failed to read benchmark output file: {:?}", output_file))?;
 
-    match args.format.as_str() {
-        "json" => {
-            let output = serde_json::json!({
-                "comparisons": report.comparisons,
-                "alerts": report.alerts,
-                "failures": report.failures,
-                "new_benchmarks": report.new_benchmarks,
-                "removed_benchmarks": report.removed_benchmarks,
-                "has_alerts": report.has_alerts(),
-                "has_failures": report.has_failures(),
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
-        }
-        "markdown" => {
-            println!("{}", report.summary());
-        }
-        _ => {
-            println!("{}", report.short_summary());
-            for comp in &report.comparisons {
-                let indicator = if comp.is_regression { "↑" } else { "↓" };
-                println!(
-                    "  {} {}: {:.2} {} -> {:.2} {} ({:+.1}%)",
-                    indicator,
-                    comp.name,
-                    comp.previous,
-                    comp.unit,
-                    comp.current,
-                    comp.unit,
-                    comp.percentage_change
-                );
+        let results = match parse_from_string_as(&output, parse_output_format(&args.parser)?) {
+            Ok(r) if !r.is_empty() => r,
+            _ => {
+                info!("No benchmark results found, skipping comparison");
+                if args.format == "json" {
+                    output::print_json(&output::CompareOutput::new(&CompareReport::default()))?;
+                }
+                return Ok(());
             }
+        };
+
+        let mut report = compare_with_history(&results, base_history.unwrap_or(runs), &config);
+        // Only the baseline stops at --base; budgets see the whole history
+        if base_history.is_some() {
+            report.budgets = check_budgets(&results, runs, &config, Utc::now());
         }
+        (report, runs)
+    } else {
+        let base_history = base_history.context("--base is required without --output-file")?;
+        let head = resolve_stored_run(
+            &resolved,
+            &args.name,
+            args.head.as_deref().unwrap_or("HEAD"),
+        )?;
+        // Sparklines end at the head run
+        let before_head = runs
+            .iter()
+            .position(|run| std::ptr::eq(run, head))
+            .unwrap_or(runs.len());
+        let mut report = compare_with_history_at(&head.benches, base_history, &config, head.date);
+        // Budgets see the runs up to the head, so the commits after the base count too
+        let through_head = &runs[..(before_head + 1).min(runs.len())];
+        report.budgets = check_budgets(&head.benches, through_head, &config, head.date);
+        (report, &runs[..before_head])
+    };
+    if args.comparison.change_points {
+        report.change_points = detect_suite_change_points(runs, &ChangePointConfig::default());
+    }
+
+    let table = render_options(&args.sort_by, args.group, &args.color, args.sparkline)?;
+    print_report(&report, &args.format, &table, history)?;
+
+    if !accepted_renames.is_empty() {
+        data.save_to_file(&args.data_file)
+            .with_context(|| "Failed to save benchmark data")?;
     }
 
     Ok(())
 }
 
-fn history_command(args: HistoryArgs) -> Result<()> {
+fn history_command(args: HistoryArgs, output: OutputMode) -> Result<()> {
     let data = BenchmarkData::load_from_file(&args.data_file)
         .with_context(|| "Failed to load benchmark data")?;
 
@@ -446,6 +1290,17 @@ fn history_command(args: HistoryArgs) -> Result<()> {
         data.entries.keys().collect()
     };
 
+    if output.is_json() {
+        let history: Vec<output::SuiteHistory> = suites
+            .into_iter()
+            .map(|suite| output::SuiteHistory {
+                suite,
+                runs: data.get_runs(suite).iter().rev().take(args.limit).collect(),
+            })
+            .collect();
+        return output::print_json(&history);
+    }
+
     for suite_name in suites {
         println!("## {}\n", suite_name);
 
@@ -472,6 +1327,768 @@ fn history_command(args: HistoryArgs) -> Result<()> {
     Ok(())
 }
 
+fn diff_command(args: DiffArgs) -> Result<()> {
+    let (before_files, after_files) = match args.files.as_slice() {
+        [] => (args.before, args.after),
+        [before, after] => (
+            std::iter::once(before.clone()).chain(args.before).collect(),
+            std::iter::once(after.clone()).chain(args.after).collect(),
+        ),
+        _ => anyhow::bail!("Expected a BEFORE and an AFTER file"),
+    };
+    if before_files.is_empty() || after_files.is_empty() {
+        anyhow::bail!("At least one output is required for each side");
+    }
+
+    let before = read_repetitions(&before_files)?;
+    let after = read_repetitions(&after_files)?;
+
+    let config = CompareConfig {
+        group_rules: parse_group_rules(&args.group_thresholds)?,
+        ..args.thresholds.compare_config(None)?
+    };
+
+    let report = compare_runs(
+        &diff_run("before", before),
+        &diff_run("after", after),
+        &config,
+    );
+    let table = render_options(&args.sort_by, args.group, &args.color, 0)?;
+    print_report(&report, &args.format, &table, &[])
+}
+
+/// Parse each output file and average the repetitions
+fn read_repetitions(files: &[PathBuf]) -> Result<Vec<BenchmarkResult>> {
+    let repetitions = files
+        .iter()
+        .map(|file| {
+            let output = std::fs::read_to_string(file)
+                .with_context(|| format!("Failed to read benchmark output file: {:?}", file))?;
+            parse_from_string(&output)
+                .with_context(|| format!("No benchmark results found in {:?}", file))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(merge_repetitions(&repetitions))
+}
+
+/// Wrap parsed results in a run with a placeholder commit
+fn diff_run(label: &str, benches: Vec<BenchmarkResult>) -> BenchmarkRun {
+    BenchmarkRun {
+        commit: CommitInfo {
+            id: label.to_string(),
+            message: label.to_string(),
+            timestamp: Utc::now(),
+            url: None,
+            author: None,
+        },
+        date: Utc::now(),
+        tool: "cargo".to_string(),
+        benches,
+    }
+}
+
+/// Record the `--accept-rename OLD NEW` pairs as aliases, returning the accepted pairs
+fn accept_renames(data: &mut BenchmarkData, renames: &[String]) -> Vec<(String, String)> {
+    renames
+        .chunks_exact(2)
+        .map(|pair| {
+            info!("Accepting rename: {} -> {}", pair[0], pair[1]);
+            data.add_alias(&pair[0], &pair[1]);
+            (pair[0].clone(), pair[1].clone())
+        })
+        .collect()
+}
+
+/// Build the text table options from the command's flags
+fn render_options(
+    sort_by: &str,
+    group: bool,
+    color: &str,
+    sparkline: usize,
+) -> Result<render::RenderOptions> {
+    Ok(render::RenderOptions {
+        color: render::use_color(color).map_err(|e| anyhow::anyhow!(e))?,
+        sort: sort_by.parse().map_err(|e: String| anyhow::anyhow!(e))?,
+        group,
+        sparkline,
+    })
+}
+
+/// Print a comparison report as JSON, markdown or short text; `history` feeds
+/// the sparklines of the text table
+fn print_report(
+    report: &CompareReport,
+    format: &str,
+    table: &render::RenderOptions,
+    history: &[BenchmarkRun],
+) -> Result<()> {
+    match format {
+        "json" => output::print_json(&output::CompareOutput::new(report))?,
+        "markdown" => {
+            println!("{}", report.summary());
+        }
+        _ => {
+            print!("{}", render::render_report(report, history, table));
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the stored run for a git reference, walking back to the nearest benchmarked
+/// ancestor if the commit itself has no data. References that cannot be resolved
+/// in the local repository are matched against stored commit ids directly.
+fn resolve_stored_run<'a>(
+    data: &'a BenchmarkData,
+    suite: &str,
+    reference: &str,
+) -> Result<&'a BenchmarkRun> {
+    let repo_path = std::env::current_dir()?;
+    let has_data = |id: &str| data.get_run_by_commit(suite, id).is_some();
+
+    let commit_id = match git::find_benchmarked_commit(&repo_path, reference, has_data) {
+        Ok(Some(found)) => {
+            if found.distance > 0 {
+                info!(
+                    "No data for '{}' ({}), using nearest benchmarked ancestor {} ({} commit(s) back)",
+                    reference,
+                    &found.commit_id[..7],
+                    &found.benchmarked_id[..7],
+                    found.distance
+                );
+            }
+            found.benchmarked_id
+        }
+        Ok(None) => anyhow::bail!(
+            "No stored run in suite '{}' for '{}' or any of its {} nearest ancestors",
+            suite,
+            reference,
+            git::MAX_ANCESTOR_DISTANCE
+        ),
+        Err(e) => {
+            debug!("Could not resolve '{}' in the repository: {}", reference, e);
+            reference.to_string()
+        }
+    };
+
+    data.get_run_by_commit(suite, &commit_id)
+        .with_context(|| format!("No stored run in suite '{}' for '{}'", suite, reference))
+}
+
+/// Parse the `--budget` flags, resolving commit baselines to stored runs
+fn parse_budgets(
+    data: &BenchmarkData,
+    suite: &str,
+    specs: &[String],
+) -> Result<Vec<RegressionBudget>> {
+    specs
+        .iter()
+        .map(|spec| {
+            let mut budget = RegressionBudget::parse(spec)
+                .map_err(|e| anyhow::anyhow!("Invalid budget: {}", e))?;
+            if let BudgetBaseline::Commit(reference) = &budget.baseline {
+                let run = resolve_stored_run(data, suite, reference)?;
+                budget.baseline = BudgetBaseline::Commit(run.commit.id.clone());
+            }
+            Ok(budget)
+        })
+        .collect()
+}
+
+/// Build the coverage policy from the `--missing-benchmarks` flags
+fn coverage_policy(
+    missing: &str,
+    min_coverage: Option<f64>,
+    allow_removed: &[String],
+) -> Result<CoveragePolicy> {
+    Ok(CoveragePolicy {
+        missing: missing.parse().map_err(|e: String| anyhow::anyhow!(e))?,
+        min_coverage,
+        allow_removed: allow_removed
+            .iter()
+            .map(|pattern| BenchmarkPattern::new(pattern))
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|e| anyhow::anyhow!("Invalid --allow-removed pattern: {}", e))?,
+    })
+}
+
+/// Build the comparison baseline mode from the `--baseline-window` flags
+fn baseline_mode(window: Option<usize>, mad_multiplier: f64) -> BaselineMode {
+    match window {
+        Some(window) => BaselineMode::Rolling {
+            window,
+            mad_multiplier,
+        },
+        None => BaselineMode::Latest,
+    }
+}
+
+/// Parse `--significance-level`, a p-value cutoff strictly between 0 and 1
+fn parse_significance_level(value: &str) -> std::result::Result<f64, String> {
+    let level = value.parse::<f64>().map_err(|e| e.to_string())?;
+    if level > 0.0 && level < 1.0 {
+        Ok(level)
+    } else {
+        Err(format!("{} is not between 0 and 1 (exclusive)", level))
+    }
+}
+
+/// Parse `--min-coverage`, a percentage of benchmarks between 0% and 100%
+fn parse_min_coverage(value: &str) -> std::result::Result<f64, String> {
+    let percentage = parse_percentage(value)? * 100.0;
+    if (0.0..=100.0).contains(&percentage) {
+        Ok(percentage)
+    } else {
+        Err(format!("{} is not between 0% and 100%", value.trim()))
+    }
+}
+
+/// Parse `--flaky-warn-only`, a flakiness score between 0 and 1 (inclusive)
+fn parse_flakiness_score(value: &str) -> std::result::Result<f64, String> {
+    let score = value.parse::<f64>().map_err(|e| e.to_string())?;
+    if (0.0..=1.0).contains(&score) {
+        Ok(score)
+    } else {
+        Err(format!("{} is not between 0 and 1", score))
+    }
+}
+
+/// Parse a threshold flag value
+fn parse_threshold(value: &str) -> Result<Threshold> {
+    Threshold::parse(value).map_err(|e| anyhow::anyhow!("Invalid threshold: {}", e))
+}
+
+/// Parse a threshold flag value that can be turned off with `off`
+fn parse_optional_threshold(value: &str) -> Result<Option<Threshold>> {
+    if value.eq_ignore_ascii_case("off") {
+        Ok(None)
+    } else {
+        parse_threshold(value).map(Some)
+    }
+}
+
+/// Build the flakiness config from the `--flakiness` flags
+fn flakiness_config(enabled: bool, warn_only_score: Option<f64>) -> Option<FlakinessConfig> {
+    (enabled || warn_only_score.is_some()).then(|| FlakinessConfig {
+        warn_only_score,
+        ..Default::default()
+    })
+}
+
+/// Parse the `--threshold-rule` flags in order
+fn parse_threshold_rules(specs: &[String]) -> Result<Vec<ThresholdRule>> {
+    specs
+        .iter()
+        .map(|spec| ThresholdRule::parse(spec).map_err(|e| anyhow::anyhow!(e)))
+        .collect()
+}
+
+/// Parse the `--group-threshold` flags in order
+fn parse_group_rules(specs: &[String]) -> Result<Vec<ThresholdRule>> {
+    specs
+        .iter()
+        .map(|spec| ThresholdRule::parse_group(spec).map_err(|e| anyhow::anyhow!(e)))
+        .collect()
+}
+
+/// The requested suite, or every suite in name order
+fn select_suites<'a>(data: &'a BenchmarkData, name: Option<&str>) -> Result<Vec<&'a String>> {
+    match name {
+        Some(name) => match data.entries.get_key_value(name) {
+            Some((suite, _)) => Ok(vec![suite]),
+            None => anyhow::bail!("Suite '{}' not found", name),
+        },
+        None => {
+            let mut names: Vec<&String> = data.entries.keys().collect();
+            names.sort();
+            Ok(names)
+        }
+    }
+}
+
+fn changepoints_command(args: ChangepointsArgs) -> Result<()> {
+    let data = BenchmarkData::load_from_file(&args.data_file)
+        .with_context(|| "Failed to load benchmark data")?;
+    let data = data.resolved();
+
+    let suites = select_suites(&data, args.name.as_deref())?;
+
+    let config = ChangePointConfig {
+        confidence: args.confidence,
+        min_segment: args.min_segment,
+        min_change_percent: args.min_change,
+    };
+
+    let results: Vec<(&String, Vec<ChangePoint>)> = suites
+        .into_iter()
+        .map(|suite| {
+            (
+                suite,
+                detect_suite_change_points(data.get_runs(suite), &config),
+            )
+        })
+        .collect();
+
+    if args.format == "json" {
+        let by_suite: BTreeMap<&String, Vec<ChangePoint>> = results.into_iter().collect();
+        return output::print_json(&by_suite);
+    }
+
+    for (suite, change_points) in results {
+        println!("## {}\n", suite);
+
+        if change_points.is_empty() {
+            println!("No change points detected\n");
+            continue;
+        }
+
+        for cp in change_points {
+            let indicator = if cp.is_regression() { "↑" } else { "↓" };
+            println!(
+                "  {} {} at {} ({}): {:.2} {} -> {:.2} {} ({:+.1}%, confidence {:.1}%)",
+                indicator,
+                cp.benchmark,
+                &cp.commit_id[..7.min(cp.commit_id.len())],
+                cp.date.format("%Y-%m-%d"),
+                cp.before,
+                cp.unit,
+                cp.after,
+                cp.unit,
+                cp.percentage_change,
+                cp.confidence * 100.0
+            );
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+fn flaky_command(args: FlakyArgs) -> Result<()> {
+    let data = BenchmarkData::load_from_file(&args.data_file)
+        .with_context(|| "Failed to load benchmark data")?;
+    let data = data.resolved();
+
+    let suites = select_suites(&data, args.name.as_deref())?;
+
+    let config = CompareConfig {
+        rules: parse_threshold_rules(&args.threshold_rules)?,
+        ..CompareConfig::from_thresholds(&args.alert_threshold, None)
+            .map_err(|e| anyhow::anyhow!("Invalid threshold: {}", e))?
+    };
+
+    let mut results: Vec<(&String, Vec<Flakiness>)> = Vec::new();
+    for suite in suites {
+        let mut scores = suite_flakiness(
+            data.get_runs(suite),
+            |name| config.alert_threshold_for(name),
+            args.min_runs,
+        );
+        match args.sort.as_str() {
+            "score" => {}
+            "cv" => scores.sort_by(|a, b| {
+                b.coefficient_of_variation
+                    .total_cmp(&a.coefficient_of_variation)
+            }),
+            "flips" => scores.sort_by(|a, b| b.sign_flip_rate.total_cmp(&a.sign_flip_rate)),
+            "alerts" => scores.sort_by(|a, b| b.alert_rate.total_cmp(&a.alert_rate)),
+            "name" => scores.sort_by(|a, b| a.benchmark.cmp(&b.benchmark)),
+            other => anyhow::bail!(
+                "Unknown sort key '{}' (expected score, cv, flips, alerts or name)",
+                other
+            ),
+        }
+        if let Some(limit) = args.limit {
+            scores.truncate(limit);
+        }
+        results.push((suite, scores));
+    }
+
+    if args.format == "json" {
+        let by_suite: BTreeMap<&String, Vec<Flakiness>> = results.into_iter().collect();
+        return output::print_json(&by_suite);
+    }
+
+    for (suite, scores) in results {
+        println!("## {}\n", suite);
+
+        if scores.is_empty() {
+            println!("No benchmark has at least {} runs\n", args.min_runs);
+            continue;
+        }
+
+        let width = scores
+            .iter()
+            .map(|f| f.benchmark.len())
+            .max()
+            .unwrap_or(0)
+            .max("Benchmark".len());
+        println!(
+            "  {:<width$}  {:>5}  {:>7}  {:>10}  {:>10}  {:>5}",
+            "Benchmark", "Runs", "CV", "Sign flips", "Alert rate", "Score"
+        );
+        for f in scores {
+            println!(
+                "  {:<width$}  {:>5}  {:>6.1}%  {:>9.0}%  {:>9.0}%  {:>5.2}",
+                f.benchmark,
+                f.runs,
+                f.coefficient_of_variation * 100.0,
+                f.sign_flip_rate * 100.0,
+                f.alert_rate * 100.0,
+                f.score
+            );
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+fn bisect_command(args: BisectArgs) -> Result<()> {
+    let repo_path = std::env::current_dir()?;
+    let config = args.thresholds.compare_config(None)?;
+
+    let mut data = args.store.then(|| {
+        BenchmarkData::load_from_file(&args.data_file).unwrap_or_else(|_| {
+            info!("Creating new benchmark data file");
+            BenchmarkData::new()
+        })
+    });
+    let store = data.as_mut().map(|data| bisect::BisectStore {
+        data,
+        data_file: &args.data_file,
+        suite: &args.name,
+    });
+
+    let report = bisect::run_bisection(
+        &bisect::BisectOptions {
+            repo_path: &repo_path,
+            good: &args.good,
+            bad: &args.bad,
+            bench: &args.bench,
+            repetitions: args.repetitions,
+            cargo_args: &args.cargo_args,
+            config: &config,
+            echo: if args.format == "json" {
+                bench::Echo::Stderr
+            } else {
+                bench::Echo::Live
+            },
+        },
+        store,
+    )?;
+
+    if args.format == "json" {
+        output::print_json(&report)
+    } else {
+        println!("{}", report.summary());
+        Ok(())
+    }
+}
+
+fn backfill_command(args: BackfillArgs, output: OutputMode) -> Result<()> {
+    let repo_path = std::env::current_dir()?;
+    let selection = if args.tags_only {
+        backfill::Selection::Tags(git::tagged_commits(&repo_path)?)
+    } else {
+        args.every
+            .map_or(backfill::Selection::All, backfill::Selection::Every)
+    };
+    let state_file = args
+        .state_file
+        .clone()
+        .unwrap_or_else(|| args.data_file.with_extension("backfill.json"));
+
+    let summary = backfill::run_backfill(&backfill::BackfillOptions {
+        repo_path: &repo_path,
+        range: &args.range,
+        selection: &selection,
+        jobs: args.jobs,
+        retry_skipped: args.retry_skipped,
+        data_file: &args.data_file,
+        state_file: &state_file,
+        suite: &args.name,
+        bench_command: args.bench_command.as_deref(),
+        cargo_args: &args.cargo_args,
+        echo: output.bench_echo(),
+    })?;
+
+    if output.is_json() {
+        output::print_json(&output::BackfillOutput {
+            suite: args.name,
+            stored: summary.stored,
+            total: summary.total,
+            skipped: summary.skipped,
+            state_file,
+        })?;
+    } else if summary.total > 0 {
+        println!(
+            "Backfilled {} of {} commit(s) into suite '{}' ({} skipped in total, see {:?})",
+            summary.stored, summary.total, args.name, summary.skipped, state_file
+        );
+    }
+    Ok(())
+}
+
+fn export_command(args: ExportArgs) -> Result<()> {
+    let data = BenchmarkData::load_from_file(&args.data_file)
+        .with_context(|| "Failed to load benchmark data")?;
+    let data = data.resolved();
+
+    let format: ExportFormat = args
+        .format
+        .parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+    for name in &args.names {
+        select_suites(&data, Some(name))?;
+    }
+    let filter = ExportFilter {
+        suites: args.names,
+        benchmarks: args
+            .benches
+            .iter()
+            .map(|pattern| BenchmarkPattern::new(pattern))
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|e| anyhow::anyhow!("Invalid --bench pattern: {}", e))?,
+        since: args
+            .since
+            .as_deref()
+            .map(|d| parse_date(d, false))
+            .transpose()?,
+        until: args
+            .until
+            .as_deref()
+            .map(|d| parse_date(d, true))
+            .transpose()?,
+    };
+
+    let rows = export_rows(&data, &filter);
+    let output = format_export(&rows, format);
+    match &args.out_file {
+        Some(path) => {
+            std::fs::write(path, output)
+                .with_context(|| format!("Failed to write export to {:?}", path))?;
+            info!("Exported {} row(s) to {:?}", rows.len(), path);
+        }
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
+fn import_command(args: ImportArgs, output: OutputMode) -> Result<()> {
+    if args.from != "github-action-benchmark" {
+        anyhow::bail!(
+            "Unknown import source '{}' (expected github-action-benchmark)",
+            args.from
+        );
+    }
+
+    let (content, source) = match &args.file {
+        Some(file) => (
+            std::fs::read_to_string(file).with_context(|| format!("Failed to read {:?}", file))?,
+            file.display().to_string(),
+        ),
+        None => {
+            let repo_path = std::env::current_dir()?;
+            let file = format!("{}/data.js", args.benchmark_data_dir_path);
+            let source = format!("origin/{}:{}", args.gh_pages_branch, file);
+            info!("Reading {}", source);
+            let content =
+                git::read_from_gh_pages(&repo_path, &args.gh_pages_branch, &file, "origin")
+                    .with_context(|| format!("No {} found", source))?;
+            (content, source)
+        }
+    };
+
+    let import = import_github_action_benchmark(&content)
+        .with_context(|| format!("Failed to import {}", source))?;
+    for (suite, (tool, runs)) in &import.skipped {
+        warn!(
+            "Skipped {} run(s) of suite '{}': {} values are bigger-is-better, \
+             which git-bench cannot compare",
+            runs, suite, tool
+        );
+    }
+
+    let renames = args
+        .rename_suites
+        .iter()
+        .map(|spec| {
+            spec.split_once('=')
+                .map(|(from, to)| (from.trim().to_string(), to.trim().to_string()))
+                .with_context(|| format!("Invalid --rename-suite '{}': expected FROM=TO", spec))
+        })
+        .collect::<Result<HashMap<String, String>>>()?;
+
+    let mut data = BenchmarkData::load_from_file(&args.data_file)
+        .with_context(|| "Failed to load benchmark data")?;
+    if data.repo_url.is_none() {
+        data.repo_url = import.data.repo_url.clone();
+    }
+
+    let mut result = output::ImportOutput {
+        data_file: args.data_file.clone(),
+        suites: Vec::new(),
+        skipped: import
+            .skipped
+            .iter()
+            .map(|(suite, (tool, runs))| output::SkippedSuite {
+                suite: suite.clone(),
+                tool: tool.clone(),
+                runs: *runs,
+            })
+            .collect(),
+    };
+    let mut suites: Vec<(String, Vec<BenchmarkRun>)> = import.data.entries.into_iter().collect();
+    suites.sort_by(|a, b| a.0.cmp(&b.0));
+    for (suite, runs) in suites {
+        let target = renames.get(&suite).unwrap_or(&suite).clone();
+        let (mut imported, mut kept) = (0, 0);
+        for run in runs {
+            let stored = data
+                .get_runs(&target)
+                .iter()
+                .any(|r| r.commit.id == run.commit.id);
+            if stored && !args.overwrite {
+                kept += 1;
+                continue;
+            }
+            data.insert_run(&target, run);
+            imported += 1;
+        }
+        if !output.is_json() {
+            println!(
+                "Imported {} run(s) into suite '{}' ({} already stored)",
+                imported, target, kept
+            );
+        }
+        result.suites.push(output::ImportedSuite {
+            suite: target,
+            imported,
+            kept,
+        });
+    }
+
+    data.save_to_file(&args.data_file)
+        .with_context(|| "Failed to save benchmark data")?;
+    info!("Stored benchmark data to {:?}", args.data_file);
+    if output.is_json() {
+        output::print_json(&result)?;
+    }
+    Ok(())
+}
+
+fn serve_command(args: ServeArgs) -> Result<()> {
+    let listener = std::net::TcpListener::bind((args.host.as_str(), args.port))
+        .with_context(|| format!("Failed to listen on {}:{}", args.host, args.port))?;
+    if !args.dashboard_dir.join("index.html").exists() {
+        warn!(
+            "No dashboard found in {:?}; run `make build` or pass --dashboard-dir",
+            args.dashboard_dir
+        );
+    }
+
+    println!(
+        "Dashboard: http://{} (serving {:?}, data from {:?})",
+        listener.local_addr()?,
+        args.dashboard_dir,
+        args.data_file
+    );
+    serve::serve(
+        listener,
+        serve::ServeConfig {
+            dist: args.dashboard_dir,
+            data_file: args.data_file,
+            poll_interval: std::time::Duration::from_millis(500),
+        },
+    )
+}
+
+fn tui_command(args: TuiArgs) -> Result<()> {
+    let data = BenchmarkData::load_from_file(&args.data_file)
+        .with_context(|| "Failed to load benchmark data")?;
+    let data = data.resolved();
+    if data.entries.is_empty() {
+        anyhow::bail!("No benchmark data in {:?}", args.data_file);
+    }
+    // Fail early on an unknown suite
+    select_suites(&data, args.name.as_deref())?;
+    let config = CompareConfig::from_thresholds(&args.alert_threshold, None)
+        .map_err(|e| anyhow::anyhow!("Invalid threshold: {}", e))?;
+
+    let mut app = tui::App::new(&data, config, args.name.as_deref());
+    let mut terminal = ratatui::init();
+    let result = tui::run(&mut app, &mut terminal);
+    ratatui::restore();
+    result.with_context(|| "Terminal UI failed")
+}
+
+fn report_command(args: ReportArgs) -> Result<()> {
+    let data = BenchmarkData::load_from_file(&args.data_file)
+        .with_context(|| "Failed to load benchmark data")?;
+    let data = data.resolved();
+    let suites = select_suites(&data, args.name.as_deref())?;
+    let config = CompareConfig::from_thresholds(&args.alert_threshold, None)
+        .map_err(|e| anyhow::anyhow!("Invalid threshold: {}", e))?;
+
+    let html = report::html_report(&data, &suites, &config, args.runs);
+    std::fs::write(&args.html, html).with_context(|| format!("Failed to write {:?}", args.html))?;
+    info!("Wrote report to {:?}", args.html);
+    Ok(())
+}
+
+fn doctor_command(
+    args: DoctorArgs,
+    config: Option<&std::path::Path>,
+    output: OutputMode,
+) -> Result<()> {
+    let repo_path = std::env::current_dir()?;
+    let checks = doctor::run_checks(
+        &doctor::DoctorOptions {
+            data_file: &args.data_file,
+            config,
+            repo_path: &repo_path,
+            gh_pages_branch: &args.gh_pages_branch,
+            data_dir: &args.benchmark_data_dir_path,
+            remote: "origin",
+            github_token: args.github_token.as_deref(),
+            offline: args.offline,
+        },
+        &Cli::command(),
+    );
+    if output.is_json() {
+        output::print_json(&checks)?;
+    } else {
+        print!("{}", doctor::format_checks(&checks));
+    }
+
+    let errors = checks
+        .iter()
+        .filter(|check| check.status == doctor::Status::Error)
+        .count();
+    if errors > 0 {
+        anyhow::bail!("doctor found {} problem(s)", errors);
+    }
+    Ok(())
+}
+
+/// Parse a date bound: RFC 3339, or a plain date meaning the start of that day
+/// (its last instant with `end_of_day`, for inclusive upper bounds)
+fn parse_date(value: &str, end_of_day: bool) -> Result<chrono::DateTime<Utc>> {
+    if let Ok(date) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    let day = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}' (expected YYYY-MM-DD or RFC 3339)", value))?;
+    let time = if end_of_day {
+        chrono::NaiveTime::from_hms_nano_opt(23, 59, 59, 999_999_999).expect("valid time")
+    } else {
+        chrono::NaiveTime::MIN
+    };
+    Ok(day.and_time(time).and_utc())
+}
+
 fn create_github_comment(
     owner: &str,
     repo: &str,
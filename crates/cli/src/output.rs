@@ -0,0 +1,207 @@
+//! Machine-readable command output for `--output json`
+//!
+//! Each command prints one JSON document on stdout, built from the structs
+//! below; logs go to stderr. Fields are only ever added, so consumers can
+//! ignore the ones they do not know.
+
+use crate::bench::Echo;
+use git_bench_core::{BenchmarkRun, CompareReport};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// How commands print their results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON document on stdout
+    Json,
+}
+
+impl OutputMode {
+    pub fn is_json(self) -> bool {
+        self == Self::Json
+    }
+
+    /// Where benchmark commands echo their output: never to stdout when it
+    /// carries the JSON document
+    pub fn bench_echo(self) -> Echo {
+        match self {
+            Self::Text => Echo::Live,
+            Self::Json => Echo::Stderr,
+        }
+    }
+}
+
+impl FromStr for OutputMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("Unknown output '{}' (expected text or json)", s)),
+        }
+    }
+}
+
+/// Print a value as pretty JSON on stdout
+pub fn print_json<T: Serialize>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Whether a comparison triggered alerts or failures
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct AlertStatus {
+    pub has_alerts: bool,
+    pub has_failures: bool,
+    pub has_suspicious_improvements: bool,
+}
+
+impl AlertStatus {
+    pub fn new(report: &CompareReport) -> Self {
+        Self {
+            has_alerts: report.has_alerts(),
+            has_failures: report.has_failures(),
+            has_suspicious_improvements: report.has_suspicious_improvements(),
+        }
+    }
+}
+
+/// A comparison report with its alert status (`compare`, `diff`)
+#[derive(Debug, Serialize)]
+pub struct CompareOutput<'a> {
+    #[serde(flatten)]
+    pub report: &'a CompareReport,
+    #[serde(flatten)]
+    pub status: AlertStatus,
+}
+
+impl<'a> CompareOutput<'a> {
+    pub fn new(report: &'a CompareReport) -> Self {
+        Self {
+            report,
+            status: AlertStatus::new(report),
+        }
+    }
+}
+
+/// Stored runs of one suite, newest first (`history`)
+#[derive(Debug, Serialize)]
+pub struct SuiteHistory<'a> {
+    pub suite: &'a str,
+    pub runs: Vec<&'a BenchmarkRun>,
+}
+
+/// Outcome of pushing the data to the gh-pages branch
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DeployOutput {
+    pub branch: String,
+    /// The gh-pages commit, None when the data was already up to date
+    pub commit: Option<String>,
+    /// Why the deployment failed
+    pub error: Option<String>,
+}
+
+/// Outcome of storing a run (`store`, and the storing step of `run`)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StoreOutput {
+    pub suite: String,
+    /// False when the output held no benchmark results
+    pub stored: bool,
+    pub commit: Option<String>,
+    pub benchmarks: usize,
+    /// Local data file written, None when deployed to gh-pages instead
+    pub data_file: Option<PathBuf>,
+    pub deploy: Option<DeployOutput>,
+}
+
+/// Outcome of `run`
+#[derive(Debug, Serialize)]
+pub struct RunOutput<'a> {
+    /// The comparison against the history, None without benchmark results
+    pub comparison: Option<CompareOutput<'a>>,
+    pub store: Option<StoreOutput>,
+    /// URL of the commit comment, when one was created
+    pub comment_url: Option<String>,
+    /// Whether the command fails because of the alerts
+    pub failed: bool,
+}
+
+/// Runs imported into one suite (`import`)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ImportedSuite {
+    pub suite: String,
+    pub imported: usize,
+    /// Runs left alone because their commit was already stored
+    pub kept: usize,
+}
+
+/// Outcome of `import`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ImportOutput {
+    pub data_file: PathBuf,
+    pub suites: Vec<ImportedSuite>,
+    /// Runs left out per suite because their tool measures bigger-is-better values
+    pub skipped: Vec<SkippedSuite>,
+}
+
+/// Runs of a bigger-is-better tool left out of an import
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SkippedSuite {
+    pub suite: String,
+    pub tool: String,
+    pub runs: usize,
+}
+
+/// Outcome of `backfill`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BackfillOutput {
+    pub suite: String,
+    pub stored: usize,
+    pub total: usize,
+    /// Commits skipped so far, including earlier invocations
+    pub skipped: usize,
+    pub state_file: PathBuf,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git_bench_core::{compare_runs, BenchmarkResult, CommitInfo, CompareConfig};
+
+    #[test]
+    fn test_compare_output_is_flat() {
+        let run = |value: f64| BenchmarkRun {
+            commit: CommitInfo {
+                id: "abc".to_string(),
+                message: String::new(),
+                timestamp: chrono::Utc::now(),
+                url: None,
+                author: None,
+            },
+            date: chrono::Utc::now(),
+            tool: "cargo".to_string(),
+            benches: vec![BenchmarkResult {
+                name: "parse".to_string(),
+                value,
+                unit: "ns/iter".to_string(),
+                range: None,
+                extra: Default::default(),
+            }],
+        };
+        let report = compare_runs(&run(100.0), &run(300.0), &CompareConfig::default());
+
+        let json = serde_json::to_value(CompareOutput::new(&report)).unwrap();
+        assert_eq!(json["comparisons"][0]["name"], "parse");
+        assert_eq!(json["has_alerts"], true);
+        assert_eq!(json["has_failures"], report.has_failures());
+        assert_eq!(json["has_suspicious_improvements"], false);
+
+        assert_eq!("json".parse(), Ok(OutputMode::Json));
+        assert!("yaml".parse::<OutputMode>().is_err());
+    }
+}
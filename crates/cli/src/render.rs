@@ -0,0 +1,485 @@
+//! Terminal rendering of comparison reports
+//!
+//! An aligned table with one row per benchmark, coloured by severity against
+//! the thresholds, values in readable units and a sparkline of each
+//! benchmark's recent history. Without colour the same table is plain text.
+
+use git_bench_core::{format_value, BenchmarkPath, BenchmarkRun, CompareReport};
+use std::collections::{BTreeMap, HashMap};
+use std::io::IsTerminal;
+use std::str::FromStr;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// How a benchmark fared, worst first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    Failure,
+    Alert,
+    Suspicious,
+    Improvement,
+    New,
+    Removed,
+    Unchanged,
+}
+
+impl Severity {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Severity::Failure => "FAIL",
+            Severity::Alert => "ALERT",
+            Severity::Suspicious => "SUSPICIOUS",
+            Severity::Improvement => "faster",
+            Severity::New => "new",
+            Severity::Removed => "removed",
+            Severity::Unchanged => "ok",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            Severity::Failure => "\x1b[1;31m",
+            Severity::Alert => "\x1b[33m",
+            Severity::Suspicious => "\x1b[35m",
+            Severity::Improvement => "\x1b[32m",
+            Severity::New | Severity::Removed => "\x1b[36m",
+            Severity::Unchanged => "\x1b[2m",
+        }
+    }
+}
+
+/// Order of the rows within a group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// Worst first, then by change
+    #[default]
+    Severity,
+    /// Largest slowdown first
+    Change,
+    /// Alphabetical
+    Name,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "severity" => Ok(Self::Severity),
+            "change" => Ok(Self::Change),
+            "name" => Ok(Self::Name),
+            _ => Err(format!(
+                "Unknown sort key '{}' (expected severity, change or name)",
+                s
+            )),
+        }
+    }
+}
+
+/// How to render a report
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Use ANSI colours
+    pub color: bool,
+    /// Row order
+    pub sort: SortKey,
+    /// Group rows by their hierarchy group
+    pub group: bool,
+    /// Number of values in each sparkline (0 hides them)
+    pub sparkline: usize,
+}
+
+/// Resolve `--color auto|always|never`; `auto` colours only terminals and honours `NO_COLOR`
+pub fn use_color(choice: &str) -> Result<bool, String> {
+    match choice {
+        "auto" => Ok(std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()),
+        "always" => Ok(true),
+        "never" => Ok(false),
+        _ => Err(format!(
+            "Unknown color choice '{}' (expected auto, always or never)",
+            choice
+        )),
+    }
+}
+
+struct Row {
+    severity: Severity,
+    name: String,
+    group: String,
+    before: String,
+    after: String,
+    change: Option<f64>,
+    history: String,
+    p_value: String,
+}
+
+/// Render a report as a table. `history` holds the stored runs before the
+/// compared one; each sparkline ends with the current value.
+pub fn render_report(
+    report: &CompareReport,
+    history: &[BenchmarkRun],
+    options: &RenderOptions,
+) -> String {
+    let mut rows: Vec<Row> = Vec::new();
+    for comp in &report.comparisons {
+        rows.push(Row {
+            severity: severity(report, &comp.name),
+            name: comp.name.clone(),
+            group: String::new(),
+            before: format_value(comp.previous, &comp.unit),
+            after: format_value(comp.current, &comp.unit),
+            change: Some(comp.percentage_change),
+            history: sparkline(&series(
+                history,
+                &comp.name,
+                comp.current,
+                options.sparkline,
+            )),
+            p_value: comp
+                .p_value
+                .map(|p| format!("p={:.3}", p))
+                .unwrap_or_default(),
+        });
+    }
+    for bench in &report.new_benchmarks {
+        rows.push(Row {
+            severity: Severity::New,
+            name: bench.name.clone(),
+            group: String::new(),
+            before: "-".to_string(),
+            after: format_value(bench.value, &bench.unit),
+            change: None,
+            history: String::new(),
+            p_value: String::new(),
+        });
+    }
+    for bench in &report.removed_benchmarks {
+        rows.push(Row {
+            severity: Severity::Removed,
+            name: bench.name.clone(),
+            group: String::new(),
+            before: format_value(bench.value, &bench.unit),
+            after: "-".to_string(),
+            change: None,
+            history: String::new(),
+            p_value: String::new(),
+        });
+    }
+
+    if options.group {
+        for row in &mut rows {
+            let path = BenchmarkPath::parse(&row.name);
+            if let Some(group) = path.groups().pop() {
+                row.group = group;
+                row.name = path.test;
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<String, Vec<Row>> = BTreeMap::new();
+    for row in rows {
+        groups.entry(row.group.clone()).or_default().push(row);
+    }
+    for rows in groups.values_mut() {
+        sort_rows(rows, options.sort);
+    }
+
+    let group_scores: HashMap<&str, f64> = report
+        .groups
+        .iter()
+        .map(|g| (g.group.as_str(), g.percentage_change))
+        .collect();
+
+    let mut out = report.short_summary();
+    out.push('\n');
+
+    let all: Vec<&Row> = groups.values().flatten().collect();
+    if all.is_empty() {
+        return out;
+    }
+    let width = |cell: fn(&Row) -> &str, header: &str| {
+        all.iter()
+            .map(|row| cell(row).chars().count())
+            .chain([header.chars().count()])
+            .max()
+            .unwrap_or(0)
+    };
+    let changes: Vec<String> = all.iter().map(|row| format_change(row.change)).collect();
+    let widths = [
+        width(|r| r.severity.label(), "STATUS"),
+        width(|r| &r.name, "BENCHMARK"),
+        width(|r| &r.before, "BEFORE"),
+        width(|r| &r.after, "AFTER"),
+        changes
+            .iter()
+            .map(|c| c.chars().count())
+            .chain(["CHANGE".len()])
+            .max()
+            .unwrap_or(0),
+    ];
+    let history_width = all
+        .iter()
+        .map(|row| row.history.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let paint = |text: String, color: &str| {
+        if options.color {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text
+        }
+    };
+
+    let mut header = format!(
+        "  {:<w0$}  {:<w1$}  {:>w2$}  {:>w3$}  {:>w4$}",
+        "STATUS",
+        "BENCHMARK",
+        "BEFORE",
+        "AFTER",
+        "CHANGE",
+        w0 = widths[0],
+        w1 = widths[1],
+        w2 = widths[2],
+        w3 = widths[3],
+        w4 = widths[4],
+    );
+    if history_width > 0 {
+        header.push_str("  HISTORY");
+    }
+    out.push('\n');
+    out.push_str(&paint(header, BOLD));
+    out.push('\n');
+
+    for (group, rows) in &groups {
+        if options.group {
+            let title = if group.is_empty() {
+                "(ungrouped)"
+            } else {
+                group
+            };
+            // The empty group is the whole suite, not the ungrouped benchmarks
+            let score = group_scores
+                .get(group.as_str())
+                .filter(|_| !group.is_empty())
+                .map(|change| format!(" ({:+.1}%)", change))
+                .unwrap_or_default();
+            out.push('\n');
+            out.push_str(&paint(format!("{}{}", title, score), BOLD));
+            out.push('\n');
+        }
+
+        for row in rows {
+            let status = paint(
+                format!("{:<w$}", row.severity.label(), w = widths[0]),
+                row.severity.color(),
+            );
+            let change = paint(
+                format!("{:>w$}", format_change(row.change), w = widths[4]),
+                row.severity.color(),
+            );
+            let mut line = format!(
+                "  {}  {:<w1$}  {:>w2$}  {:>w3$}  {}",
+                status,
+                row.name,
+                row.before,
+                row.after,
+                change,
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+            );
+            if history_width > 0 {
+                line.push_str(&format!("  {:<w$}", row.history, w = history_width));
+            }
+            if !row.p_value.is_empty() {
+                line.push_str(&format!("  {}", row.p_value));
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// How a compared benchmark fared in `report`
+pub(crate) fn severity(report: &CompareReport, name: &str) -> Severity {
+    let listed = |list: &[git_bench_core::ComparisonResult]| list.iter().any(|c| c.name == name);
+    if listed(&report.failures) {
+        Severity::Failure
+    } else if listed(&report.alerts) {
+        Severity::Alert
+    } else if listed(&report.suspicious_improvements) {
+        Severity::Suspicious
+    } else if listed(&report.improvements) {
+        Severity::Improvement
+    } else {
+        Severity::Unchanged
+    }
+}
+
+fn sort_rows(rows: &mut [Row], sort: SortKey) {
+    let change = |row: &Row| row.change.unwrap_or(0.0);
+    match sort {
+        SortKey::Severity => rows.sort_by(|a, b| {
+            a.severity
+                .cmp(&b.severity)
+                .then(change(b).abs().total_cmp(&change(a).abs()))
+        }),
+        SortKey::Change => rows.sort_by(|a, b| change(b).total_cmp(&change(a))),
+        SortKey::Name => rows.sort_by(|a, b| a.name.cmp(&b.name)),
+    }
+}
+
+fn format_change(change: Option<f64>) -> String {
+    change.map(|c| format!("{:+.1}%", c)).unwrap_or_default()
+}
+
+/// The last `len` values of a benchmark: stored history followed by the current value
+fn series(history: &[BenchmarkRun], name: &str, current: f64, len: usize) -> Vec<f64> {
+    if len == 0 {
+        return Vec::new();
+    }
+    let mut values: Vec<f64> = history
+        .iter()
+        .rev()
+        .filter_map(|run| run.benches.iter().find(|b| b.name == name))
+        .map(|b| b.value)
+        .take(len - 1)
+        .collect();
+    values.reverse();
+    values.push(current);
+    values
+}
+
+/// Draw values as a unicode sparkline scaled between their minimum and maximum
+fn sparkline(values: &[f64]) -> String {
+    let finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    if finite.len() < 2 {
+        return String::new();
+    }
+    let min = finite.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = finite.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    finite
+        .iter()
+        .map(|v| {
+            if max - min <= f64::EPSILON * max.abs() {
+                SPARKS[3]
+            } else {
+                let level = ((v - min) / (max - min) * (SPARKS.len() - 1) as f64).round();
+                SPARKS[level as usize]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use git_bench_core::{compare_runs, BenchmarkResult, CommitInfo, CompareConfig};
+
+    fn run(values: &[(&str, f64)]) -> BenchmarkRun {
+        BenchmarkRun {
+            commit: CommitInfo {
+                id: "abc".to_string(),
+                message: String::new(),
+                timestamp: Utc::now(),
+                url: None,
+                author: None,
+            },
+            date: Utc::now(),
+            tool: "cargo".to_string(),
+            benches: values
+                .iter()
+                .map(|(name, value)| BenchmarkResult {
+                    name: name.to_string(),
+                    value: *value,
+                    unit: "ns/iter".to_string(),
+                    range: None,
+                    extra: HashMap::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn report() -> CompareReport {
+        let config = CompareConfig::from_thresholds("150%", None).unwrap();
+        compare_runs(
+            &run(&[
+                ("core::parse::json", 1_000.0),
+                ("core::parse::xml", 100.0),
+                ("io::read", 50.0),
+            ]),
+            &run(&[
+                ("core::parse::json", 1_020.0),
+                ("core::parse::xml", 300.0),
+                ("io::write", 9.0),
+            ]),
+            &config,
+        )
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[1.0, 2.0, 3.0, 8.0]), "▁▂▃█");
+        assert_eq!(sparkline(&[5.0, 5.0]), "▄▄");
+        assert_eq!(sparkline(&[5.0]), "");
+
+        let history = vec![run(&[("a", 1.0)]), run(&[("b", 9.0)]), run(&[("a", 3.0)])];
+        assert_eq!(series(&history, "a", 2.0, 5), vec![1.0, 3.0, 2.0]);
+        assert_eq!(series(&history, "a", 2.0, 2), vec![3.0, 2.0]);
+        assert!(series(&history, "a", 2.0, 0).is_empty());
+    }
+
+    #[test]
+    fn test_render_plain_table() {
+        let history = vec![
+            run(&[("core::parse::xml", 90.0)]),
+            run(&[("core::parse::xml", 100.0)]),
+        ];
+        let options = RenderOptions {
+            sparkline: 10,
+            ..RenderOptions::default()
+        };
+        let text = render_report(&report(), &history, &options);
+        assert!(!text.contains('\x1b'));
+
+        let lines: Vec<&str> = text.lines().skip(2).collect();
+        assert!(lines[0].starts_with("  STATUS"));
+        assert!(lines[0].ends_with("HISTORY"));
+        // Worst first, small changes are not regressions
+        assert!(lines[1].starts_with("  FAIL "));
+        assert!(lines[1].contains("100 ns") && lines[1].contains("300 ns"));
+        assert!(lines[1].contains("+200.0%"));
+        assert!(lines[1].ends_with("▁▁█"));
+        assert!(lines[2].starts_with("  new ") && lines[3].starts_with("  removed "));
+        assert!(lines[4].starts_with("  ok "));
+        assert!(lines[4].contains("1.00 µs"));
+        // Columns line up
+        let column = |line: &str| line.find('%').map(|i| line[..i].chars().count());
+        assert_eq!(column(lines[1]), column(lines[4]));
+    }
+
+    #[test]
+    fn test_render_grouped_and_colored() {
+        let options = RenderOptions {
+            color: true,
+            sort: SortKey::Name,
+            group: true,
+            sparkline: 0,
+        };
+        let text = render_report(&report(), &[], &options);
+        assert!(text.contains("\x1b[1;31mFAIL"));
+        assert!(text.contains("core/parse"));
+        assert!(!text.contains("HISTORY"));
+
+        let json = text.find(" json").unwrap();
+        let xml = text.find(" xml").unwrap();
+        assert!(json < xml);
+    }
+}
@@ -0,0 +1,528 @@
+//! Self-contained HTML report
+//!
+//! A single file with inline CSS and SVG, readable without JavaScript or the
+//! WASM dashboard: per suite, the latest run compared to the one before and a
+//! trend chart for each hierarchy group. Hovering a point shows its value
+//! through the SVG `<title>`.
+
+use crate::render::{severity, Severity};
+use git_bench_core::{
+    compare_runs, format_value, BenchmarkData, BenchmarkPath, BenchmarkRun, CompareConfig,
+    CompareReport,
+};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const STYLE: &str = "
+body { font: 14px/1.5 system-ui, sans-serif; color: #1f2328; margin: 2rem auto; max-width: 1100px; padding: 0 1rem; }
+h1, h2, h3 { font-weight: 600; }
+h2 { border-bottom: 1px solid #d0d7de; padding-bottom: .3rem; margin-top: 2.5rem; }
+.meta, .muted { color: #656d76; }
+table { border-collapse: collapse; width: 100%; font-variant-numeric: tabular-nums; }
+th, td { padding: .25rem .6rem; border-bottom: 1px solid #eaeef2; text-align: right; }
+th:nth-child(-n+2), td:nth-child(-n+2) { text-align: left; }
+td.name { font-family: ui-monospace, monospace; }
+.status { font-weight: 600; }
+.fail { color: #cf222e; } .alert { color: #bc4c00; } .suspicious { color: #8250df; }
+.faster { color: #1a7f37; } .new, .removed { color: #0969da; } .ok { color: #656d76; }
+.charts { display: grid; grid-template-columns: repeat(auto-fill, minmax(480px, 1fr)); gap: 1.5rem; }
+figure { margin: 0; }
+figcaption { font-weight: 600; margin-bottom: .3rem; }
+svg text { font: 11px system-ui, sans-serif; fill: #656d76; }
+.legend { list-style: none; padding: 0; margin: .3rem 0 0; font: 12px ui-monospace, monospace; }
+.legend span { display: inline-block; width: .8em; height: .8em; margin-right: .4em; }
+";
+
+/// Line colours, cycled through within a chart
+const PALETTE: [&str; 8] = [
+    "#0969da", "#cf222e", "#1a7f37", "#8250df", "#bc4c00", "#1b7c83", "#bf3989", "#6e7781",
+];
+
+const CHART_WIDTH: f64 = 480.0;
+const CHART_HEIGHT: f64 = 200.0;
+/// Room for the axis labels
+const MARGIN_LEFT: f64 = 70.0;
+const MARGIN_BOTTOM: f64 = 20.0;
+const MARGIN_TOP: f64 = 8.0;
+
+/// Render the report for `suites`, charting at most the last `runs` runs of each
+pub fn html_report(
+    data: &BenchmarkData,
+    suites: &[&String],
+    config: &CompareConfig,
+    runs: usize,
+) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>Benchmark report</title>\n<style>{}</style>\n</head>\n<body>\n\
+         <h1>Benchmark report</h1>\n<p class=\"meta\">",
+        STYLE
+    );
+    if let Some(url) = &data.repo_url {
+        let _ = write!(html, "<a href=\"{0}\">{0}</a> · ", escape(url));
+    }
+    let _ = writeln!(
+        html,
+        "Generated {}</p>",
+        chrono::Utc::now().format("%Y-%m-%d %H:%M UTC")
+    );
+
+    for suite in suites {
+        let all = data.get_runs(suite);
+        let window = &all[all.len().saturating_sub(runs.max(1))..];
+        let _ = writeln!(html, "<section>\n<h2>{}</h2>", escape(suite));
+        let Some(latest) = all.last() else {
+            html.push_str("<p class=\"muted\">No runs stored.</p>\n</section>\n");
+            continue;
+        };
+        let _ = writeln!(
+            html,
+            "<p class=\"meta\">{} runs · latest {} ({})</p>",
+            all.len(),
+            commit_label(latest),
+            latest.commit.timestamp.format("%Y-%m-%d")
+        );
+
+        html.push_str("<h3>Latest comparison</h3>\n");
+        if all.len() < 2 {
+            html.push_str("<p class=\"muted\">Only one run stored, nothing to compare.</p>\n");
+        } else {
+            let previous = &all[all.len() - 2];
+            let report = compare_runs(previous, latest, config);
+            let _ = writeln!(
+                html,
+                "<p>{} → {}: {}</p>",
+                commit_label(previous),
+                commit_label(latest),
+                escape(&report.short_summary())
+            );
+            comparison_table(&mut html, &report, window);
+        }
+
+        html.push_str("<h3>Trends</h3>\n<div class=\"charts\">\n");
+        for (group, names) in groups(window) {
+            trend_chart(&mut html, &group, &names, window);
+        }
+        html.push_str("</div>\n</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Abbreviated commit id
+fn short(id: &str) -> String {
+    id.chars().take(7).collect()
+}
+
+fn commit_label(run: &BenchmarkRun) -> String {
+    let id = short(&run.commit.id);
+    let label = match &run.commit.url {
+        Some(url) => format!(
+            "<a href=\"{}\"><code>{}</code></a>",
+            escape(url),
+            escape(&id)
+        ),
+        None => format!("<code>{}</code>", escape(&id)),
+    };
+    match run.commit.message.lines().next() {
+        Some(message) if !message.is_empty() => format!("{} {}", label, escape(message)),
+        _ => label,
+    }
+}
+
+struct Row<'a> {
+    severity: Severity,
+    name: &'a str,
+    before: String,
+    after: String,
+    change: Option<f64>,
+}
+
+fn comparison_table(html: &mut String, report: &CompareReport, history: &[BenchmarkRun]) {
+    let mut rows: Vec<Row> = report
+        .comparisons
+        .iter()
+        .map(|comp| Row {
+            severity: severity(report, &comp.name),
+            name: &comp.name,
+            before: format_value(comp.previous, &comp.unit),
+            after: format_value(comp.current, &comp.unit),
+            change: Some(comp.percentage_change),
+        })
+        .chain(report.new_benchmarks.iter().map(|bench| Row {
+            severity: Severity::New,
+            name: &bench.name,
+            before: "-".to_string(),
+            after: format_value(bench.value, &bench.unit),
+            change: None,
+        }))
+        .chain(report.removed_benchmarks.iter().map(|bench| Row {
+            severity: Severity::Removed,
+            name: &bench.name,
+            before: format_value(bench.value, &bench.unit),
+            after: "-".to_string(),
+            change: None,
+        }))
+        .collect();
+    if rows.is_empty() {
+        return;
+    }
+    let change = |row: &Row| row.change.unwrap_or(0.0).abs();
+    rows.sort_by(|a, b| {
+        a.severity
+            .cmp(&b.severity)
+            .then(change(b).total_cmp(&change(a)))
+    });
+
+    html.push_str(
+        "<table>\n<tr><th>Status</th><th>Benchmark</th><th>Before</th><th>After</th>\
+         <th>Change</th><th>History</th></tr>\n",
+    );
+    for row in rows {
+        let class = row.severity.label().to_lowercase();
+        let values: Vec<f64> = history
+            .iter()
+            .filter_map(|run| run.benches.iter().find(|b| b.name == row.name))
+            .map(|b| b.value)
+            .collect();
+        let _ = writeln!(
+            html,
+            "<tr><td class=\"status {0}\">{1}</td><td class=\"name\">{2}</td><td>{3}</td>\
+             <td>{4}</td><td class=\"{0}\">{5}</td><td>{6}</td></tr>",
+            class,
+            row.severity.label(),
+            escape(row.name),
+            escape(&row.before),
+            escape(&row.after),
+            row.change
+                .map(|c| format!("{:+.1}%", c))
+                .unwrap_or_default(),
+            sparkline(&values)
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+/// Benchmarks of the runs keyed by their innermost hierarchy group
+fn groups(runs: &[BenchmarkRun]) -> BTreeMap<String, Vec<String>> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for bench in runs.iter().flat_map(|run| &run.benches) {
+        let group = BenchmarkPath::parse(&bench.name)
+            .groups()
+            .pop()
+            .unwrap_or_default();
+        let names = groups.entry(group).or_default();
+        if !names.contains(&bench.name) {
+            names.push(bench.name.clone());
+        }
+    }
+    for names in groups.values_mut() {
+        names.sort();
+    }
+    groups
+}
+
+/// A small inline SVG line of the values, scaled between their minimum and maximum
+fn sparkline(values: &[f64]) -> String {
+    let values: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    if values.len() < 2 {
+        return String::new();
+    }
+    let (width, height) = (80.0, 18.0);
+    let scale = Scale::new(&values, height, 0.0);
+    let step = width / (values.len() - 1) as f64;
+    let points: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| format!("{:.1},{:.1}", i as f64 * step, scale.y(*v)))
+        .collect();
+    format!(
+        "<svg width=\"{}\" height=\"{}\" viewBox=\"-1 -1 {} {}\" role=\"img\">\
+         <polyline fill=\"none\" stroke=\"#0969da\" stroke-width=\"1.5\" points=\"{}\"/></svg>",
+        width,
+        height,
+        width + 2.0,
+        height + 2.0,
+        points.join(" ")
+    )
+}
+
+/// Maps values onto the vertical axis of a chart, larger values higher up
+struct Scale {
+    min: f64,
+    max: f64,
+    height: f64,
+    top: f64,
+}
+
+impl Scale {
+    fn new(values: &[f64], height: f64, top: f64) -> Self {
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let (min, max) = if max - min > f64::EPSILON * max.abs() {
+            (min, max)
+        } else {
+            // A flat line sits in the middle
+            (min - 1.0, max + 1.0)
+        };
+        Self {
+            min,
+            max,
+            height,
+            top,
+        }
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        self.top + (self.max - value) / (self.max - self.min) * self.height
+    }
+}
+
+/// One benchmark's line in a trend chart
+struct Series<'a> {
+    name: &'a str,
+    unit: String,
+    /// Run index and value
+    points: Vec<(usize, f64)>,
+}
+
+/// Trend charts of a group, one per unit so that every chart shares one scale
+fn trend_chart(html: &mut String, group: &str, names: &[String], runs: &[BenchmarkRun]) {
+    let mut by_unit: BTreeMap<String, Vec<Series>> = BTreeMap::new();
+    for name in names {
+        let mut unit = String::new();
+        let points = runs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, run)| {
+                let bench = run.benches.iter().find(|b| b.name == *name)?;
+                unit.clone_from(&bench.unit);
+                bench.value.is_finite().then_some((i, bench.value))
+            })
+            .collect();
+        by_unit
+            .entry(unit.clone())
+            .or_default()
+            .push(Series { name, unit, points });
+    }
+
+    let title = if group.is_empty() {
+        "(ungrouped)"
+    } else {
+        group
+    };
+    let split = by_unit.len() > 1;
+    for (unit, series) in &by_unit {
+        let title = if split {
+            format!("{} ({})", title, unit)
+        } else {
+            title.to_string()
+        };
+        unit_chart(html, &title, series, runs);
+    }
+}
+
+fn unit_chart(html: &mut String, title: &str, series: &[Series], runs: &[BenchmarkRun]) {
+    let values: Vec<f64> = series
+        .iter()
+        .flat_map(|series| series.points.iter().map(|p| p.1))
+        .collect();
+    if values.is_empty() {
+        return;
+    }
+
+    let plot_width = CHART_WIDTH - MARGIN_LEFT - 8.0;
+    let plot_height = CHART_HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let scale = Scale::new(&values, plot_height, MARGIN_TOP);
+    let step = plot_width / runs.len().saturating_sub(1).max(1) as f64;
+    let x = |i: usize| MARGIN_LEFT + i as f64 * step;
+    let unit = &series[0].unit;
+
+    let _ = write!(
+        html,
+        "<figure>\n<figcaption>{}</figcaption>\n\
+         <svg width=\"100%\" viewBox=\"0 0 {} {}\" role=\"img\">\n\
+         <line x1=\"{3}\" y1=\"{4}\" x2=\"{3}\" y2=\"{5}\" stroke=\"#d0d7de\"/>\
+         <line x1=\"{3}\" y1=\"{5}\" x2=\"{6}\" y2=\"{5}\" stroke=\"#d0d7de\"/>\n\
+         <text x=\"{7}\" y=\"{8}\" text-anchor=\"end\">{9}</text>\
+         <text x=\"{7}\" y=\"{5}\" text-anchor=\"end\">{10}</text>\n\
+         <text x=\"{3}\" y=\"{11}\">{12}</text>\
+         <text x=\"{6}\" y=\"{11}\" text-anchor=\"end\">{13}</text>\n",
+        escape(title),
+        CHART_WIDTH,
+        CHART_HEIGHT,
+        MARGIN_LEFT,
+        MARGIN_TOP,
+        MARGIN_TOP + plot_height,
+        MARGIN_LEFT + plot_width,
+        MARGIN_LEFT - 6.0,
+        MARGIN_TOP + 10.0,
+        escape(&format_value(scale.max, unit)),
+        escape(&format_value(scale.min, unit)),
+        CHART_HEIGHT - 4.0,
+        escape(&short(&runs[0].commit.id)),
+        escape(&short(&runs[runs.len() - 1].commit.id)),
+    );
+
+    let mut legend = String::from("<ul class=\"legend\">");
+    for (index, Series { name, unit, points }) in series.iter().enumerate() {
+        let color = PALETTE[index % PALETTE.len()];
+        let line: Vec<String> = points
+            .iter()
+            .map(|&(i, v)| format!("{:.1},{:.1}", x(i), scale.y(v)))
+            .collect();
+        let _ = writeln!(
+            html,
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" points=\"{}\"/>",
+            color,
+            line.join(" ")
+        );
+        for &(i, v) in points {
+            let _ = writeln!(
+                html,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"2.5\" fill=\"{}\"><title>{} @ {}: {}</title></circle>",
+                x(i),
+                scale.y(v),
+                color,
+                escape(name),
+                escape(&short(&runs[i].commit.id)),
+                escape(&format_value(v, unit))
+            );
+        }
+        let _ = write!(
+            legend,
+            "<li><span style=\"background:{}\"></span>{}</li>",
+            color,
+            escape(&BenchmarkPath::parse(name).test)
+        );
+    }
+    legend.push_str("</ul>");
+    let _ = writeln!(html, "</svg>\n{}\n</figure>", legend);
+}
+
+/// Escape text for HTML content and attribute values
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use git_bench_core::{BenchmarkResult, CommitInfo};
+    use std::collections::HashMap;
+
+    fn run(id: &str, day: i64, values: &[(&str, f64)]) -> BenchmarkRun {
+        BenchmarkRun {
+            commit: CommitInfo {
+                id: id.to_string(),
+                message: format!("Change <{}>", id),
+                timestamp: Utc::now() - Duration::days(10 - day),
+                url: None,
+                author: None,
+            },
+            date: Utc::now(),
+            tool: "cargo".to_string(),
+            benches: values
+                .iter()
+                .map(|(name, value)| BenchmarkResult {
+                    name: name.to_string(),
+                    value: *value,
+                    unit: "ns/iter".to_string(),
+                    range: None,
+                    extra: HashMap::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_html_report() {
+        let mut data = BenchmarkData::new();
+        data.repo_url = Some("https://github.com/owner/repo".to_string());
+        for (day, (json, xml)) in [(100.0, 50.0), (110.0, 50.0), (400.0, 45.0)]
+            .into_iter()
+            .enumerate()
+        {
+            let id = format!("{}{}", day, "a".repeat(39));
+            data.insert_run(
+                "cargo",
+                run(
+                    &id,
+                    day as i64,
+                    &[("parse::json", json), ("parse::xml", xml), ("alloc", 1.0)],
+                ),
+            );
+        }
+        let suite = "cargo".to_string();
+        let config = CompareConfig::from_thresholds("150%", None).unwrap();
+        let html = html_report(&data, &[&suite], &config, 2);
+
+        // Self-contained and script-free
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("src="));
+        assert!(html.contains("<a href=\"https://github.com/owner/repo\">"));
+
+        // Latest comparison, worst first, with escaped commit messages
+        assert!(html.contains("<code>1aaaaaa</code> Change &lt;1aaa"));
+        let fail = html.find("<td class=\"status fail\">FAIL</td><td class=\"name\">parse::json</td><td>110 ns</td><td>400 ns</td>").unwrap();
+        let ok = html.find("<td class=\"name\">parse::xml</td>").unwrap();
+        assert!(fail < ok);
+        assert!(html.contains("+263.6%"));
+
+        // One chart per group over the last two runs
+        assert_eq!(html.matches("<figure>").count(), 2);
+        assert!(html.contains("<figcaption>parse</figcaption>"));
+        assert!(html.contains("<figcaption>(ungrouped)</figcaption>"));
+        assert!(html.contains("<title>parse::json @ 2aaaaaa: 400 ns</title>"));
+        assert!(!html.contains("@ 0aaaaaa"));
+        assert!(html.contains("<li><span style=\"background:#cf222e\"></span>xml</li>"));
+    }
+
+    #[test]
+    fn test_trend_chart_per_unit() {
+        let runs: Vec<BenchmarkRun> = (0..2)
+            .map(|day| {
+                let mut run = run(
+                    &format!("{}{}", day, "é".repeat(8)),
+                    day,
+                    &[("io::read", 10.0), ("io::rss", 2048.0)],
+                );
+                run.benches[1].unit = "bytes".to_string();
+                run
+            })
+            .collect();
+        let names = vec!["io::read".to_string(), "io::rss".to_string()];
+        let mut html = String::new();
+        trend_chart(&mut html, "io", &names, &runs);
+
+        assert_eq!(html.matches("<figure>").count(), 2);
+        assert!(html.contains("<figcaption>io (bytes)</figcaption>"));
+        assert!(html.contains("<figcaption>io (ns/iter)</figcaption>"));
+        assert!(commit_label(&runs[1]).contains("<code>1éééééé</code>"));
+    }
+}
@@ -0,0 +1,367 @@
+//! Local dashboard server with live reload
+//!
+//! Serves a dashboard dist directory, with `data.json` generated from the data
+//! file on every request. HTML pages get a small script that listens for
+//! server-sent events; the data file is polled and every change sends a
+//! reload event, so a `store` shows up in the open page right away.
+
+use git_bench_core::BenchmarkData;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Path of the server-sent event stream announcing reloads
+const EVENTS_PATH: &str = "/__git-bench/events";
+
+/// Script added to HTML pages to reload them when the data changes
+const RELOAD_SCRIPT: &str = "<script>new EventSource(\"/__git-bench/events\")\
+    .addEventListener(\"reload\", () => location.reload());</script>";
+
+/// How long an idle event stream waits before sending a keep-alive comment
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// What the server serves
+#[derive(Debug, Clone)]
+pub struct ServeConfig {
+    /// Built dashboard (index.html, wasm, assets)
+    pub dist: PathBuf,
+    /// Benchmark data served as `data.json`
+    pub data_file: PathBuf,
+    /// How often the data file is checked for changes
+    pub poll_interval: Duration,
+}
+
+/// Number of changes seen to the data file, to wake up event streams
+#[derive(Default)]
+struct Changes {
+    generation: Mutex<u64>,
+    changed: Condvar,
+}
+
+/// Serve requests on `listener` until the process exits
+pub fn serve(listener: TcpListener, config: ServeConfig) -> anyhow::Result<()> {
+    let config = Arc::new(config);
+    let changes = Arc::new(Changes::default());
+
+    {
+        let config = Arc::clone(&config);
+        let changes = Arc::clone(&changes);
+        thread::spawn(move || watch(&config, &changes));
+    }
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::warn!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        let config = Arc::clone(&config);
+        let changes = Arc::clone(&changes);
+        thread::spawn(move || {
+            if let Err(e) = handle(stream, &config, &changes) {
+                tracing::debug!("Connection closed: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Poll the data file and bump the generation whenever it changes
+fn watch(config: &ServeConfig, changes: &Changes) {
+    let stamp = |path: &Path| -> Option<(SystemTime, u64)> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    };
+
+    let mut last = stamp(&config.data_file);
+    loop {
+        thread::sleep(config.poll_interval);
+        let current = stamp(&config.data_file);
+        if current != last {
+            last = current;
+            tracing::info!("{:?} changed, reloading the dashboard", config.data_file);
+            *changes.generation.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+            changes.changed.notify_all();
+        }
+    }
+}
+
+fn handle(stream: TcpStream, config: &ServeConfig, changes: &Changes) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip the headers
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or("/"));
+    let path = target.split(['?', '#']).next().unwrap_or("/");
+    let mut stream = stream;
+
+    if method != "GET" && method != "HEAD" {
+        return respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            b"Method not allowed",
+        );
+    }
+
+    if path == EVENTS_PATH {
+        return events(stream, changes);
+    }
+
+    let (status, content_type, body) = route(path, config);
+    let body = if method == "HEAD" { Vec::new() } else { body };
+    respond(&mut stream, status, content_type, &body)
+}
+
+/// Resolve a request path to a status, content type and body
+fn route(path: &str, config: &ServeConfig) -> (&'static str, &'static str, Vec<u8>) {
+    if path.ends_with("/data.json") {
+        return match BenchmarkData::load_from_file(&config.data_file)
+            .map_err(|e| e.to_string())
+            .and_then(|data| serde_json::to_vec_pretty(&data).map_err(|e| e.to_string()))
+        {
+            Ok(json) => ("200 OK", "application/json", json),
+            Err(e) => (
+                "500 Internal Server Error",
+                "text/plain",
+                format!("Failed to load {:?}: {}", config.data_file, e).into_bytes(),
+            ),
+        };
+    }
+
+    let Some(file) = resolve_path(&config.dist, path) else {
+        return ("404 Not Found", "text/plain", b"Not found".to_vec());
+    };
+    match std::fs::read(&file) {
+        Ok(content) => {
+            let content_type = content_type(&file);
+            if content_type.starts_with("text/html") {
+                ("200 OK", content_type, inject_reload(&content))
+            } else {
+                ("200 OK", content_type, content)
+            }
+        }
+        Err(_) if !config.dist.join("index.html").exists() => (
+            "404 Not Found",
+            "text/html; charset=utf-8",
+            inject_reload(
+                format!(
+                    "<html><body><p>No dashboard in {:?}; build it with <code>make build</code>. \
+                     The data is at <a href=\"/data.json\">data.json</a>.</p></body></html>",
+                    config.dist
+                )
+                .as_bytes(),
+            ),
+        ),
+        Err(_) => ("404 Not Found", "text/plain", b"Not found".to_vec()),
+    }
+}
+
+/// Map a request path into the dist directory. Directories and extensionless
+/// paths (client-side routes) get `index.html`; paths leaving the directory
+/// are rejected.
+fn resolve_path(dist: &Path, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let file = dist.join(relative);
+    if file.is_dir() {
+        Some(file.join("index.html"))
+    } else if file.exists() || relative.extension().is_some() {
+        Some(file)
+    } else {
+        Some(dist.join("index.html"))
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "js" | "mjs" => "text/javascript",
+        "wasm" => "application/wasm",
+        "css" => "text/css",
+        "json" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "ico" => "image/x-icon",
+        "woff2" => "font/woff2",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Add the reload script before `</body>`, or at the end without one
+fn inject_reload(html: &[u8]) -> Vec<u8> {
+    let html = String::from_utf8_lossy(html);
+    match html.rfind("</body>") {
+        Some(position) => format!(
+            "{}{}{}",
+            &html[..position],
+            RELOAD_SCRIPT,
+            &html[position..]
+        )
+        .into_bytes(),
+        None => format!("{}{}", html, RELOAD_SCRIPT).into_bytes(),
+    }
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+/// Stream a `reload` event for every change until the client goes away
+fn events(mut stream: TcpStream, changes: &Changes) -> std::io::Result<()> {
+    let mut seen = *changes.generation.lock().unwrap_or_else(|e| e.into_inner());
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+    )?;
+    stream.flush()?;
+
+    loop {
+        let generation = changes.generation.lock().unwrap_or_else(|e| e.into_inner());
+        let (generation, _) = changes
+            .changed
+            .wait_timeout_while(generation, KEEP_ALIVE, |generation| *generation == seen)
+            .unwrap_or_else(|e| e.into_inner());
+        let current = *generation;
+        drop(generation);
+
+        if current == seen {
+            stream.write_all(b": keep-alive\n\n")?;
+        } else {
+            seen = current;
+            write!(stream, "event: reload\ndata: {}\n\n", current)?;
+        }
+        stream.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    fn start(dir: &TempDir) -> std::net::SocketAddr {
+        let dist = dir.path().join("dist");
+        std::fs::create_dir_all(dist.join("assets")).unwrap();
+        std::fs::write(
+            dist.join("index.html"),
+            "<html><body>dashboard</body></html>",
+        )
+        .unwrap();
+        std::fs::write(dist.join("assets/app.wasm"), b"\0asm").unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let config = ServeConfig {
+            dist,
+            data_file: dir.path().join("data.json"),
+            poll_interval: Duration::from_millis(20),
+        };
+        thread::spawn(move || serve(listener, config));
+        addr
+    }
+
+    fn get(addr: std::net::SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("assets")).unwrap();
+        let dist = dir.path();
+
+        assert_eq!(resolve_path(dist, "/"), Some(dist.join("index.html")));
+        assert_eq!(
+            resolve_path(dist, "/assets"),
+            Some(dist.join("assets/index.html"))
+        );
+        // Client-side routes fall back to the app
+        assert_eq!(
+            resolve_path(dist, "/suite/cargo"),
+            Some(dist.join("index.html"))
+        );
+        assert_eq!(
+            resolve_path(dist, "/missing.js"),
+            Some(dist.join("missing.js"))
+        );
+        assert_eq!(resolve_path(dist, "/../secret"), None);
+    }
+
+    #[test]
+    fn test_serves_dashboard_and_generated_data() {
+        let dir = TempDir::new().unwrap();
+        let addr = start(&dir);
+
+        let page = get(addr, "/");
+        assert!(page.starts_with("HTTP/1.1 200 OK"));
+        assert!(page.contains(&format!("dashboard{}</body>", RELOAD_SCRIPT)));
+
+        assert!(get(addr, "/assets/app.wasm").contains("Content-Type: application/wasm"));
+        assert!(get(addr, "/nope.css").starts_with("HTTP/1.1 404"));
+
+        // A missing data file is served as empty data
+        assert!(get(addr, "/data.json").contains("\"entries\": {}"));
+
+        let mut data = BenchmarkData::new();
+        data.entries.insert("cargo".to_string(), Vec::new());
+        data.save_to_file(&dir.path().join("data.json")).unwrap();
+        assert!(get(addr, "/data.json?t=1").contains("\"cargo\": []"));
+    }
+
+    #[test]
+    fn test_sends_reload_event_on_change() {
+        let dir = TempDir::new().unwrap();
+        let addr = start(&dir);
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\n\r\n", EVENTS_PATH).unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        while line != "\r\n" {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+        }
+
+        BenchmarkData::new()
+            .save_to_file(&dir.path().join("data.json"))
+            .unwrap();
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "event: reload\n");
+    }
+}
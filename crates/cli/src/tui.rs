@@ -0,0 +1,612 @@
+//! Interactive terminal explorer of the stored history
+//!
+//! A tree of suites and hierarchy groups (like the dashboard), a chart of the
+//! selected benchmark, the suite's commits with a from/to selection compared
+//! by the core comparison logic, and a search over benchmark names.
+
+use crate::render::{render_report, RenderOptions};
+use git_bench_core::{compare_runs, format_value, BenchmarkData, BenchmarkPath, CompareConfig};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::{
+    Axis, Block, Chart, Dataset, GraphType, List, ListItem, ListState, Paragraph,
+};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// A row of the benchmark tree
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    key: String,
+    label: String,
+    depth: usize,
+    suite: String,
+    /// Full benchmark name for leaves
+    benchmark: Option<String>,
+    expanded: bool,
+}
+
+/// Which pane receives the arrow keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Tree,
+    Commits,
+}
+
+/// Explorer state, independent of the terminal
+pub struct App<'a> {
+    data: &'a BenchmarkData,
+    config: CompareConfig,
+    nodes: Vec<Node>,
+    expanded: HashSet<String>,
+    selected: usize,
+    focus: Focus,
+    /// Selected commit, as an index into the suite's runs
+    commit: usize,
+    from: Option<usize>,
+    to: Option<usize>,
+    search: String,
+    searching: bool,
+    quit: bool,
+}
+
+impl<'a> App<'a> {
+    /// Start with `suite` (or the only suite) expanded
+    pub fn new(data: &'a BenchmarkData, config: CompareConfig, suite: Option<&str>) -> Self {
+        let mut expanded = HashSet::new();
+        match suite {
+            Some(suite) => {
+                expanded.insert(suite.to_string());
+            }
+            None if data.entries.len() == 1 => expanded.extend(data.entries.keys().cloned()),
+            None => {}
+        }
+
+        let mut app = Self {
+            data,
+            config,
+            nodes: Vec::new(),
+            expanded,
+            selected: 0,
+            focus: Focus::Tree,
+            commit: 0,
+            from: None,
+            to: None,
+            search: String::new(),
+            searching: false,
+            quit: false,
+        };
+        app.rebuild();
+        app.reset_commits();
+        app
+    }
+
+    fn rebuild(&mut self) {
+        let key = self.nodes.get(self.selected).map(|node| node.key.clone());
+        self.nodes = build_tree(self.data, &self.search, &self.expanded);
+        self.selected = key
+            .and_then(|key| self.nodes.iter().position(|node| node.key == key))
+            .unwrap_or(0)
+            .min(self.nodes.len().saturating_sub(1));
+    }
+
+    fn node(&self) -> Option<&Node> {
+        self.nodes.get(self.selected)
+    }
+
+    fn suite(&self) -> Option<&str> {
+        self.node().map(|node| node.suite.as_str())
+    }
+
+    fn runs(&self) -> &'a [git_bench_core::BenchmarkRun] {
+        self.suite()
+            .map(|suite| self.data.get_runs(suite))
+            .unwrap_or_default()
+    }
+
+    fn reset_commits(&mut self) {
+        self.commit = self.runs().len().saturating_sub(1);
+        self.from = None;
+        self.to = None;
+    }
+
+    fn select(&mut self, index: usize) {
+        let suite = self.suite().map(String::from);
+        self.selected = index.min(self.nodes.len().saturating_sub(1));
+        if self.suite().map(String::from) != suite {
+            self.reset_commits();
+        }
+    }
+
+    /// Handle a key press
+    pub fn handle_key(&mut self, key: KeyCode) {
+        if self.searching {
+            match key {
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.search.clear();
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Backspace => {
+                    self.search.pop();
+                }
+                KeyCode::Char(c) => self.search.push(c),
+                _ => return,
+            }
+            self.rebuild();
+            return;
+        }
+
+        match (key, self.focus) {
+            (KeyCode::Char('q') | KeyCode::Esc, _) => self.quit = true,
+            (KeyCode::Char('/'), _) => self.searching = true,
+            (KeyCode::Tab, Focus::Tree) => self.focus = Focus::Commits,
+            (KeyCode::Tab, Focus::Commits) => self.focus = Focus::Tree,
+
+            (KeyCode::Up | KeyCode::Char('k'), Focus::Tree) => {
+                self.select(self.selected.saturating_sub(1))
+            }
+            (KeyCode::Down | KeyCode::Char('j'), Focus::Tree) => self.select(self.selected + 1),
+            (KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter, Focus::Tree) => {
+                if let Some(node) = self.node().filter(|node| node.benchmark.is_none()) {
+                    self.expanded.insert(node.key.clone());
+                    self.rebuild();
+                }
+            }
+            (KeyCode::Left | KeyCode::Char('h'), Focus::Tree) => self.collapse(),
+
+            // Commits are listed newest first
+            (KeyCode::Up | KeyCode::Char('k'), Focus::Commits) => {
+                self.commit = (self.commit + 1).min(self.runs().len().saturating_sub(1))
+            }
+            (KeyCode::Down | KeyCode::Char('j'), Focus::Commits) => {
+                self.commit = self.commit.saturating_sub(1)
+            }
+            (KeyCode::Char('f'), Focus::Commits) if !self.runs().is_empty() => {
+                self.from = Some(self.commit)
+            }
+            (KeyCode::Char('t'), Focus::Commits) if !self.runs().is_empty() => {
+                self.to = Some(self.commit)
+            }
+            (KeyCode::Char('c'), _) => {
+                self.from = None;
+                self.to = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Collapse the selected group, or move to the parent of a collapsed node
+    fn collapse(&mut self) {
+        let Some(node) = self.node().cloned() else {
+            return;
+        };
+        if node.expanded && self.search.is_empty() {
+            self.expanded.remove(&node.key);
+            self.rebuild();
+        } else if let Some(parent) = self.nodes[..self.selected]
+            .iter()
+            .rposition(|n| n.depth < node.depth)
+        {
+            self.select(parent);
+        }
+    }
+
+    /// Whether the user asked to leave
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Draw the whole explorer
+    pub fn draw(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        let [tree, right] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Fill(1)]).areas(main);
+        let [chart, bottom] =
+            Layout::vertical([Constraint::Percentage(55), Constraint::Fill(1)]).areas(right);
+        let [commits, compare] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Fill(1)]).areas(bottom);
+
+        self.draw_tree(frame, tree);
+        self.draw_chart(frame, chart);
+        self.draw_commits(frame, commits);
+        self.draw_compare(frame, compare);
+
+        let help = if self.searching {
+            format!("/{}▏ (enter: keep, esc: clear)", self.search)
+        } else {
+            "q quit · / search · ←→ fold · tab switch pane · f/t from/to commit · c clear"
+                .to_string()
+        };
+        frame.render_widget(
+            Paragraph::new(help).style(Style::new().fg(Color::DarkGray)),
+            status,
+        );
+    }
+
+    fn pane(&self, title: String, focus: Option<Focus>) -> Block<'static> {
+        let block = Block::bordered().title(title);
+        if focus.is_some() && focus == Some(self.focus) {
+            block.border_style(Style::new().fg(Color::Cyan))
+        } else {
+            block
+        }
+    }
+
+    fn draw_tree(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let marker = match (&node.benchmark, node.expanded) {
+                    (Some(_), _) => "  ",
+                    (None, true) => "▾ ",
+                    (None, false) => "▸ ",
+                };
+                ListItem::new(format!(
+                    "{}{}{}",
+                    "  ".repeat(node.depth),
+                    marker,
+                    node.label
+                ))
+            })
+            .collect();
+
+        let title = if self.search.is_empty() {
+            " Benchmarks ".to_string()
+        } else {
+            format!(" Benchmarks matching '{}' ", self.search)
+        };
+        let list = List::new(items)
+            .block(self.pane(title, Some(Focus::Tree)))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn draw_chart(&self, frame: &mut Frame, area: Rect) {
+        let Some(name) = self.node().and_then(|node| node.benchmark.as_deref()) else {
+            let hint = Paragraph::new("Select a benchmark to chart its history")
+                .block(self.pane(" History ".to_string(), None));
+            frame.render_widget(hint, area);
+            return;
+        };
+
+        let mut unit = String::new();
+        let points: Vec<(f64, f64)> = self
+            .runs()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, run)| {
+                let bench = run.benches.iter().find(|b| b.name == name)?;
+                unit.clone_from(&bench.unit);
+                Some((i as f64, bench.value))
+            })
+            .collect();
+
+        let (min, max) = points
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+                (lo.min(p.1), hi.max(p.1))
+            });
+        let (min, max) = if min < max {
+            let pad = (max - min) * 0.05;
+            (min - pad, max + pad)
+        } else {
+            (min * 0.9, max * 1.1 + f64::EPSILON)
+        };
+        let last = self.runs().len().saturating_sub(1) as f64;
+        let commit_label = |index: f64| {
+            self.runs()
+                .get(index as usize)
+                .map(|run| short_id(&run.commit.id).to_string())
+                .unwrap_or_default()
+        };
+
+        let dataset = Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::new().fg(Color::Cyan))
+            .data(&points);
+        let chart = Chart::new(vec![dataset])
+            .block(self.pane(format!(" {} ", name), None))
+            .x_axis(
+                Axis::default()
+                    .bounds([0.0, last.max(1.0)])
+                    .labels([commit_label(0.0), commit_label(last)]),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([min, max])
+                    .labels([format_value(min, &unit), format_value(max, &unit)]),
+            );
+        frame.render_widget(chart, area);
+    }
+
+    fn draw_commits(&self, frame: &mut Frame, area: Rect) {
+        let runs = self.runs();
+        let items: Vec<ListItem> = runs
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, run)| {
+                let marker = match (Some(i) == self.from, Some(i) == self.to) {
+                    (true, true) => "FT",
+                    (true, false) => "F ",
+                    (false, true) => " T",
+                    (false, false) => "  ",
+                };
+                ListItem::new(format!(
+                    "{} {} {} {}",
+                    marker,
+                    short_id(&run.commit.id),
+                    run.commit.timestamp.format("%Y-%m-%d"),
+                    run.commit.message
+                ))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(self.pane(" Commits ".to_string(), Some(Focus::Commits)))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        let selected = (!runs.is_empty()).then(|| runs.len() - 1 - self.commit);
+        let mut state = ListState::default().with_selected(selected);
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn draw_compare(&self, frame: &mut Frame, area: Rect) {
+        let runs = self.runs();
+        let text = match (self.from, self.to) {
+            (Some(from), Some(to)) if from < runs.len() && to < runs.len() => {
+                let report = compare_runs(&runs[from], &runs[to], &self.config);
+                let options = RenderOptions::default();
+                render_report(&report, &[], &options)
+            }
+            _ => "Pick commits with f (from) and t (to) in the commit list".to_string(),
+        };
+        let title = match (self.from, self.to) {
+            (Some(from), Some(to)) if from < runs.len() && to < runs.len() => format!(
+                " {} → {} ",
+                short_id(&runs[from].commit.id),
+                short_id(&runs[to].commit.id)
+            ),
+            _ => " Compare ".to_string(),
+        };
+        let lines: Vec<Line> = text
+            .lines()
+            .map(|line| Line::raw(line.to_string()))
+            .collect();
+        frame.render_widget(Paragraph::new(lines).block(self.pane(title, None)), area);
+    }
+}
+
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(7)]
+}
+
+/// Flatten the suites and their hierarchy into the visible tree rows. With a
+/// search query, only matching benchmarks are listed, fully expanded.
+fn build_tree(data: &BenchmarkData, query: &str, expanded: &HashSet<String>) -> Vec<Node> {
+    let query = query.to_lowercase();
+    let mut suites: Vec<&String> = data.entries.keys().collect();
+    suites.sort();
+
+    let mut nodes = Vec::new();
+    for suite in suites {
+        // grandparent -> parent -> benchmark names
+        let mut groups: BTreeMap<String, BTreeMap<String, BTreeSet<&str>>> = BTreeMap::new();
+        for bench in data.get_runs(suite).iter().flat_map(|run| &run.benches) {
+            if !query.is_empty() && !bench.name.to_lowercase().contains(&query) {
+                continue;
+            }
+            let path = BenchmarkPath::parse(&bench.name);
+            groups
+                .entry(path.grandparent_key())
+                .or_default()
+                .entry(path.parent_key())
+                .or_default()
+                .insert(&bench.name);
+        }
+        if !query.is_empty() && groups.is_empty() {
+            continue;
+        }
+
+        let is_open = |key: &str| !query.is_empty() || expanded.contains(key);
+        let group = |nodes: &mut Vec<Node>, key: String, label: &str, depth: usize| {
+            let open = is_open(&key);
+            nodes.push(Node {
+                key,
+                label: label.to_string(),
+                depth,
+                suite: suite.clone(),
+                benchmark: None,
+                expanded: open,
+            });
+            open
+        };
+
+        if !group(&mut nodes, suite.clone(), suite, 0) {
+            continue;
+        }
+        for (grandparent, parents) in groups {
+            let mut depth = 1;
+            if grandparent != BenchmarkPath::UNGROUPED {
+                if !group(
+                    &mut nodes,
+                    format!("{}/{}", suite, grandparent),
+                    &grandparent,
+                    depth,
+                ) {
+                    continue;
+                }
+                depth += 1;
+            }
+            for (parent, names) in parents {
+                let mut depth = depth;
+                if parent != BenchmarkPath::UNGROUPED {
+                    let key = format!("{}/{}/{}", suite, grandparent, parent);
+                    if !group(&mut nodes, key, &parent, depth) {
+                        continue;
+                    }
+                    depth += 1;
+                }
+                for name in names {
+                    nodes.push(Node {
+                        key: format!("{}\0{}", suite, name),
+                        label: BenchmarkPath::parse(name).test,
+                        depth,
+                        suite: suite.clone(),
+                        benchmark: Some(name.to_string()),
+                        expanded: false,
+                    });
+                }
+            }
+        }
+    }
+    nodes
+}
+
+/// Run the explorer until the user quits
+pub fn run(app: &mut App, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+    while !app.should_quit() {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.handle_key(key.code);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use git_bench_core::{BenchmarkResult, BenchmarkRun, CommitInfo};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::collections::HashMap;
+
+    fn data() -> BenchmarkData {
+        let run = |id: &str, day: i64, scale: f64| BenchmarkRun {
+            commit: CommitInfo {
+                id: id.to_string(),
+                message: format!("commit {}", id),
+                timestamp: Utc::now() - Duration::days(10 - day),
+                url: None,
+                author: None,
+            },
+            date: Utc::now(),
+            tool: "cargo".to_string(),
+            benches: ["core::parse::json", "core::parse::xml", "alloc"]
+                .iter()
+                .map(|name| BenchmarkResult {
+                    name: name.to_string(),
+                    value: 100.0 * scale,
+                    unit: "ns/iter".to_string(),
+                    range: None,
+                    extra: HashMap::new(),
+                })
+                .collect(),
+        };
+
+        let mut data = BenchmarkData::new();
+        data.entries.insert(
+            "cargo".to_string(),
+            vec![
+                run("aaaaaaa1", 1, 1.0),
+                run("bbbbbbb2", 2, 1.0),
+                run("ccccccc3", 3, 3.0),
+            ],
+        );
+        data.entries
+            .insert("other".to_string(), vec![run("ddddddd4", 1, 1.0)]);
+        data
+    }
+
+    fn labels(app: &App) -> Vec<String> {
+        app.nodes
+            .iter()
+            .map(|node| format!("{}{}", " ".repeat(node.depth), node.label))
+            .collect()
+    }
+
+    fn config() -> CompareConfig {
+        CompareConfig::from_thresholds("150%", None).unwrap()
+    }
+
+    #[test]
+    fn test_tree_mirrors_hierarchy() {
+        let data = data();
+        let mut app = App::new(&data, config(), Some("cargo"));
+        assert_eq!(labels(&app), vec!["cargo", " alloc", " core", "other"]);
+
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Right);
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Right);
+        assert_eq!(
+            labels(&app),
+            vec!["cargo", " alloc", " core", "  parse", "   json", "   xml", "other"]
+        );
+
+        // Left on a leaf jumps to its group, then folds it
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Left);
+        assert_eq!(app.node().unwrap().label, "parse");
+        app.handle_key(KeyCode::Left);
+        assert_eq!(labels(&app).len(), 5);
+    }
+
+    #[test]
+    fn test_search_filters_benchmarks() {
+        let data = data();
+        let mut app = App::new(&data, config(), None);
+        for key in [KeyCode::Char('/'), KeyCode::Char('X'), KeyCode::Char('m')] {
+            app.handle_key(key);
+        }
+        assert_eq!(
+            labels(&app),
+            vec!["cargo", " core", "  parse", "   xml", "other", " core", "  parse", "   xml"]
+        );
+
+        app.handle_key(KeyCode::Esc);
+        assert_eq!(labels(&app), vec!["cargo", "other"]);
+        assert!(!app.should_quit());
+    }
+
+    #[test]
+    fn test_compare_selected_commits() {
+        let data = data();
+        let mut app = App::new(&data, config(), Some("cargo"));
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Char('t'));
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Char('f'));
+        assert_eq!((app.from, app.to), (Some(0), Some(2)));
+
+        let mut terminal = Terminal::new(TestBackend::new(140, 30)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("aaaaaaa → ccccccc"));
+        assert!(screen.contains("FAIL"));
+        assert!(screen.contains("+200.0%"));
+        // The selected benchmark is charted
+        assert!(screen.contains(" alloc "));
+        assert!(screen.contains("300 ns"));
+    }
+}
@@ -1,79 +1,16 @@
 //! Configuration validation for CLI arguments
 
 use crate::error::{Error, Result};
+use git_bench_core::{BenchmarkData, Threshold};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
-use std::time::Duration;
 
-/// Validate alert threshold format (e.g., "200%", "1.5x", "50ms")
+/// Validate alert threshold format (e.g., "200%", "1.5x", "+5%", "50ms", "+5% & +100ns")
 pub fn validate_alert_threshold(threshold: &str) -> Result<()> {
-    if threshold.is_empty() {
-        return Err(Error::Validation(
-            "Alert threshold cannot be empty".to_string(),
-        ));
-    }
-
-    // Check for percentage format (e.g., "200%")
-    if threshold.ends_with('%') {
-        let num_str = threshold.strip_suffix('%').unwrap();
-        if let Ok(num) = num_str.parse::<f64>() {
-            if num > 0.0 {
-                return Ok(());
-            }
-        }
-        return Err(Error::Validation(
-            "Alert threshold percentage must be greater than 0".to_string(),
-        ));
-    }
-
-    // Check for multiplier format (e.g., "1.5x")
-    if threshold.ends_with('x') {
-        let num_str = threshold.strip_suffix('x').unwrap();
-        if let Ok(num) = num_str.parse::<f64>() {
-            if num > 0.0 {
-                return Ok(());
-            }
-        }
-        return Err(Error::Validation(
-            "Alert threshold multiplier must be greater than 0".to_string(),
-        ));
-    }
-
-    // Check for absolute time format (e.g., "50ms", "1s")
-    if let Ok(duration) = parse_duration(threshold) {
-        if !duration.is_zero() {
-            return Ok(());
-        }
-        return Err(Error::Validation(
-            "Alert threshold duration must be greater than 0".to_string(),
-        ));
-    }
-
-    Err(Error::Validation(format!(
-        "Invalid alert threshold format: '{}'. Expected formats: '200%', '1.5x', '50ms', '1s'",
-        threshold
-    )))
-}
-
-/// Parse duration string (e.g., "50ms", "1s", "1m")
-fn parse_duration(duration_str: &str) -> Result<Duration> {
-    if let Some(millis_str) = duration_str.strip_suffix("ms") {
-        let millis: u64 = millis_str
-            .parse()
-            .map_err(|_| Error::Validation("Invalid millisecond duration".to_string()))?;
-        Ok(Duration::from_millis(millis))
-    } else if let Some(secs_str) = duration_str.strip_suffix('s') {
-        let secs: u64 = secs_str
-            .parse()
-            .map_err(|_| Error::Validation("Invalid second duration".to_string()))?;
-        Ok(Duration::from_secs(secs))
-    } else if let Some(mins_str) = duration_str.strip_suffix('m') {
-        let mins: u64 = mins_str
-            .parse()
-            .map_err(|_| Error::Validation("Invalid minute duration".to_string()))?;
-        Ok(Duration::from_secs(mins * 60))
-    } else {
-        Err(Error::Validation("Invalid duration format".to_string()))
-    }
+    Threshold::parse(threshold)
+        .map(|_| ())
+        .map_err(|e| Error::Validation(format!("Invalid alert threshold: {}", e)))
 }
 
 /// Validate GitHub token format
@@ -221,6 +158,136 @@ pub fn validate_max_items(max_items: Option<usize>) -> Result<()> {
     Ok(())
 }
 
+/// A problem in a benchmark data file, with how to repair it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataIssue {
+    pub message: String,
+    pub fix: String,
+}
+
+impl DataIssue {
+    fn new(message: String, fix: impl Into<String>) -> Self {
+        Self {
+            message,
+            fix: fix.into(),
+        }
+    }
+}
+
+/// Check the content of a data file: it must deserialize as benchmark data,
+/// without missing values (NaN is stored as `null`), empty commit ids,
+/// several runs of one commit or benchmarks changing unit within a suite
+pub fn validate_data_json(content: &str) -> Vec<DataIssue> {
+    let json: Value = match serde_json::from_str(content) {
+        Ok(json) => json,
+        Err(e) => {
+            let hint = if content.contains("NaN") || content.contains("Infinity") {
+                " (NaN and Infinity are not JSON)"
+            } else {
+                ""
+            };
+            return vec![DataIssue::new(
+                format!("Not valid JSON: {}{}", e, hint),
+                "Restore the file from version control or from the gh-pages branch \
+                 (`git show origin/gh-pages:dev/bench/data.json`)",
+            )];
+        }
+    };
+
+    let mut issues = missing_values(&json);
+    if !issues.is_empty() {
+        // The typed checks need every value in place
+        return issues;
+    }
+
+    let data: BenchmarkData = match serde_json::from_value(json) {
+        Ok(data) => data,
+        Err(e) => {
+            return vec![DataIssue::new(
+                format!("Does not match the benchmark data format: {}", e),
+                "Compare the file with one written by `git-bench store`; \
+                 github-action-benchmark data is converted by `git-bench import`",
+            )]
+        }
+    };
+
+    let mut suites: Vec<_> = data.entries.iter().collect();
+    suites.sort_by_key(|(suite, _)| suite.as_str());
+    for (suite, runs) in suites {
+        let mut commits: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut units: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for (index, run) in runs.iter().enumerate() {
+            if run.commit.id.trim().is_empty() {
+                issues.push(DataIssue::new(
+                    format!("{}: run {} has an empty commit id", suite, index),
+                    "Remove the run, or store it again with `--git-ref <commit>`",
+                ));
+            } else {
+                *commits.entry(&run.commit.id).or_default() += 1;
+            }
+            for bench in &run.benches {
+                units.entry(&bench.name).or_default().insert(&bench.unit);
+            }
+        }
+
+        for (commit, count) in commits.into_iter().filter(|(_, count)| *count > 1) {
+            issues.push(DataIssue::new(
+                format!(
+                    "{}: commit {} is stored {} times",
+                    suite,
+                    &commit[..commit.len().min(7)],
+                    count
+                ),
+                "Delete all but one of its runs; storing the commit again also replaces them",
+            ));
+        }
+        for (name, units) in units.into_iter().filter(|(_, units)| units.len() > 1) {
+            let units: Vec<&str> = units.into_iter().collect();
+            issues.push(DataIssue::new(
+                format!("{}: '{}' is recorded in {}", suite, name, units.join(", ")),
+                "Values in different units are compared as if they were the same; \
+                 give the benchmark a new name when its unit changes",
+            ));
+        }
+    }
+
+    issues
+}
+
+/// Benchmarks whose `value` is not a number
+fn missing_values(json: &Value) -> Vec<DataIssue> {
+    let mut issues = Vec::new();
+    let Some(entries) = json.get("entries").and_then(Value::as_object) else {
+        return issues;
+    };
+    for (suite, runs) in entries {
+        for (index, run) in runs.as_array().into_iter().flatten().enumerate() {
+            let commit = run
+                .pointer("/commit/id")
+                .and_then(Value::as_str)
+                .unwrap_or("?");
+            let benches = run.get("benches").and_then(Value::as_array);
+            for bench in benches.into_iter().flatten() {
+                if bench.get("value").is_some_and(Value::is_number) {
+                    continue;
+                }
+                let name = bench.get("name").and_then(Value::as_str).unwrap_or("?");
+                issues.push(DataIssue::new(
+                    format!(
+                        "{}: run {} (commit {}) has no value for '{}' (NaN or infinity when stored)",
+                        suite,
+                        index,
+                        &commit[..commit.len().min(7)],
+                        name
+                    ),
+                    "Remove the benchmark from that run, or benchmark the commit again",
+                ));
+            }
+        }
+    }
+    issues
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -292,7 +359,49 @@ mod tests {
         assert!(
             validate_file_exists(&temp_dir.path().join("nonexistent.txt"), "Test file").is_err()
         );
-        assert!(validate_file_exists(&temp_dir.path(), "Test file").is_err());
+        assert!(validate_file_exists(temp_dir.path(), "Test file").is_err());
+    }
+
+    #[test]
+    fn test_validate_data_json() {
+        let run = |id: &str, value: &str, unit: &str| {
+            format!(
+                r#"{{"commit": {{"id": "{}", "message": "", "timestamp": "2024-01-01T00:00:00Z"}},
+                   "date": "2024-01-01T00:00:00Z", "tool": "cargo",
+                   "benches": [{{"name": "parse", "value": {}, "unit": "{}"}}]}}"#,
+                id, value, unit
+            )
+        };
+        let data = |runs: &[String]| format!(r#"{{"entries": {{"cargo": [{}]}}}}"#, runs.join(","));
+
+        let clean = data(&[run("aaa", "1.0", "ns/iter"), run("bbb", "2.0", "ns/iter")]);
+        assert_eq!(validate_data_json(&clean), Vec::new());
+
+        let issues = validate_data_json(&data(&[
+            run("aaa", "1.0", "ns/iter"),
+            run("", "1.0", "ns/iter"),
+            run("aaa", "1.0", "us/iter"),
+        ]));
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "cargo: run 1 has an empty commit id",
+                "cargo: commit aaa is stored 2 times",
+                "cargo: 'parse' is recorded in ns/iter, us/iter",
+            ]
+        );
+
+        let issues = validate_data_json(&data(&[run("aaa", "null", "ns/iter")]));
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("no value for 'parse'"));
+
+        let issues = validate_data_json(&data(&[run("aaa", "NaN", "ns/iter")]));
+        assert!(issues[0].message.contains("NaN and Infinity are not JSON"));
+
+        assert!(validate_data_json(r#"{"entries": {"cargo": [{}]}}"#)[0]
+            .message
+            .starts_with("Does not match"));
     }
 
     #[test]
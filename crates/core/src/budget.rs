@@ -0,0 +1,403 @@
+//! Regression budgets: limits on cumulative drift over time
+//!
+//! Per-commit thresholds let a series of small slowdowns through. A budget
+//! compares the current run against the suite as it was some time ago (or at
+//! a baseline commit) and fails once the overall drift exceeds the limit. The
+//! drift is the geometric mean of the per-benchmark ratios, like the suite
+//! score in [`crate::compare::aggregate_scores`]; absolute limits apply to the
+//! mean slowdown of the benchmarks measured in time units.
+
+use crate::data::{BenchmarkResult, BenchmarkRun};
+use crate::stats::geometric_mean;
+use crate::threshold::{unit_to_ns, Threshold};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// What a regression budget is measured against
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetBaseline {
+    /// The latest run at least this long before now (the oldest run if the
+    /// history is shorter, see [`BudgetResult::history_too_short`])
+    Window(Duration),
+    /// The most recent run of a stored commit (id or prefix of at least 4 characters)
+    Commit(String),
+}
+
+impl fmt::Display for BudgetBaseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetBaseline::Window(window) if window.num_hours() % 24 == 0 => {
+                write!(f, "{} days ago", window.num_days())
+            }
+            BudgetBaseline::Window(window) => write!(f, "{} hours ago", window.num_hours()),
+            BudgetBaseline::Commit(commit) if is_full_sha(commit) => write!(f, "{}", &commit[..7]),
+            BudgetBaseline::Commit(commit) => write!(f, "{}", commit),
+        }
+    }
+}
+
+/// Check whether a commit reference is a full 40-character SHA
+fn is_full_sha(commit: &str) -> bool {
+    commit.len() == 40 && commit.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Maximum slowdown of a suite relative to a baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegressionBudget {
+    /// Largest allowed drift (e.g. `+10%`, `+2ms` or `+10% & +2ms`)
+    pub limit: Threshold,
+    /// Where the drift is measured from
+    pub baseline: BudgetBaseline,
+}
+
+impl RegressionBudget {
+    /// Parse a budget spec `LIMIT@BASELINE`, e.g. `+10%@30d` or `+5%@v1.0`
+    ///
+    /// The baseline is a duration (`12h`, `30d`, `4w`) or a commit reference.
+    /// References are kept as-is; callers resolve tags and branches to stored
+    /// commit ids before checking the budget.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (limit, baseline) = spec
+            .rsplit_once('@')
+            .ok_or_else(|| format!("Invalid budget '{}': expected LIMIT@BASELINE", spec))?;
+
+        let limit = Threshold::parse(limit)?;
+        if !is_slowdown(&limit) {
+            return Err(format!(
+                "Invalid budget '{}': the limit must be a slowdown such as +10%, 1.1x or +2ms",
+                spec
+            ));
+        }
+
+        let baseline = baseline.trim();
+        if baseline.is_empty() {
+            return Err(format!("Invalid budget '{}': missing baseline", spec));
+        }
+
+        Ok(Self {
+            limit,
+            baseline: parse_window(baseline)
+                .map(BudgetBaseline::Window)
+                .unwrap_or_else(|| BudgetBaseline::Commit(baseline.to_string())),
+        })
+    }
+}
+
+impl fmt::Display for RegressionBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} since {}", self.limit, self.baseline)
+    }
+}
+
+/// Check that every term of a limit requires the suite to get slower
+fn is_slowdown(limit: &Threshold) -> bool {
+    match limit {
+        Threshold::Ratio(ratio) => *ratio > 1.0,
+        Threshold::AbsoluteDelta(delta_ns) => *delta_ns > 0.0,
+        Threshold::All(terms) | Threshold::Any(terms) => terms.iter().all(is_slowdown),
+    }
+}
+
+/// Parse a window such as `12h`, `30d` or `4w`
+fn parse_window(s: &str) -> Option<Duration> {
+    let (amount, unit) = s.split_at(s.len().checked_sub(1)?);
+    let amount: i64 = amount.parse().ok().filter(|n| *n > 0)?;
+    match unit {
+        "h" => Some(Duration::hours(amount)),
+        "d" => Some(Duration::days(amount)),
+        "w" => Some(Duration::weeks(amount)),
+        _ => None,
+    }
+}
+
+/// A commit that slowed the suite down while the budget was being spent
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BudgetContribution {
+    /// Commit id (empty for the run being compared)
+    pub commit_id: String,
+    /// Commit message (first line)
+    pub commit_message: String,
+    /// Change of the suite score against the run before it
+    pub percentage_change: f64,
+}
+
+/// Outcome of checking a regression budget
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BudgetResult {
+    /// The budget as written, e.g. `110% since 30 days ago`
+    pub budget: String,
+    /// Commit of the baseline run
+    pub baseline_commit: String,
+    /// When the baseline run was taken
+    pub baseline_date: DateTime<Utc>,
+    /// Number of benchmarks present in both the baseline and the current run
+    pub benchmarks: usize,
+    /// Geometric mean of current/baseline ratios
+    pub ratio: f64,
+    /// Overall change in percent
+    pub percentage_change: f64,
+    /// Mean slowdown in nanoseconds of the benchmarks measured in time units
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean_delta_ns: Option<f64>,
+    /// The history does not reach back to the budget's window, so the drift
+    /// is measured from the oldest stored run instead
+    #[serde(default)]
+    pub history_too_short: bool,
+    /// Whether the drift exceeds the budget
+    pub is_exceeded: bool,
+    /// Commits that slowed the suite down since the baseline, largest first
+    pub contributing_commits: Vec<BudgetContribution>,
+}
+
+/// Check a budget for the current benchmarks against a suite's stored history
+/// (oldest run first), leaving out the benchmarks `is_ignored` returns true for.
+/// Returns `None` when no baseline run can be found or no benchmark is shared with it.
+pub fn check_budget(
+    budget: &RegressionBudget,
+    current_benches: &[BenchmarkResult],
+    history: &[BenchmarkRun],
+    is_ignored: impl Fn(&str) -> bool,
+    now: DateTime<Utc>,
+) -> Option<BudgetResult> {
+    let (start, history_too_short) = match &budget.baseline {
+        BudgetBaseline::Window(window) => {
+            let cutoff = now - *window;
+            match history.iter().rposition(|run| run.date <= cutoff) {
+                Some(start) => (start, false),
+                None if history.is_empty() => return None,
+                None => (0, true),
+            }
+        }
+        BudgetBaseline::Commit(commit) => (
+            history.iter().rposition(|r| r.matches_commit(commit))?,
+            false,
+        ),
+    };
+    let baseline = &history[start];
+
+    let drift = suite_drift(&baseline.benches, current_benches, &is_ignored)?;
+
+    // Every step from the baseline to the current run, attributed to its commit
+    let mut steps: Vec<(&[BenchmarkResult], &str, &str)> = history[start..]
+        .iter()
+        .map(|run| {
+            (
+                run.benches.as_slice(),
+                run.commit.id.as_str(),
+                run.commit.message.as_str(),
+            )
+        })
+        .collect();
+    steps.push((current_benches, "", ""));
+
+    let mut contributing_commits: Vec<BudgetContribution> = steps
+        .windows(2)
+        .filter_map(|w| {
+            let step = suite_drift(w[0].0, w[1].0, &is_ignored)?.ratio;
+            (step > 1.0).then(|| BudgetContribution {
+                commit_id: w[1].1.to_string(),
+                commit_message: w[1].2.lines().next().unwrap_or("").to_string(),
+                percentage_change: (step - 1.0) * 100.0,
+            })
+        })
+        .collect();
+    contributing_commits.sort_by(|a, b| b.percentage_change.total_cmp(&a.percentage_change));
+
+    Some(BudgetResult {
+        budget: budget.to_string(),
+        baseline_commit: baseline.commit.id.clone(),
+        baseline_date: baseline.date,
+        benchmarks: drift.benchmarks,
+        ratio: drift.ratio,
+        percentage_change: (drift.ratio - 1.0) * 100.0,
+        mean_delta_ns: drift.mean_delta_ns,
+        history_too_short,
+        // Without time benchmarks nothing slowed down in time, so absolute limits hold
+        is_exceeded: budget.limit.is_exceeded_by(
+            drift.ratio,
+            drift.mean_delta_ns.unwrap_or(0.0),
+            "ns",
+        ),
+        contributing_commits,
+    })
+}
+
+/// How far the benchmarks two runs share moved
+struct Drift {
+    /// Geometric mean of the current/previous ratios
+    ratio: f64,
+    /// Number of benchmarks compared
+    benchmarks: usize,
+    /// Mean slowdown in nanoseconds of the benchmarks measured in time units
+    mean_delta_ns: Option<f64>,
+}
+
+/// Drift over the benchmarks both runs share, leaving out ignored benchmarks
+fn suite_drift(
+    previous: &[BenchmarkResult],
+    current: &[BenchmarkResult],
+    is_ignored: &impl Fn(&str) -> bool,
+) -> Option<Drift> {
+    let previous: HashMap<&str, &BenchmarkResult> =
+        previous.iter().map(|b| (b.name.as_str(), b)).collect();
+    let pairs: Vec<(&BenchmarkResult, &BenchmarkResult)> = current
+        .iter()
+        .filter(|b| !is_ignored(&b.name))
+        .filter_map(|b| {
+            let prev = *previous.get(b.name.as_str())?;
+            (prev.value > 0.0 && b.value > 0.0 && prev.unit == b.unit).then_some((prev, b))
+        })
+        .collect();
+
+    let ratios: Vec<f64> = pairs.iter().map(|(prev, b)| b.value / prev.value).collect();
+    let deltas_ns: Vec<f64> = pairs
+        .iter()
+        .filter_map(|(prev, b)| Some((b.value - prev.value) * unit_to_ns(&b.unit)?))
+        .collect();
+
+    Some(Drift {
+        ratio: geometric_mean(&ratios)?,
+        benchmarks: ratios.len(),
+        mean_delta_ns: (!deltas_ns.is_empty())
+            .then(|| deltas_ns.iter().sum::<f64>() / deltas_ns.len() as f64),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::CommitInfo;
+
+    fn make_run(id: &str, days_ago: i64, values: &[(&str, f64)]) -> BenchmarkRun {
+        let date = Utc::now() - Duration::days(days_ago);
+        BenchmarkRun {
+            commit: CommitInfo {
+                id: id.to_string(),
+                message: format!("commit {}", id),
+                timestamp: date,
+                url: None,
+                author: None,
+            },
+            date,
+            tool: "cargo".to_string(),
+            benches: values
+                .iter()
+                .map(|(name, value)| BenchmarkResult {
+                    name: name.to_string(),
+                    value: *value,
+                    unit: "ns/iter".to_string(),
+                    range: None,
+                    extra: HashMap::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn history() -> Vec<BenchmarkRun> {
+        vec![
+            make_run("aaaa", 60, &[("a", 80.0), ("b", 80.0)]),
+            make_run("bbbb", 40, &[("a", 100.0), ("b", 100.0)]),
+            make_run("cccc", 20, &[("a", 104.0), ("b", 104.0)]),
+            make_run("dddd", 10, &[("a", 102.0), ("b", 102.0)]),
+            make_run("eeee", 5, &[("a", 108.0), ("b", 108.0)]),
+        ]
+    }
+
+    #[test]
+    fn test_parse_budget() {
+        let budget = RegressionBudget::parse("+10%@30d").unwrap();
+        assert_eq!(budget.limit, Threshold::Ratio(1.1));
+        assert_eq!(budget.baseline, BudgetBaseline::Window(Duration::days(30)));
+        assert_eq!(budget.to_string(), "110% since 30 days ago");
+
+        let budget = RegressionBudget::parse("1.05x@v1.0").unwrap();
+        assert_eq!(budget.baseline, BudgetBaseline::Commit("v1.0".to_string()));
+
+        assert_eq!(
+            RegressionBudget::parse("+10%@2w").unwrap().baseline,
+            BudgetBaseline::Window(Duration::weeks(2))
+        );
+        assert!(RegressionBudget::parse("+10%").is_err());
+        assert!(RegressionBudget::parse("10%@30d").is_err());
+        assert!(RegressionBudget::parse("+5ns@30d").is_ok());
+        assert!(RegressionBudget::parse("+10% & +5ns@30d").is_ok());
+        assert!(RegressionBudget::parse("-5ns@30d").is_err());
+        assert!(RegressionBudget::parse("+10%@").is_err());
+    }
+
+    #[test]
+    fn test_budget_window() {
+        let current = [("a", 112.0), ("b", 112.0)];
+        let current = make_run("", 0, &current).benches;
+        let budget = RegressionBudget::parse("+10%@30d").unwrap();
+
+        let result = check_budget(&budget, &current, &history(), |_| false, Utc::now()).unwrap();
+
+        // 30 days ago the suite was at 100 (commit bbbb)
+        assert_eq!(result.baseline_commit, "bbbb");
+        assert_eq!(result.benchmarks, 2);
+        assert!((result.percentage_change - 12.0).abs() < 1e-9);
+        assert!(result.is_exceeded);
+
+        let commits: Vec<&str> = result
+            .contributing_commits
+            .iter()
+            .map(|c| c.commit_id.as_str())
+            .collect();
+        assert_eq!(commits, vec!["eeee", "cccc", ""]);
+        assert_eq!(result.contributing_commits[1].commit_message, "commit cccc");
+    }
+
+    #[test]
+    fn test_budget_commit_baseline() {
+        let current = make_run("", 0, &[("a", 84.0), ("b", 84.0)]).benches;
+        let budget = RegressionBudget::parse("+10%@aaaa").unwrap();
+        let result = check_budget(&budget, &current, &history(), |_| false, Utc::now()).unwrap();
+        assert_eq!(result.baseline_commit, "aaaa");
+        assert!(!result.is_exceeded);
+
+        let missing = RegressionBudget::parse("+10%@ffff").unwrap();
+        assert!(check_budget(&missing, &current, &history(), |_| false, Utc::now()).is_none());
+    }
+
+    #[test]
+    fn test_budget_short_history_uses_oldest_run() {
+        let current = make_run("", 0, &[("a", 100.0)]).benches;
+        let budget = RegressionBudget::parse("+10%@365d").unwrap();
+        let result = check_budget(&budget, &current, &history(), |_| false, Utc::now()).unwrap();
+        assert_eq!(result.baseline_commit, "aaaa");
+        assert_eq!(result.benchmarks, 1);
+        assert!(result.history_too_short);
+        assert!(result.is_exceeded);
+    }
+
+    #[test]
+    fn test_budget_absolute_limit() {
+        let current = make_run("", 0, &[("a", 112.0), ("b", 106.0)]).benches;
+
+        let result = check_budget(
+            &RegressionBudget::parse("+10ns@30d").unwrap(),
+            &current,
+            &history(),
+            |_| false,
+            Utc::now(),
+        )
+        .unwrap();
+        assert_eq!(result.mean_delta_ns, Some(9.0));
+        assert!(!result.history_too_short);
+        assert!(!result.is_exceeded);
+
+        let result = check_budget(
+            &RegressionBudget::parse("+5ns@30d").unwrap(),
+            &current,
+            &history(),
+            |name| name == "b",
+            Utc::now(),
+        )
+        .unwrap();
+        assert_eq!(result.mean_delta_ns, Some(12.0));
+        assert!(result.is_exceeded);
+    }
+}
@@ -0,0 +1,312 @@
+//! Change-point detection over benchmark history
+//!
+//! Gradual regressions never cross a per-commit threshold, but they do shift
+//! the distribution of a benchmark over time. This module runs binary
+//! segmentation over each benchmark's series: the split maximising the
+//! standardized difference of means (a CUSUM statistic) is kept when a
+//! Welch's t-test between the two segments, Bonferroni-corrected for the
+//! number of candidate splits, says it is unlikely to be noise. Both halves
+//! are then searched again.
+
+use crate::data::BenchmarkRun;
+use crate::stats::{welch_t_test, Estimate};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Configuration for change-point detection
+#[derive(Debug, Clone)]
+pub struct ChangePointConfig {
+    /// Minimum number of runs on each side of a change point
+    pub min_segment: usize,
+    /// Minimum confidence (1 - corrected p-value) to report a change point
+    pub confidence: f64,
+    /// Minimum absolute percentage shift to report
+    pub min_change_percent: f64,
+}
+
+impl Default for ChangePointConfig {
+    fn default() -> Self {
+        Self {
+            min_segment: 3,
+            confidence: 0.95,
+            min_change_percent: 1.0,
+        }
+    }
+}
+
+/// A detected shift in a benchmark's distribution
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChangePoint {
+    /// Benchmark name
+    pub benchmark: String,
+    /// Commit of the first run after the shift
+    pub commit_id: String,
+    /// Commit message (first line)
+    pub commit_message: String,
+    /// Date of the first run after the shift
+    pub date: DateTime<Utc>,
+    /// Mean value of the segment before the shift
+    pub before: f64,
+    /// Mean value of the segment after the shift
+    pub after: f64,
+    /// Percentage change of the mean
+    pub percentage_change: f64,
+    /// Confidence that the shift is real (1 - corrected p-value)
+    pub confidence: f64,
+    /// Unit of measurement
+    pub unit: String,
+}
+
+impl ChangePoint {
+    /// Whether the shift made the benchmark slower
+    pub fn is_regression(&self) -> bool {
+        self.after > self.before
+    }
+}
+
+/// A change point found in a plain series
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeriesChangePoint {
+    /// Index of the first value after the shift
+    pub index: usize,
+    /// Mean of the segment before the shift
+    pub before: f64,
+    /// Mean of the segment after the shift
+    pub after: f64,
+    /// 1 - corrected p-value of the split
+    pub confidence: f64,
+}
+
+/// Detect change points in a series of values, returned in index order
+pub fn detect_change_points(values: &[f64], config: &ChangePointConfig) -> Vec<SeriesChangePoint> {
+    let mut found = Vec::new();
+    segment(values, 0, config, &mut found);
+    found.sort_by_key(|cp| cp.index);
+    found
+}
+
+/// Detect change points for every benchmark of a suite (runs in stored order)
+pub fn detect_suite_change_points(
+    runs: &[BenchmarkRun],
+    config: &ChangePointConfig,
+) -> Vec<ChangePoint> {
+    // benchmark name -> (run index, value, unit)
+    let mut series: BTreeMap<&str, Vec<(usize, f64, &str)>> = BTreeMap::new();
+    for (run_idx, run) in runs.iter().enumerate() {
+        for bench in &run.benches {
+            series.entry(bench.name.as_str()).or_default().push((
+                run_idx,
+                bench.value,
+                bench.unit.as_str(),
+            ));
+        }
+    }
+
+    let mut change_points = Vec::new();
+    for (name, points) in series {
+        let values: Vec<f64> = points.iter().map(|(_, v, _)| *v).collect();
+
+        for cp in detect_change_points(&values, config) {
+            let percentage_change = if cp.before != 0.0 {
+                (cp.after / cp.before - 1.0) * 100.0
+            } else {
+                0.0
+            };
+            if percentage_change.abs() < config.min_change_percent {
+                continue;
+            }
+
+            let (run_idx, _, unit) = points[cp.index];
+            let run = &runs[run_idx];
+            change_points.push(ChangePoint {
+                benchmark: name.to_string(),
+                commit_id: run.commit.id.clone(),
+                commit_message: run.commit.message.clone(),
+                date: run.date,
+                before: cp.before,
+                after: cp.after,
+                percentage_change,
+                confidence: cp.confidence,
+                unit: unit.to_string(),
+            });
+        }
+    }
+
+    change_points
+}
+
+/// Binary segmentation: find the best split, test it, and recurse into both halves
+fn segment(
+    values: &[f64],
+    offset: usize,
+    config: &ChangePointConfig,
+    found: &mut Vec<SeriesChangePoint>,
+) {
+    let min_segment = config.min_segment.max(1);
+    if values.len() < 2 * min_segment {
+        return;
+    }
+
+    let Some(split) = best_split(values, min_segment) else {
+        return;
+    };
+
+    let (left, right) = values.split_at(split);
+    let confidence = split_confidence(left, right, values.len() + 1 - 2 * min_segment);
+    if confidence < config.confidence {
+        return;
+    }
+
+    found.push(SeriesChangePoint {
+        index: offset + split,
+        before: mean(left),
+        after: mean(right),
+        confidence,
+    });
+
+    segment(left, offset, config, found);
+    segment(right, offset + split, config, found);
+}
+
+/// Confidence that two segments differ, corrected for having picked the best of `candidates` splits
+fn split_confidence(left: &[f64], right: &[f64], candidates: usize) -> f64 {
+    let (Some(left), Some(right)) = (Estimate::from_samples(left), Estimate::from_samples(right))
+    else {
+        return 0.0;
+    };
+
+    match welch_t_test(&left, &right) {
+        Some(test) => 1.0 - (test.p_value * candidates as f64).min(1.0),
+        // Both segments are perfectly flat: any difference is a real shift
+        None if left.mean != right.mean => 1.0,
+        None => 0.0,
+    }
+}
+
+/// Find the split maximising the standardized mean difference (CUSUM statistic)
+fn best_split(values: &[f64], min_segment: usize) -> Option<usize> {
+    let n = values.len();
+    let total: f64 = values.iter().sum();
+
+    let mut best: Option<(usize, f64)> = None;
+    let mut left_sum = 0.0;
+    for (k, value) in values.iter().enumerate().take(n - min_segment) {
+        left_sum += value;
+        let left_len = k + 1;
+        if left_len < min_segment {
+            continue;
+        }
+
+        let right_len = n - left_len;
+        let diff = left_sum / left_len as f64 - (total - left_sum) / right_len as f64;
+        let weight = (left_len * right_len) as f64 / n as f64;
+        let statistic = weight.sqrt() * diff.abs();
+
+        if best.is_none_or(|(_, s)| statistic > s) {
+            best = Some((left_len, statistic));
+        }
+    }
+
+    best.map(|(split, _)| split)
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{BenchmarkResult, CommitInfo};
+    use chrono::{Duration, TimeZone};
+    use std::collections::HashMap;
+
+    /// Deterministic ±1% noise around a base value (xorshift)
+    fn noisy(base: f64, len: usize, seed: u64) -> Vec<f64> {
+        let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                base * (1.0 + ((state % 1000) as f64 / 1000.0 - 0.5) * 0.02)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_step_change() {
+        let mut values = noisy(100.0, 15, 1);
+        values.extend(noisy(110.0, 15, 2));
+
+        let found = detect_change_points(&values, &ChangePointConfig::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].index, 15);
+        assert!(found[0].confidence >= 0.95);
+        assert!((found[0].after / found[0].before - 1.1).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_no_change_in_noise() {
+        let values = noisy(100.0, 30, 3);
+        assert!(detect_change_points(&values, &ChangePointConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_detect_multiple_changes() {
+        let mut values = noisy(100.0, 10, 4);
+        values.extend(noisy(150.0, 10, 5));
+        values.extend(noisy(100.0, 10, 6));
+
+        let found = detect_change_points(&values, &ChangePointConfig::default());
+        let indices: Vec<_> = found.iter().map(|cp| cp.index).collect();
+        assert_eq!(indices, vec![10, 20]);
+    }
+
+    #[test]
+    fn test_short_series() {
+        let config = ChangePointConfig::default();
+        assert!(detect_change_points(&[1.0, 2.0, 3.0], &config).is_empty());
+        assert!(detect_change_points(&[], &config).is_empty());
+    }
+
+    #[test]
+    fn test_detect_suite_change_points() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let values: Vec<f64> = noisy(100.0, 8, 7)
+            .into_iter()
+            .chain(noisy(120.0, 8, 8))
+            .collect();
+
+        let runs: Vec<BenchmarkRun> = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| BenchmarkRun {
+                commit: CommitInfo {
+                    id: format!("commit{}", i),
+                    message: format!("change {}", i),
+                    timestamp: start + Duration::days(i as i64),
+                    url: None,
+                    author: None,
+                },
+                date: start + Duration::days(i as i64),
+                tool: "cargo".to_string(),
+                benches: vec![BenchmarkResult {
+                    name: "parse".to_string(),
+                    value: *v,
+                    unit: "ns/iter".to_string(),
+                    range: None,
+                    extra: HashMap::new(),
+                }],
+            })
+            .collect();
+
+        let found = detect_suite_change_points(&runs, &ChangePointConfig::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].benchmark, "parse");
+        assert_eq!(found[0].commit_id, "commit8");
+        assert!(found[0].is_regression());
+        assert!((found[0].percentage_change - 20.0).abs() < 2.0);
+    }
+}
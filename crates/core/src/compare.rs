@@ -1,60 +1,555 @@
 //! Benchmark comparison logic
 
-use crate::data::{BenchmarkResult, BenchmarkRun, ComparisonResult};
-use std::collections::HashMap;
+use crate::budget::{check_budget, BudgetResult, RegressionBudget};
+use crate::changepoint::ChangePoint;
+use crate::data::{
+    BenchmarkData, BenchmarkPath, BenchmarkResult, BenchmarkRun, CommitInfo, ComparisonResult,
+    DEFAULT_SIGNIFICANCE_LEVEL,
+};
+use crate::flakiness::{suite_flakiness, FlakinessConfig};
+use crate::pattern::BenchmarkPattern;
+use crate::stats::{geometric_mean, median, median_absolute_deviation};
+use crate::threshold::{
+    fail_at_least_alert, speedup, unit_to_ns, RuleAction, Threshold, ThresholdRule,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+
+/// Describe a speedup for humans (`3.00x faster`)
+pub fn format_speedup(comparison: &ComparisonResult) -> String {
+    let speedup = speedup(comparison.previous, comparison.current);
+    if speedup.is_finite() {
+        format!("{:.2}x faster", speedup)
+    } else {
+        "dropped to zero".to_string()
+    }
+}
+
+/// Format a value for humans: durations in the most readable time unit
+/// (`1.23 ms`), bytes with an SI prefix for large values (`12.3 kB`) and
+/// other units as they are
+pub fn format_value(value: f64, unit: &str) -> String {
+    if !value.is_finite() {
+        return format!("{} {}", value, unit);
+    }
+
+    let (scaled, unit) = match unit_to_ns(unit) {
+        Some(scale) => {
+            let ns = value * scale;
+            let (divisor, unit) = match ns.abs() {
+                n if n >= 1_000_000_000.0 => (1_000_000_000.0, "s"),
+                n if n >= 1_000_000.0 => (1_000_000.0, "ms"),
+                n if n >= 1_000.0 => (1_000.0, "µs"),
+                _ => (1.0, "ns"),
+            };
+            (ns / divisor, unit.to_string())
+        }
+        None if !is_byte_unit(unit) => (value, unit.to_string()),
+        None => {
+            let (divisor, prefix) = match value.abs() {
+                v if v >= 1e9 => (1e9, "G"),
+                v if v >= 1e6 => (1e6, "M"),
+                v if v >= 1e4 => (1e3, "k"),
+                _ => (1.0, ""),
+            };
+            (value / divisor, format!("{}{}", prefix, unit))
+        }
+    };
+
+    let precision = match scaled.abs() {
+        v if v >= 100.0 => 0,
+        v if v >= 10.0 => 1,
+        _ => 2,
+    };
+    format!("{:.*} {}", precision, scaled, unit)
+        .trim_end()
+        .to_string()
+}
+
+/// Whether a unit counts bytes (`B`, `bytes`, `B/s`, ...)
+fn is_byte_unit(unit: &str) -> bool {
+    let base = unit.trim().split('/').next().unwrap_or("");
+    matches!(base, "B" | "byte" | "bytes")
+}
+
+/// Speedup ratio above which an improvement is reported as suspicious by default
+pub const DEFAULT_SUSPICIOUS_SPEEDUP: f64 = 10.0;
+
+/// Record the suspicious-speedup threshold a suite was compared with, so that readers
+/// of the data file (like the dashboard) flag the same speedups
+pub fn record_suspicious_threshold(
+    data: &mut BenchmarkData,
+    suite: &str,
+    threshold: Option<&Threshold>,
+) {
+    let value = threshold.map_or_else(|| "off".to_string(), Threshold::to_string);
+    data.suspicious_speedups.insert(suite.to_string(), value);
+}
+
+/// The suspicious-speedup threshold recorded for a suite, `None` when it was disabled.
+/// Suites without a (valid) recorded threshold use the default.
+pub fn recorded_suspicious_threshold(data: &BenchmarkData, suite: &str) -> Option<Threshold> {
+    let default = Threshold::Ratio(DEFAULT_SUSPICIOUS_SPEEDUP);
+    match data.suspicious_speedups.get(suite) {
+        Some(value) if value.eq_ignore_ascii_case("off") => None,
+        Some(value) => Some(value.parse().unwrap_or(default)),
+        None => Some(default),
+    }
+}
+
+/// How the expected value of each benchmark is derived from stored history
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BaselineMode {
+    /// Compare against the most recent stored run only
+    #[default]
+    Latest,
+    /// Compare against the median of the last `window` runs. A regression (or improvement)
+    /// must additionally move away from the median by `mad_multiplier` times the median
+    /// absolute deviation.
+    Rolling { window: usize, mad_multiplier: f64 },
+}
+
+/// What happens when benchmarks of the baseline are missing from the current run
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MissingBenchmarks {
+    /// Only list them as removed
+    #[default]
+    Ignore,
+    /// Raise an alert
+    Warn,
+    /// Fail the comparison
+    Fail,
+}
+
+impl FromStr for MissingBenchmarks {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ignore" => Ok(Self::Ignore),
+            "warn" => Ok(Self::Warn),
+            "fail" => Ok(Self::Fail),
+            other => Err(format!(
+                "Invalid missing-benchmark policy '{}': expected fail, warn or ignore",
+                other
+            )),
+        }
+    }
+}
+
+/// Policy for benchmarks that disappear between runs
+#[derive(Debug, Clone, Default)]
+pub struct CoveragePolicy {
+    /// What to do when any (not allowlisted) benchmark is missing
+    pub missing: MissingBenchmarks,
+    /// Minimum percentage of the baseline's benchmarks that must still run;
+    /// falling below it fails regardless of `missing`
+    pub min_coverage: Option<f64>,
+    /// Benchmarks expected to go away
+    pub allow_removed: Vec<BenchmarkPattern>,
+}
+
+impl CoveragePolicy {
+    /// Check whether a benchmark is expected to go away
+    pub fn is_allowed_removal(&self, name: &str) -> bool {
+        self.allow_removed
+            .iter()
+            .any(|pattern| pattern.matches(name))
+    }
+}
+
+/// How many of the baseline's benchmarks still ran
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Coverage {
+    /// Baseline benchmarks that are expected to run (allowlisted removals excluded)
+    pub expected: usize,
+    /// Expected benchmarks present in the current run
+    pub present: usize,
+    /// `present` as a percentage of `expected`
+    pub percentage: f64,
+    /// Expected benchmarks missing from the current run
+    pub missing: Vec<String>,
+    /// Minimum coverage percentage required by the policy, if any
+    pub min_coverage: Option<f64>,
+    /// Whether the policy raises an alert
+    pub is_alert: bool,
+    /// Whether the policy fails the comparison
+    pub is_failure: bool,
+}
+
+/// Check how many of the previous run's benchmarks are still present in the
+/// current run. Returns `None` if the previous run has no benchmarks.
+pub fn check_coverage(
+    previous: &[BenchmarkResult],
+    current: &[BenchmarkResult],
+    policy: &CoveragePolicy,
+) -> Option<Coverage> {
+    if previous.is_empty() {
+        return None;
+    }
+
+    let current: HashSet<&str> = current.iter().map(|b| b.name.as_str()).collect();
+    let mut expected = 0;
+    let mut missing = Vec::new();
+    for bench in previous {
+        if current.contains(bench.name.as_str()) {
+            expected += 1;
+        } else if !policy.is_allowed_removal(&bench.name) {
+            expected += 1;
+            missing.push(bench.name.clone());
+        }
+    }
+
+    let present = expected - missing.len();
+    let percentage = if expected > 0 {
+        present as f64 / expected as f64 * 100.0
+    } else {
+        100.0
+    };
+    let below_minimum = policy.min_coverage.is_some_and(|min| percentage < min);
+    let is_failure =
+        below_minimum || (policy.missing == MissingBenchmarks::Fail && !missing.is_empty());
+    let is_alert = is_failure || (policy.missing == MissingBenchmarks::Warn && !missing.is_empty());
+
+    Some(Coverage {
+        expected,
+        present,
+        percentage,
+        missing,
+        min_coverage: policy.min_coverage,
+        is_alert,
+        is_failure,
+    })
+}
 
 /// Configuration for benchmark comparison
 #[derive(Debug, Clone)]
 pub struct CompareConfig {
-    /// Alert threshold as a ratio (e.g., 2.0 means 200%)
-    pub alert_threshold: f64,
-    /// Fail threshold as a ratio (defaults to alert_threshold)
-    pub fail_threshold: Option<f64>,
+    /// Alert threshold (e.g., a ratio of 2.0 means 200%)
+    pub alert_threshold: Threshold,
+    /// Fail threshold (defaults to alert_threshold)
+    pub fail_threshold: Option<Threshold>,
+    /// Significance level for the per-benchmark t-test (e.g., 0.05)
+    pub significance_level: f64,
+    /// Only alert/fail on changes that are statistically significant.
+    /// Benchmarks without spread information cannot be tested and fall back to the threshold.
+    pub require_significance: bool,
+    /// How the baseline is built from history (see [`compare_with_history`])
+    pub baseline: BaselineMode,
+    /// Ordered per-benchmark overrides; the first matching rule wins
+    pub rules: Vec<ThresholdRule>,
+    /// Speedup that is reported as a notable improvement (None disables).
+    /// Read as a speedup, see [`Threshold::is_exceeded_by_improvement`].
+    pub improvement_threshold: Option<Threshold>,
+    /// Speedup large enough to suggest the benchmark stopped doing its work
+    /// (e.g. optimized away); None disables
+    pub suspicious_improvement_threshold: Option<Threshold>,
+    /// Alert/fail thresholds for aggregate group scores, matched against group
+    /// paths such as `core` or `core/parsing` (`**` also matches the whole suite).
+    /// Only ratio thresholds apply to groups (see [`ThresholdRule::parse_group`]).
+    pub group_rules: Vec<ThresholdRule>,
+    /// Score each benchmark's flakiness over the history (see [`compare_with_history`])
+    pub flakiness: Option<FlakinessConfig>,
+    /// Limits on the suite's cumulative drift, checked against the history
+    pub budgets: Vec<RegressionBudget>,
+    /// What to do when benchmarks disappear
+    pub coverage: CoveragePolicy,
 }
 
 impl Default for CompareConfig {
     fn default() -> Self {
         Self {
-            alert_threshold: 2.0, // 200%
+            alert_threshold: Threshold::Ratio(2.0), // 200%
             fail_threshold: None,
+            significance_level: DEFAULT_SIGNIFICANCE_LEVEL,
+            require_significance: false,
+            baseline: BaselineMode::Latest,
+            rules: Vec::new(),
+            improvement_threshold: None,
+            suspicious_improvement_threshold: Some(Threshold::Ratio(DEFAULT_SUSPICIOUS_SPEEDUP)),
+            group_rules: Vec::new(),
+            flakiness: None,
+            budgets: Vec::new(),
+            coverage: CoveragePolicy::default(),
         }
     }
 }
 
 impl CompareConfig {
-    /// Create config from percentage strings (e.g., "150%")
-    pub fn from_percentages(alert: &str, fail: Option<&str>) -> Result<Self, String> {
-        let alert_threshold = parse_percentage(alert)?;
-        let fail_threshold = fail.map(parse_percentage).transpose()?;
-
-        if let Some(ft) = fail_threshold {
-            if ft < alert_threshold {
-                return Err("fail-threshold must be >= alert-threshold".to_string());
-            }
+    /// Create config from threshold strings (e.g., "150%", "1.5x", "+5% & +100ns")
+    pub fn from_thresholds(alert: &str, fail: Option<&str>) -> Result<Self, String> {
+        let alert_threshold = Threshold::parse(alert)?;
+        let fail_threshold = fail.map(Threshold::parse).transpose()?;
+
+        if !fail_at_least_alert(&alert_threshold, fail_threshold.as_ref()) {
+            return Err("fail-threshold must be >= alert-threshold".to_string());
         }
 
         Ok(Self {
             alert_threshold,
             fail_threshold,
+            ..Default::default()
         })
     }
 
     /// Get the effective fail threshold
-    pub fn effective_fail_threshold(&self) -> f64 {
-        self.fail_threshold.unwrap_or(self.alert_threshold)
+    pub fn effective_fail_threshold(&self) -> &Threshold {
+        self.fail_threshold
+            .as_ref()
+            .unwrap_or(&self.alert_threshold)
+    }
+
+    /// Find the first rule matching a benchmark name
+    pub fn rule_for(&self, name: &str) -> Option<&ThresholdRule> {
+        self.rules.iter().find(|rule| rule.matches(name))
+    }
+
+    /// Resolve the alert threshold for a benchmark, or `None` if it is ignored
+    pub fn alert_threshold_for(&self, name: &str) -> Option<&Threshold> {
+        self.thresholds_for(name).map(|(alert, _)| alert)
+    }
+
+    /// Resolve the (alert, fail) thresholds for a benchmark, or `None` if it is ignored
+    pub fn thresholds_for(&self, name: &str) -> Option<(&Threshold, &Threshold)> {
+        match self.rule_for(name).map(|rule| &rule.action) {
+            Some(RuleAction::Ignore) => None,
+            Some(RuleAction::Thresholds { alert, fail }) => {
+                Some((alert, fail.as_ref().unwrap_or(alert)))
+            }
+            None => Some((&self.alert_threshold, self.effective_fail_threshold())),
+        }
+    }
+}
+
+/// Aggregate score of a group of benchmarks in the name hierarchy
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GroupScore {
+    /// Group path (e.g. `core` or `core/parsing`); empty for the whole suite
+    pub group: String,
+    /// Number of benchmarks in the group
+    pub benchmarks: usize,
+    /// Geometric mean of the current/previous ratios
+    pub ratio: f64,
+    /// Percentage change implied by the ratio
+    pub percentage_change: f64,
+    /// Pattern of the group rule that applied, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Whether the score reached the group's alert threshold
+    pub is_alert: bool,
+    /// Whether the score reached the group's fail threshold
+    pub is_failure: bool,
+}
+
+impl GroupScore {
+    /// Name to display for the group
+    pub fn display_name(&self) -> &str {
+        if self.group.is_empty() {
+            "all benchmarks"
+        } else {
+            &self.group
+        }
+    }
+}
+
+/// Roll comparisons up into geometric-mean scores for the whole suite and for
+/// each `grandparent` and `grandparent/parent` group, suite first and groups in
+/// name order. Ignored benchmarks do not count.
+///
+/// Groups holding a zero or infinite ratio have no geometric mean; they are
+/// returned separately as skipped.
+pub fn aggregate_scores(
+    comparisons: &[ComparisonResult],
+    config: &CompareConfig,
+) -> (Vec<GroupScore>, Vec<String>) {
+    let mut groups: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for comparison in comparisons {
+        if config.thresholds_for(&comparison.name).is_none() {
+            continue;
+        }
+
+        groups
+            .entry(String::new())
+            .or_default()
+            .push(comparison.ratio);
+        for group in BenchmarkPath::parse(&comparison.name).groups() {
+            groups.entry(group).or_default().push(comparison.ratio);
+        }
+    }
+
+    let mut scores = Vec::new();
+    let mut skipped = Vec::new();
+    for (group, ratios) in groups {
+        let Some(ratio) = geometric_mean(&ratios) else {
+            skipped.push(group);
+            continue;
+        };
+        let rule = config.group_rules.iter().find(|rule| rule.matches(&group));
+        // A geometric mean has no unit, so only ratio thresholds can apply
+        // (see `ThresholdRule::parse_group`)
+        let reaches = |threshold: &Threshold| threshold.as_ratio().is_some_and(|min| ratio >= min);
+        let (is_alert, is_failure) = match rule.map(|rule| &rule.action) {
+            Some(RuleAction::Thresholds { alert, fail }) => {
+                (reaches(alert), reaches(fail.as_ref().unwrap_or(alert)))
+            }
+            _ => (false, false),
+        };
+
+        scores.push(GroupScore {
+            benchmarks: ratios.len(),
+            ratio,
+            percentage_change: (ratio - 1.0) * 100.0,
+            rule: rule.map(|rule| rule.pattern.to_string()),
+            is_alert,
+            is_failure,
+            group,
+        });
+    }
+    (scores, skipped)
+}
+
+/// Minimum score for a removed/new pair to be suggested as a rename
+pub const RENAME_SCORE_THRESHOLD: f64 = 0.5;
+
+/// A removed benchmark that probably reappeared under a new name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RenameSuggestion {
+    /// Name in the previous run
+    pub from: String,
+    /// Name in the current run
+    pub to: String,
+    /// Match score between 0 and 1
+    pub score: f64,
+}
+
+/// Pair removed benchmarks with new ones that look like renames
+///
+/// Each pair is scored on name similarity, value proximity (same unit only)
+/// and whether the hierarchy leaf (the `test` of `grandparent::parent::test`)
+/// is shared. Pairs are picked greedily by score, each name at most once.
+pub fn suggest_renames(
+    removed: &[BenchmarkResult],
+    new: &[BenchmarkResult],
+) -> Vec<RenameSuggestion> {
+    let mut candidates = Vec::new();
+    for old in removed {
+        for current in new {
+            let score = rename_score(old, current);
+            if score >= RENAME_SCORE_THRESHOLD {
+                candidates.push((score, old, current));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut suggestions: Vec<RenameSuggestion> = Vec::new();
+    for (score, old, current) in candidates {
+        let taken = suggestions
+            .iter()
+            .any(|s| s.from == old.name || s.to == current.name);
+        if !taken {
+            suggestions.push(RenameSuggestion {
+                from: old.name.clone(),
+                to: current.name.clone(),
+                score,
+            });
+        }
+    }
+    suggestions
+}
+
+/// Weighted similarity of two benchmarks (0 to 1)
+fn rename_score(old: &BenchmarkResult, new: &BenchmarkResult) -> f64 {
+    let old_name = normalize_name(&old.name);
+    let new_name = normalize_name(&new.name);
+
+    let old_tokens: Vec<&str> = old_name.split('_').filter(|t| !t.is_empty()).collect();
+    let new_tokens: Vec<&str> = new_name.split('_').filter(|t| !t.is_empty()).collect();
+    let shared = old_tokens.iter().filter(|t| new_tokens.contains(t)).count();
+    let union = old_tokens.len() + new_tokens.len() - shared;
+    let jaccard = if union > 0 {
+        shared as f64 / union as f64
+    } else {
+        0.0
+    };
+    let edit = 1.0
+        - levenshtein(&old_name, &new_name) as f64
+            / old_name
+                .chars()
+                .count()
+                .max(new_name.chars().count())
+                .max(1) as f64;
+    let name_score = jaccard.max(edit);
+
+    let value_score = if old.unit != new.unit {
+        0.0
+    } else if old.value == new.value {
+        1.0
+    } else if old.value > 0.0 && new.value > 0.0 {
+        old.value.min(new.value) / old.value.max(new.value)
+    } else {
+        0.0
+    };
+
+    let old_leaf = normalize_name(&BenchmarkPath::parse(&old.name).test);
+    let new_leaf = normalize_name(&BenchmarkPath::parse(&new.name).test);
+    let leaf_score = if old_leaf == new_leaf {
+        1.0
+    } else if old_tokens.contains(&new_leaf.as_str()) || new_tokens.contains(&old_leaf.as_str()) {
+        0.5
+    } else {
+        0.0
+    };
+
+    0.35 * name_score + 0.25 * value_score + 0.4 * leaf_score
+}
+
+/// Lowercase a name and use `_` for every separator
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .replace("::", "_")
+        .replace(['/', '-', ' ', '.'], "_")
+}
+
+/// Edit distance between two strings (in characters)
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
     }
+
+    row[b.len()]
 }
 
-/// Parse a percentage string like "150%" to a ratio (1.5)
-fn parse_percentage(s: &str) -> Result<f64, String> {
-    let s = s.trim();
-    let s = s.strip_suffix('%').unwrap_or(s);
-    let value: f64 = s.parse().map_err(|_| format!("Invalid percentage: {}", s))?;
-    Ok(value / 100.0)
+/// History window a rolling baseline was built from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BaselineWindow {
+    /// Number of runs in the window
+    pub runs: usize,
+    /// Oldest commit in the window
+    pub from_commit: String,
+    /// Newest commit in the window
+    pub to_commit: String,
+    /// Number of MADs a change must move away from the median by
+    pub mad_multiplier: f64,
 }
 
 /// Result of comparing benchmark runs
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CompareReport {
     /// Individual comparison results
     pub comparisons: Vec<ComparisonResult>,
@@ -62,21 +557,59 @@ pub struct CompareReport {
     pub alerts: Vec<ComparisonResult>,
     /// Benchmarks that should cause failure
     pub failures: Vec<ComparisonResult>,
+    /// Benchmarks that sped up past the improvement threshold
+    pub improvements: Vec<ComparisonResult>,
+    /// Benchmarks that sped up so much they need a second look
+    pub suspicious_improvements: Vec<ComparisonResult>,
+    /// Aggregate scores for the suite and each hierarchy group
+    pub groups: Vec<GroupScore>,
+    /// Groups without a score because a benchmark's ratio is zero or infinite
+    pub skipped_groups: Vec<String>,
     /// New benchmarks (no previous data)
     pub new_benchmarks: Vec<BenchmarkResult>,
     /// Removed benchmarks (in previous but not current)
     pub removed_benchmarks: Vec<BenchmarkResult>,
+    /// Removed/new pairs that look like renames
+    pub possible_renames: Vec<RenameSuggestion>,
+    /// Benchmarks too flaky to fail the build; they only alert
+    pub warn_only: Vec<String>,
+    /// Regression budgets checked against the history
+    pub budgets: Vec<BudgetResult>,
+    /// How many of the baseline's benchmarks still ran (None without a baseline)
+    pub coverage: Option<Coverage>,
+    /// Rolling baseline window (None when compared against a single run)
+    pub baseline: Option<BaselineWindow>,
+    /// Distribution shifts detected across the stored history (optional)
+    pub change_points: Vec<ChangePoint>,
 }
 
 impl CompareReport {
-    /// Check if any alerts were triggered
+    /// Check if any alerts were triggered (by a benchmark, a group score, a budget
+    /// or the coverage policy)
     pub fn has_alerts(&self) -> bool {
         !self.alerts.is_empty()
+            || self.groups.iter().any(|g| g.is_alert)
+            || self.has_exceeded_budgets()
+            || self.coverage.as_ref().is_some_and(|c| c.is_alert)
     }
 
-    /// Check if any failures were triggered
+    /// Check if any failures were triggered (by a benchmark, a group score, a budget
+    /// or the coverage policy)
     pub fn has_failures(&self) -> bool {
         !self.failures.is_empty()
+            || self.groups.iter().any(|g| g.is_failure)
+            || self.has_exceeded_budgets()
+            || self.coverage.as_ref().is_some_and(|c| c.is_failure)
+    }
+
+    /// Check if the suite drifted past any regression budget
+    pub fn has_exceeded_budgets(&self) -> bool {
+        self.budgets.iter().any(|b| b.is_exceeded)
+    }
+
+    /// Check if any speedups look too good to be true
+    pub fn has_suspicious_improvements(&self) -> bool {
+        !self.suspicious_improvements.is_empty()
     }
 
     /// Generate a summary string
@@ -89,10 +622,85 @@ impl CompareReport {
 
         lines.push("## Benchmark Comparison Report\n".to_string());
 
+        if let Some(window) = &self.baseline {
+            lines.push(format!(
+                "_Baseline: median of the last {} run(s) ({}..{}), changes must exceed {}×MAD_\n",
+                window.runs,
+                short_sha(&window.from_commit),
+                short_sha(&window.to_commit),
+                window.mad_multiplier
+            ));
+        }
+
+        if self.comparisons.len() > 1 && !self.groups.is_empty() {
+            lines.push("### Aggregate Scores\n".to_string());
+            lines.push("| Group | Benchmarks | Score | Change |".to_string());
+            lines.push("|-------|------------|-------|--------|".to_string());
+            for group in &self.groups {
+                let indicator = if group.is_failure {
+                    "🚨 "
+                } else if group.is_alert {
+                    "🔴 "
+                } else {
+                    ""
+                };
+                lines.push(format!(
+                    "| {} | {} | {:.3}x | {}{:+.2}% |",
+                    if group.group.is_empty() {
+                        format!("**{}**", group.display_name())
+                    } else {
+                        table_cell(&group.group)
+                    },
+                    group.benchmarks,
+                    group.ratio,
+                    indicator,
+                    group.percentage_change
+                ));
+            }
+            lines.push(String::new());
+        }
+
+        if self.comparisons.len() > 1 && !self.skipped_groups.is_empty() {
+            let names: Vec<&str> = self
+                .skipped_groups
+                .iter()
+                .map(|group| {
+                    if group.is_empty() {
+                        "all benchmarks"
+                    } else {
+                        group
+                    }
+                })
+                .collect();
+            lines.push(format!(
+                "_No score for {} (a benchmark's ratio is zero or infinite)_\n",
+                names.join(", ")
+            ));
+        }
+
         if !self.comparisons.is_empty() {
+            let show_p_values = self.comparisons.iter().any(|c| c.p_value.is_some());
+            let show_rules = self.comparisons.iter().any(|c| c.rule.is_some());
+            let show_flakiness = self.comparisons.iter().any(|c| c.flakiness.is_some());
+
+            let mut header = "| Benchmark | Previous | Current | Change |".to_string();
+            let mut divider = "|-----------|----------|---------|--------|".to_string();
+            if show_p_values {
+                header.push_str(" p-value |");
+                divider.push_str("---------|");
+            }
+            if show_rules {
+                header.push_str(" Rule |");
+                divider.push_str("------|");
+            }
+            if show_flakiness {
+                header.push_str(" Flakiness |");
+                divider.push_str("-----------|");
+            }
+
             lines.push("### Comparisons\n".to_string());
-            lines.push("| Benchmark | Previous | Current | Change |".to_string());
-            lines.push("|-----------|----------|---------|--------|".to_string());
+            lines.push(header);
+            lines.push(divider);
 
             for comp in &self.comparisons {
                 let change_str = if comp.percentage_change >= 0.0 {
@@ -101,24 +709,48 @@ impl CompareReport {
                     format!("{:.2}%", comp.percentage_change)
                 };
 
-                let indicator = if comp.is_regression {
+                let listed = |list: &[ComparisonResult]| list.iter().any(|c| c.name == comp.name);
+                let indicator = if !comp.is_plausibly_significant() {
+                    "⚪"
+                } else if listed(&self.suspicious_improvements) {
+                    "🔍"
+                } else if comp.is_regression {
                     "🔴"
-                } else if comp.percentage_change < -5.0 {
+                } else if listed(&self.improvements) {
                     "🟢"
                 } else {
                     "⚪"
                 };
 
-                lines.push(format!(
+                let mut row = format!(
                     "| {} | {:.2} {} | {:.2} {} | {} {} |",
-                    comp.name,
+                    table_cell(&comp.name),
                     comp.previous,
                     comp.unit,
                     comp.current,
                     comp.unit,
                     indicator,
                     change_str
-                ));
+                );
+                if show_p_values {
+                    match comp.p_value {
+                        Some(p) => row.push_str(&format!(" {:.3} |", p)),
+                        None => row.push_str(" - |"),
+                    }
+                }
+                if show_rules {
+                    match &comp.rule {
+                        Some(rule) => row.push_str(&format!(" `{}` |", rule)),
+                        None => row.push_str(" - |"),
+                    }
+                }
+                if show_flakiness {
+                    match comp.flakiness {
+                        Some(score) => row.push_str(&format!(" {:.2} |", score)),
+                        None => row.push_str(" - |"),
+                    }
+                }
+                lines.push(row);
             }
             lines.push(String::new());
         }
@@ -139,28 +771,220 @@ impl CompareReport {
             lines.push(String::new());
         }
 
+        if !self.possible_renames.is_empty() {
+            lines.push("### 🔀 Possible Renames\n".to_string());
+            for rename in &self.possible_renames {
+                lines.push(format!(
+                    "- **{}** → **{}** ({:.0}% match)",
+                    rename.from,
+                    rename.to,
+                    rename.score * 100.0
+                ));
+            }
+            lines.push(String::new());
+        }
+
+        if !self.change_points.is_empty() {
+            lines.push("### 📈 Change Points\n".to_string());
+            lines.push("| Benchmark | Commit | Before | After | Change | Confidence |".to_string());
+            lines.push("|-----------|--------|--------|-------|--------|------------|".to_string());
+            for cp in &self.change_points {
+                lines.push(format!(
+                    "| {} | {} {} | {:.2} {} | {:.2} {} | {:+.2}% | {:.1}% |",
+                    table_cell(&cp.benchmark),
+                    short_sha(&cp.commit_id),
+                    table_cell(&cp.commit_message),
+                    cp.before,
+                    cp.unit,
+                    cp.after,
+                    cp.unit,
+                    cp.percentage_change,
+                    cp.confidence * 100.0
+                ));
+            }
+            lines.push(String::new());
+        }
+
+        if !self.improvements.is_empty() {
+            lines.push("### 🚀 Performance Improvements\n".to_string());
+            for improvement in &self.improvements {
+                lines.push(format!(
+                    "- **{}**: {} ({:.2} {} → {:.2} {}){}",
+                    improvement.name,
+                    format_speedup(improvement),
+                    improvement.previous,
+                    improvement.unit,
+                    improvement.current,
+                    improvement.unit,
+                    rule_suffix(improvement)
+                ));
+            }
+            lines.push(String::new());
+        }
+
+        if !self.suspicious_improvements.is_empty() {
+            lines.push("### 🔍 Suspicious Improvements\n".to_string());
+            lines.push(
+                "These speedups are large enough that the benchmark may have been optimized away \
+                 or stopped doing its work. Please verify them.\n"
+                    .to_string(),
+            );
+            for suspicious in &self.suspicious_improvements {
+                lines.push(format!(
+                    "- **{}**: {} ({:.2} {} → {:.2} {}){}",
+                    suspicious.name,
+                    format_speedup(suspicious),
+                    suspicious.previous,
+                    suspicious.unit,
+                    suspicious.current,
+                    suspicious.unit,
+                    rule_suffix(suspicious)
+                ));
+            }
+            lines.push(String::new());
+        }
+
         if !self.alerts.is_empty() {
             lines.push("### ⚠️ Performance Alerts\n".to_string());
             for alert in &self.alerts {
+                let warn_only = if self.warn_only.contains(&alert.name) {
+                    " (flaky, warn-only)"
+                } else {
+                    ""
+                };
                 lines.push(format!(
-                    "- **{}**: {:.2}% regression ({:.2} {} → {:.2} {})",
+                    "- **{}**: {:.2}% regression ({:.2} {} → {:.2} {}){}{}",
                     alert.name,
                     alert.percentage_change,
                     alert.previous,
                     alert.unit,
                     alert.current,
-                    alert.unit
+                    alert.unit,
+                    rule_suffix(alert),
+                    warn_only
+                ));
+            }
+            lines.push(String::new());
+        }
+
+        let group_alerts: Vec<_> = self.groups.iter().filter(|g| g.is_alert).collect();
+        if !group_alerts.is_empty() {
+            lines.push("### ⚠️ Group Alerts\n".to_string());
+            for group in group_alerts {
+                lines.push(format!(
+                    "- **{}**: {:.2}% slower overall across {} benchmark(s){}{}",
+                    group.display_name(),
+                    group.percentage_change,
+                    group.benchmarks,
+                    if group.is_failure {
+                        ", exceeds fail threshold"
+                    } else {
+                        ""
+                    },
+                    group
+                        .rule
+                        .as_ref()
+                        .map(|rule| format!(" (rule `{}`)", rule))
+                        .unwrap_or_default()
+                ));
+            }
+            lines.push(String::new());
+        }
+
+        if let Some(coverage) = self.coverage.as_ref().filter(|c| c.is_alert) {
+            lines.push("### 🕳️ Missing Benchmarks\n".to_string());
+            lines.push(format!(
+                "{} of {} expected benchmark(s) ran ({:.1}% coverage{}){}\n",
+                coverage.present,
+                coverage.expected,
+                coverage.percentage,
+                coverage
+                    .min_coverage
+                    .map(|min| format!(", minimum {}%", min))
+                    .unwrap_or_default(),
+                if coverage.is_failure { ", failing" } else { "" }
+            ));
+            for name in &coverage.missing {
+                match self.possible_renames.iter().find(|r| &r.from == name) {
+                    Some(rename) => lines.push(format!(
+                        "- **{}** (possibly renamed to {})",
+                        name, rename.to
+                    )),
+                    None => lines.push(format!("- **{}**", name)),
+                }
+            }
+            lines.push(String::new());
+        }
+
+        if !self.budgets.is_empty() {
+            lines.push("### 💸 Regression Budgets\n".to_string());
+            lines.push("| Budget | Baseline | Benchmarks | Change |".to_string());
+            lines.push("|--------|----------|------------|--------|".to_string());
+            for budget in &self.budgets {
+                let delta = budget
+                    .mean_delta_ns
+                    .map(|delta| {
+                        format!(
+                            " ({}{})",
+                            if delta >= 0.0 { "+" } else { "" },
+                            format_value(delta, "ns")
+                        )
+                    })
+                    .unwrap_or_default();
+                lines.push(format!(
+                    "| {} | {} ({}){} | {} | {}{:+.2}%{} |",
+                    budget.budget,
+                    short_sha(&budget.baseline_commit),
+                    budget.baseline_date.format("%Y-%m-%d"),
+                    if budget.history_too_short {
+                        " ⚠️"
+                    } else {
+                        ""
+                    },
+                    budget.benchmarks,
+                    if budget.is_exceeded { "🚨 " } else { "" },
+                    budget.percentage_change,
+                    delta
                 ));
             }
             lines.push(String::new());
+
+            if self.budgets.iter().any(|b| b.history_too_short) {
+                lines.push(
+                    "_⚠️ The history does not reach back that far; the drift is measured from the oldest stored run._\n"
+                        .to_string(),
+                );
+            }
+
+            for budget in self.budgets.iter().filter(|b| b.is_exceeded) {
+                lines.push(format!(
+                    "Budget **{}** exceeded, contributing commits:\n",
+                    budget.budget
+                ));
+                for commit in &budget.contributing_commits {
+                    if commit.commit_id.is_empty() {
+                        lines.push(format!("- this run: {:+.2}%", commit.percentage_change));
+                    } else {
+                        lines.push(format!(
+                            "- {} {}: {:+.2}%",
+                            short_sha(&commit.commit_id),
+                            table_cell(&commit.commit_message),
+                            commit.percentage_change
+                        ));
+                    }
+                }
+                lines.push(String::new());
+            }
         }
 
         if !self.failures.is_empty() {
             lines.push("### 🚨 Critical Regressions (Failing)\n".to_string());
             for failure in &self.failures {
                 lines.push(format!(
-                    "- **{}**: {:.2}% regression exceeds threshold",
-                    failure.name, failure.percentage_change
+                    "- **{}**: {:.2}% regression exceeds threshold{}",
+                    failure.name,
+                    failure.percentage_change,
+                    rule_suffix(failure)
                 ));
             }
         }
@@ -176,29 +1000,57 @@ impl CompareReport {
 
         let mut parts = Vec::new();
 
-        let regressions: Vec<_> = self.comparisons.iter().filter(|c| c.is_regression).collect();
-        let improvements: Vec<_> = self
+        let regressions: Vec<_> = self
             .comparisons
             .iter()
-            .filter(|c| !c.is_regression && c.percentage_change < -5.0)
+            .filter(|c| c.is_regression && c.is_plausibly_significant())
             .collect();
 
         if !regressions.is_empty() {
             parts.push(format!("🔴 {} regression(s)", regressions.len()));
         }
 
-        if !improvements.is_empty() {
-            parts.push(format!("🟢 {} improvement(s)", improvements.len()));
+        if !self.improvements.is_empty() {
+            parts.push(format!("🟢 {} improvement(s)", self.improvements.len()));
+        }
+
+        if !self.suspicious_improvements.is_empty() {
+            parts.push(format!(
+                "🔍 {} suspicious improvement(s)",
+                self.suspicious_improvements.len()
+            ));
         }
 
         if !self.new_benchmarks.is_empty() {
             parts.push(format!("🆕 {} new benchmark(s)", self.new_benchmarks.len()));
         }
 
-        if parts.is_empty() {
+        if let Some(coverage) = self.coverage.as_ref().filter(|c| c.is_alert) {
+            parts.push(format!(
+                "🕳️ {} missing benchmark(s)",
+                coverage.missing.len()
+            ));
+        }
+
+        let exceeded = self.budgets.iter().filter(|b| b.is_exceeded).count();
+        if exceeded > 0 {
+            parts.push(format!("💸 {} budget(s) exceeded", exceeded));
+        }
+
+        let summary = if parts.is_empty() {
             "⚪ No significant changes".to_string()
         } else {
             parts.join(", ")
+        };
+
+        match (self.comparisons.len() > 1, self.groups.first()) {
+            (true, Some(overall)) => {
+                format!(
+                    "{} (📊 {:+.1}% overall)",
+                    summary, overall.percentage_change
+                )
+            }
+            _ => summary,
         }
     }
 }
@@ -209,9 +1061,33 @@ pub fn compare_runs(
     current: &BenchmarkRun,
     config: &CompareConfig,
 ) -> CompareReport {
+    compare_runs_in_context(previous, current, config, &HistoryContext::default())
+}
+
+/// What the stored history adds to a comparison of two runs
+#[derive(Debug, Default)]
+struct HistoryContext {
+    /// (lower, upper) noise band per benchmark; changes inside it never alert
+    noise_bands: HashMap<String, (f64, f64)>,
+    /// Flakiness score per benchmark
+    flakiness: HashMap<String, f64>,
+}
+
+/// Compare two runs, taking noise bands and flakiness from the history into account
+fn compare_runs_in_context(
+    previous: &BenchmarkRun,
+    current: &BenchmarkRun,
+    config: &CompareConfig,
+    context: &HistoryContext,
+) -> CompareReport {
+    let warn_only_score = config.flakiness.as_ref().and_then(|f| f.warn_only_score);
+
     let mut comparisons = Vec::new();
     let mut alerts = Vec::new();
     let mut failures = Vec::new();
+    let mut warn_only = Vec::new();
+    let mut improvements = Vec::new();
+    let mut suspicious_improvements = Vec::new();
     let mut new_benchmarks = Vec::new();
     let mut removed_benchmarks = Vec::new();
 
@@ -232,16 +1108,55 @@ pub fn compare_runs(
     // Compare benchmarks that exist in both
     for curr_bench in &current.benches {
         if let Some(prev_bench) = prev_map.get(curr_bench.name.as_str()) {
-            let comparison = ComparisonResult::new(prev_bench, curr_bench);
+            let mut comparison = ComparisonResult::new(prev_bench, curr_bench);
+            comparison.classify_significance(config.significance_level);
+            comparison.rule = config
+                .rule_for(&curr_bench.name)
+                .map(|rule| rule.pattern.to_string());
+            comparison.flakiness = context.flakiness.get(&curr_bench.name).copied();
+            let thresholds = config.thresholds_for(&curr_bench.name);
+            let too_flaky = warn_only_score
+                .zip(comparison.flakiness)
+                .is_some_and(|(limit, score)| score >= limit);
 
-            // Check for alerts
-            if comparison.ratio >= config.alert_threshold {
-                alerts.push(comparison.clone());
-            }
+            // Noise-level changes never alert when significance is required
+            let significant = !config.require_significance || comparison.is_plausibly_significant();
+            let band = context.noise_bands.get(&curr_bench.name);
+            let above_noise = band.is_none_or(|(_, upper)| curr_bench.value > *upper);
+            let below_noise = band.is_none_or(|(lower, _)| curr_bench.value < *lower);
 
-            // Check for failures
-            if comparison.ratio >= config.effective_fail_threshold() {
-                failures.push(comparison.clone());
+            if let (true, Some((alert_threshold, fail_threshold))) = (significant, thresholds) {
+                if above_noise {
+                    // Check for alerts
+                    if alert_threshold.is_exceeded(&comparison) {
+                        alerts.push(comparison.clone());
+                    }
+
+                    // Check for failures (flaky benchmarks only alert)
+                    if fail_threshold.is_exceeded(&comparison) {
+                        if too_flaky {
+                            warn_only.push(comparison.name.clone());
+                        } else {
+                            failures.push(comparison.clone());
+                        }
+                    }
+                }
+
+                if below_noise {
+                    let exceeds = |threshold: &Option<Threshold>| {
+                        threshold
+                            .as_ref()
+                            .is_some_and(|t| t.is_exceeded_by_improvement(&comparison))
+                    };
+
+                    if exceeds(&config.improvement_threshold) {
+                        improvements.push(comparison.clone());
+                    }
+
+                    if exceeds(&config.suspicious_improvement_threshold) {
+                        suspicious_improvements.push(comparison.clone());
+                    }
+                }
             }
 
             comparisons.push(comparison);
@@ -258,15 +1173,79 @@ pub fn compare_runs(
         }
     }
 
+    let (groups, skipped_groups) = aggregate_scores(&comparisons, config);
+    let possible_renames = suggest_renames(&removed_benchmarks, &new_benchmarks);
+    let coverage = check_coverage(&previous.benches, &current.benches, &config.coverage);
+
     CompareReport {
         comparisons,
         alerts,
         failures,
+        improvements,
+        suspicious_improvements,
+        groups,
+        skipped_groups,
         new_benchmarks,
         removed_benchmarks,
+        possible_renames,
+        warn_only,
+        budgets: Vec::new(),
+        coverage,
+        baseline: None,
+        change_points: Vec::new(),
     }
 }
 
+/// Merge repeated outputs of the same benchmarks into one result per benchmark
+///
+/// Values are averaged and the individual repetitions are kept in
+/// `extra["samples"]` so comparisons can test their significance. A single
+/// repetition is returned unchanged. Benchmarks keep their first-seen order;
+/// repetitions reported in a different unit are skipped.
+pub fn merge_repetitions(repetitions: &[Vec<BenchmarkResult>]) -> Vec<BenchmarkResult> {
+    if let [single] = repetitions {
+        return single.clone();
+    }
+
+    let mut order: Vec<&str> = Vec::new();
+    let mut samples: HashMap<&str, (&BenchmarkResult, Vec<f64>)> = HashMap::new();
+    for results in repetitions {
+        for result in results {
+            let entry = samples.entry(result.name.as_str()).or_insert_with(|| {
+                order.push(result.name.as_str());
+                (result, Vec::new())
+            });
+            if entry.0.unit == result.unit {
+                entry.1.push(result.value);
+            }
+        }
+    }
+
+    order
+        .into_iter()
+        .map(|name| {
+            let (first, values) = &samples[name];
+            let mut extra = first.extra.clone();
+            extra.insert(
+                "samples".to_string(),
+                values
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+
+            BenchmarkResult {
+                name: name.to_string(),
+                value: values.iter().sum::<f64>() / values.len() as f64,
+                unit: first.unit.clone(),
+                range: None,
+                extra,
+            }
+        })
+        .collect()
+}
+
 /// Compare current benchmarks against previous data
 pub fn compare_with_previous(
     current_benches: &[BenchmarkResult],
@@ -287,16 +1266,216 @@ pub fn compare_with_previous(
         None => {
             // No previous data - all benchmarks are new
             CompareReport {
-                comparisons: Vec::new(),
-                alerts: Vec::new(),
-                failures: Vec::new(),
                 new_benchmarks: current_benches.to_vec(),
-                removed_benchmarks: Vec::new(),
+                ..Default::default()
             }
         }
     }
 }
 
+/// Compare current benchmarks against a suite's stored history (oldest run first),
+/// using the baseline mode from the config and checking its regression budgets
+pub fn compare_with_history(
+    current_benches: &[BenchmarkResult],
+    history: &[BenchmarkRun],
+    config: &CompareConfig,
+) -> CompareReport {
+    compare_with_history_at(current_benches, history, config, Utc::now())
+}
+
+/// Like [`compare_with_history`], measuring budget windows back from `now`
+/// (e.g. the date of a stored run being compared)
+pub fn compare_with_history_at(
+    current_benches: &[BenchmarkResult],
+    history: &[BenchmarkRun],
+    config: &CompareConfig,
+    now: DateTime<Utc>,
+) -> CompareReport {
+    let mut report = compare_against_baseline(current_benches, history, config);
+    report.budgets = check_budgets(current_benches, history, config, now);
+    report
+}
+
+/// Check the config's regression budgets against a suite's stored history
+/// (oldest run first), measuring duration windows back from `now`
+///
+/// The history may differ from the one the baseline is built from: comparing
+/// two stored runs, the budgets see every run up to the newer one.
+pub fn check_budgets(
+    current_benches: &[BenchmarkResult],
+    history: &[BenchmarkRun],
+    config: &CompareConfig,
+    now: DateTime<Utc>,
+) -> Vec<BudgetResult> {
+    config
+        .budgets
+        .iter()
+        .filter_map(|budget| {
+            let is_ignored = |name: &str| config.thresholds_for(name).is_none();
+            check_budget(budget, current_benches, history, is_ignored, now)
+        })
+        .collect()
+}
+
+/// Compare current benchmarks against the baseline built from the history
+fn compare_against_baseline(
+    current_benches: &[BenchmarkResult],
+    history: &[BenchmarkRun],
+    config: &CompareConfig,
+) -> CompareReport {
+    let flakiness = match &config.flakiness {
+        Some(flakiness) => suite_flakiness(
+            history,
+            |name| config.alert_threshold_for(name),
+            flakiness.min_runs,
+        )
+        .into_iter()
+        .map(|f| (f.benchmark, f.score))
+        .collect(),
+        None => HashMap::new(),
+    };
+
+    let (window, mad_multiplier) = match config.baseline {
+        BaselineMode::Latest => {
+            let Some(last) = history.last() else {
+                return compare_with_previous(current_benches, None, config);
+            };
+            let current_run = BenchmarkRun {
+                commit: last.commit.clone(), // Placeholder
+                date: chrono::Utc::now(),
+                tool: "cargo".to_string(),
+                benches: current_benches.to_vec(),
+            };
+            let context = HistoryContext {
+                flakiness,
+                ..Default::default()
+            };
+            return compare_runs_in_context(last, &current_run, config, &context);
+        }
+        BaselineMode::Rolling {
+            window,
+            mad_multiplier,
+        } => (window.max(1), mad_multiplier),
+    };
+
+    let runs = &history[history.len().saturating_sub(window)..];
+    let (Some(first), Some(last)) = (runs.first(), runs.last()) else {
+        return compare_with_previous(current_benches, None, config);
+    };
+
+    // Collect each benchmark's values across the window, in first-seen order
+    let mut names: Vec<&str> = Vec::new();
+    let mut series: HashMap<&str, (Vec<f64>, &BenchmarkResult)> = HashMap::new();
+    for run in runs {
+        for bench in &run.benches {
+            let entry = series.entry(bench.name.as_str()).or_insert_with(|| {
+                names.push(bench.name.as_str());
+                (Vec::new(), bench)
+            });
+            entry.0.push(bench.value);
+            entry.1 = bench;
+        }
+    }
+
+    let mut noise_bands = HashMap::new();
+    let mut baseline_benches = Vec::new();
+    for name in names {
+        let (values, latest) = &series[name];
+        let (Some(center), Some(mad)) = (median(values), median_absolute_deviation(values)) else {
+            continue;
+        };
+
+        noise_bands.insert(
+            name.to_string(),
+            (center - mad_multiplier * mad, center + mad_multiplier * mad),
+        );
+
+        let mut extra = HashMap::new();
+        extra.insert(
+            "samples".to_string(),
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        baseline_benches.push(BenchmarkResult {
+            name: name.to_string(),
+            value: center,
+            unit: latest.unit.clone(),
+            range: None,
+            extra,
+        });
+    }
+
+    // The medians were not measured on any commit, so the run gets a synthetic
+    // commit naming the window rather than the newest commit in it
+    let baseline_run = BenchmarkRun {
+        commit: CommitInfo {
+            id: format!(
+                "median({}..{})",
+                short_sha(&first.commit.id),
+                short_sha(&last.commit.id)
+            ),
+            message: format!("Rolling median of {} runs", runs.len()),
+            timestamp: last.commit.timestamp,
+            url: None,
+            author: None,
+        },
+        date: last.date,
+        tool: last.tool.clone(),
+        benches: baseline_benches,
+    };
+    let current_run = BenchmarkRun {
+        commit: last.commit.clone(), // Placeholder
+        date: chrono::Utc::now(),
+        tool: "cargo".to_string(),
+        benches: current_benches.to_vec(),
+    };
+
+    let mut report = compare_runs_in_context(
+        &baseline_run,
+        &current_run,
+        config,
+        &HistoryContext {
+            noise_bands,
+            flakiness,
+        },
+    );
+    // Benchmarks dropped earlier in the window are not missing from this run
+    report.coverage = check_coverage(&last.benches, current_benches, &config.coverage);
+    report.baseline = Some(BaselineWindow {
+        runs: runs.len(),
+        from_commit: first.commit.id.clone(),
+        to_commit: last.commit.id.clone(),
+        mad_multiplier,
+    });
+    report
+}
+
+/// Describe the threshold rule applied to a comparison, if any
+fn rule_suffix(comparison: &ComparisonResult) -> String {
+    comparison
+        .rule
+        .as_ref()
+        .map(|rule| format!(" (rule `{}`)", rule))
+        .unwrap_or_default()
+}
+
+/// Make text safe inside a markdown table cell or list item: pipes would end
+/// the cell and newlines the row
+fn table_cell(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+/// Abbreviate a commit SHA for display
+fn short_sha(id: &str) -> &str {
+    &id[..7.min(id.len())]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -333,6 +1512,68 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_change_points_table_escapes_commit_messages() {
+        let report = CompareReport {
+            change_points: vec![ChangePoint {
+                benchmark: "parse".to_string(),
+                commit_id: "abcdef123".to_string(),
+                commit_message: "Use a | b\n\nin the parser".to_string(),
+                date: Utc::now(),
+                before: 100.0,
+                after: 120.0,
+                percentage_change: 20.0,
+                confidence: 0.99,
+                unit: "ns/iter".to_string(),
+            }],
+            comparisons: vec![ComparisonResult::new(
+                &make_bench("parse", 100.0),
+                &make_bench("parse", 120.0),
+            )],
+            ..Default::default()
+        };
+
+        let summary = report.summary();
+        let row = summary
+            .lines()
+            .find(|line| line.starts_with("| parse | abcdef1"))
+            .unwrap();
+        assert!(row.contains("Use a \\| b in the parser |"));
+        assert_eq!(row.matches(" | ").count(), 5);
+    }
+
+    #[test]
+    fn test_comparison_table_escapes_benchmark_names() {
+        let report = CompareReport {
+            comparisons: vec![ComparisonResult::new(
+                &make_bench("select/a|b", 100.0),
+                &make_bench("select/a|b", 120.0),
+            )],
+            ..Default::default()
+        };
+
+        let summary = report.summary();
+        let row = summary
+            .lines()
+            .find(|line| line.starts_with("| select/a"))
+            .unwrap();
+        assert!(row.starts_with("| select/a\\|b | 100.00 ns/iter |"));
+        assert_eq!(row.matches(" | ").count(), 3);
+    }
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(123.4, "ns/iter"), "123 ns");
+        assert_eq!(format_value(1_234.0, "ns/iter"), "1.23 µs");
+        assert_eq!(format_value(12_345_678.0, "ns/iter"), "12.3 ms");
+        assert_eq!(format_value(2.5, "s"), "2.50 s");
+        assert_eq!(format_value(1_500.0, "B"), "1500 B");
+        assert_eq!(format_value(25_000.0, "B"), "25.0 kB");
+        assert_eq!(format_value(2_500_000.0, "B/s"), "2.50 MB/s");
+        assert_eq!(format_value(25_000.0, "elem/s"), "25000 elem/s");
+        assert_eq!(format_value(3.0, ""), "3.00");
+    }
+
     #[test]
     fn test_compare_runs_regression() {
         let prev = make_run(vec![make_bench("test", 100.0)]);
@@ -361,6 +1602,239 @@ mod tests {
         assert!(!report.has_alerts());
     }
 
+    #[test]
+    fn test_compare_runs_improvement_thresholds() {
+        let prev = make_run(vec![
+            make_bench("faster", 100.0),
+            make_bench("optimized_away", 100.0),
+            make_bench("steady", 100.0),
+            make_bench("vanished", 100.0),
+        ]);
+        let curr = make_run(vec![
+            make_bench("faster", 60.0),
+            make_bench("optimized_away", 2.0),
+            make_bench("steady", 98.0),
+            make_bench("vanished", 0.0),
+        ]);
+
+        let config = CompareConfig {
+            improvement_threshold: Some(Threshold::parse("1.5x").unwrap()),
+            ..Default::default()
+        };
+        let report = compare_runs(&prev, &curr, &config);
+
+        let names = |results: &[ComparisonResult]| {
+            results.iter().map(|c| c.name.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(&report.improvements),
+            vec!["faster", "optimized_away", "vanished"]
+        );
+        assert_eq!(
+            names(&report.suspicious_improvements),
+            vec!["optimized_away", "vanished"]
+        );
+        assert!(report.has_suspicious_improvements());
+        assert!(!report.has_alerts());
+
+        let summary = report.summary();
+        assert!(summary.contains("Suspicious Improvements"));
+        assert!(summary.contains("**optimized_away**: 50.00x faster"));
+        assert!(summary.contains("**vanished**: dropped to zero"));
+        assert!(!summary.contains("infx"));
+        let short = report.short_summary();
+        assert!(short.contains("🟢 3 improvement(s)"));
+        assert!(short.contains("2 suspicious improvement(s)"));
+
+        // Both categories can be disabled
+        let config = CompareConfig {
+            suspicious_improvement_threshold: None,
+            ..Default::default()
+        };
+        let report = compare_runs(&prev, &curr, &config);
+        assert!(report.improvements.is_empty());
+        assert!(report.suspicious_improvements.is_empty());
+        assert!(!report.short_summary().contains("improvement"));
+    }
+
+    #[test]
+    fn test_recorded_suspicious_threshold() {
+        let mut data = BenchmarkData::new();
+        assert_eq!(
+            recorded_suspicious_threshold(&data, "cargo"),
+            Some(Threshold::Ratio(DEFAULT_SUSPICIOUS_SPEEDUP))
+        );
+
+        record_suspicious_threshold(&mut data, "cargo", Some(&Threshold::Ratio(3.0)));
+        record_suspicious_threshold(&mut data, "other", None);
+        assert_eq!(data.suspicious_speedups["cargo"], "300%");
+        assert_eq!(
+            recorded_suspicious_threshold(&data, "cargo"),
+            Some(Threshold::Ratio(3.0))
+        );
+        assert_eq!(recorded_suspicious_threshold(&data, "other"), None);
+    }
+
+    #[test]
+    fn test_aggregate_scores() {
+        let prev = make_run(vec![
+            make_bench("core::parsing::json", 100.0),
+            make_bench("core::parsing::toml", 100.0),
+            make_bench("core::io::read", 100.0),
+            make_bench("fib/20", 100.0),
+            make_bench("experimental::parsing::yaml", 100.0),
+        ]);
+        let curr = make_run(vec![
+            make_bench("core::parsing::json", 121.0),
+            make_bench("core::parsing::toml", 100.0),
+            make_bench("core::io::read", 110.0),
+            make_bench("fib/20", 90.0),
+            make_bench("experimental::parsing::yaml", 500.0),
+        ]);
+
+        let config = CompareConfig {
+            rules: vec![ThresholdRule::parse("experimental::**=ignore").unwrap()],
+            group_rules: vec![ThresholdRule::parse_group("core/parsing=105%:120%").unwrap()],
+            ..Default::default()
+        };
+        let report = compare_runs(&prev, &curr, &config);
+
+        let groups: Vec<_> = report.groups.iter().map(|g| g.group.as_str()).collect();
+        assert_eq!(groups, vec!["", "core", "core/io", "core/parsing", "fib"]);
+
+        let overall = &report.groups[0];
+        assert_eq!(overall.display_name(), "all benchmarks");
+        assert_eq!(overall.benchmarks, 4);
+
+        let parsing = &report.groups[3];
+        assert!((parsing.ratio - 1.1).abs() < 1e-9); // sqrt(1.21 * 1.0)
+        assert!((parsing.percentage_change - 10.0).abs() < 1e-6);
+        assert_eq!(parsing.rule.as_deref(), Some("core/parsing"));
+        assert!(parsing.is_alert && !parsing.is_failure);
+        assert!(!report.groups[2].is_alert);
+
+        assert!(report.has_alerts());
+        assert!(!report.has_failures());
+
+        let summary = report.summary();
+        let scores = summary.find("Aggregate Scores").unwrap();
+        assert!(scores < summary.find("### Comparisons").unwrap());
+        assert!(summary.contains("| core/parsing | 2 | 1.100x | 🔴 +10.00% |"));
+        assert!(summary.contains("**core/parsing**: 10.00% slower overall"));
+    }
+
+    #[test]
+    fn test_aggregate_scores_skip_zero_ratios() {
+        let prev = make_run(vec![
+            make_bench("core::parsing::json", 100.0),
+            make_bench("core::io::read", 100.0),
+        ]);
+        let curr = make_run(vec![
+            make_bench("core::parsing::json", 110.0),
+            make_bench("core::io::read", 0.0),
+        ]);
+
+        let report = compare_runs(&prev, &curr, &CompareConfig::default());
+        let groups: Vec<_> = report.groups.iter().map(|g| g.group.as_str()).collect();
+        assert_eq!(groups, vec!["core/parsing"]);
+        assert_eq!(report.skipped_groups, vec!["", "core", "core/io"]);
+        assert!(report
+            .summary()
+            .contains("_No score for all benchmarks, core, core/io (a benchmark's ratio is zero or infinite)_"));
+    }
+
+    #[test]
+    fn test_merge_repetitions() {
+        let single = vec![make_bench("a", 100.0)];
+        assert_eq!(merge_repetitions(std::slice::from_ref(&single)), single);
+
+        let merged = merge_repetitions(&[
+            vec![make_bench("a", 100.0), make_bench("b", 10.0)],
+            vec![make_bench("a", 110.0)],
+            vec![make_bench("b", 14.0), make_bench("a", 120.0)],
+        ]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].name, "a");
+        assert_eq!(merged[0].value, 110.0);
+        assert_eq!(merged[0].extra["samples"], "100,110,120");
+        assert_eq!(merged[1].value, 12.0);
+
+        // The averaged samples feed the significance test
+        let before =
+            merge_repetitions(&[vec![make_bench("a", 100.0)], vec![make_bench("a", 101.0)]]);
+        let after =
+            merge_repetitions(&[vec![make_bench("a", 150.0)], vec![make_bench("a", 151.0)]]);
+        let comparison = ComparisonResult::new(&before[0], &after[0]);
+        assert!(comparison.p_value.is_some());
+    }
+
+    #[test]
+    fn test_suggest_renames() {
+        let prev = make_run(vec![
+            make_bench("bench_parse", 100.0),
+            make_bench("bench_serialize", 400.0),
+            make_bench("legacy_io", 5000.0),
+        ]);
+        let curr = make_run(vec![
+            make_bench("parser::parse", 103.0),
+            make_bench("codec::serialize", 390.0),
+            make_bench("network::connect", 90.0),
+        ]);
+
+        let report = compare_runs(&prev, &curr, &CompareConfig::default());
+        let pairs: Vec<_> = report
+            .possible_renames
+            .iter()
+            .map(|r| (r.from.as_str(), r.to.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("bench_serialize", "codec::serialize"),
+                ("bench_parse", "parser::parse")
+            ]
+        );
+        assert!(report
+            .summary()
+            .contains("**bench_parse** → **parser::parse**"));
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("same", "same"), 0);
+    }
+
+    #[test]
+    fn test_compare_with_history_flaky_warn_only() {
+        let history: Vec<_> = [100.0, 300.0, 100.0, 300.0, 100.0, 300.0]
+            .iter()
+            .map(|v| make_run(vec![make_bench("noisy", *v), make_bench("steady", 100.0)]))
+            .collect();
+        let current = vec![make_bench("noisy", 900.0), make_bench("steady", 250.0)];
+
+        let config = CompareConfig {
+            flakiness: Some(FlakinessConfig {
+                min_runs: 5,
+                warn_only_score: Some(0.5),
+            }),
+            ..Default::default()
+        };
+        let report = compare_with_history(&current, &history, &config);
+
+        assert!(report.comparisons[0].flakiness.unwrap() > 0.5);
+        assert_eq!(report.comparisons[1].flakiness, Some(0.0));
+        assert_eq!(report.alerts.len(), 2);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].name, "steady");
+        assert_eq!(report.warn_only, vec!["noisy"]);
+
+        let summary = report.summary();
+        assert!(summary.contains("| Flakiness |"));
+        assert!(summary.contains("(flaky, warn-only)"));
+    }
+
     #[test]
     fn test_compare_runs_new_benchmark() {
         let prev = make_run(vec![make_bench("old", 100.0)]);
@@ -386,13 +1860,272 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_percentage() {
-        assert_eq!(parse_percentage("150%").unwrap(), 1.5);
-        assert_eq!(parse_percentage("200%").unwrap(), 2.0);
-        assert_eq!(parse_percentage("100%").unwrap(), 1.0);
-        assert_eq!(parse_percentage("50%").unwrap(), 0.5);
-        assert_eq!(parse_percentage("150").unwrap(), 1.5);
+    fn test_compare_report_serde_roundtrip() {
+        let prev = make_run(vec![make_bench("kept", 100.0), make_bench("gone", 1.0)]);
+        let curr = make_run(vec![make_bench("kept", 250.0), make_bench("new", 1.0)]);
+        let report = compare_runs(&prev, &curr, &CompareConfig::default());
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["comparisons"][0]["name"], "kept");
+        assert_eq!(json["alerts"].as_array().unwrap().len(), 1);
+        assert_eq!(json["new_benchmarks"][0]["name"], "new");
+        assert_eq!(json["removed_benchmarks"][0]["name"], "gone");
+        assert!(json["baseline"].is_null());
+
+        let back: CompareReport = serde_json::from_value(json).unwrap();
+        assert!(back.has_alerts());
+        assert_eq!(back.groups, report.groups);
+    }
+
+    #[test]
+    fn test_compare_runs_require_significance() {
+        let mut noisy_prev = make_bench("noisy", 100.0);
+        noisy_prev.range = Some("+/- 200".to_string());
+        let mut noisy_curr = make_bench("noisy", 250.0);
+        noisy_curr.range = Some("+/- 200".to_string());
+
+        let mut stable_prev = make_bench("stable", 100.0);
+        stable_prev.range = Some("+/- 1".to_string());
+        let mut stable_curr = make_bench("stable", 250.0);
+        stable_curr.range = Some("+/- 1".to_string());
+
+        let prev = make_run(vec![noisy_prev, stable_prev, make_bench("untested", 100.0)]);
+        let curr = make_run(vec![noisy_curr, stable_curr, make_bench("untested", 250.0)]);
+
+        let report = compare_runs(&prev, &curr, &CompareConfig::default());
+        assert_eq!(report.alerts.len(), 3);
+
+        let config = CompareConfig {
+            require_significance: true,
+            ..Default::default()
+        };
+        let report = compare_runs(&prev, &curr, &config);
+        let alerted: Vec<_> = report.alerts.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(alerted, vec!["stable", "untested"]);
+        assert_eq!(report.failures.len(), 2);
+    }
+
+    fn make_history(values: &[f64]) -> Vec<BenchmarkRun> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let mut run = make_run(vec![make_bench("test", *v)]);
+                run.commit.id = format!("commit{}", i);
+                run
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_with_history_latest() {
+        let history = make_history(&[100.0, 100.0, 40.0]);
+        let config = CompareConfig::default();
+        let report = compare_with_history(&[make_bench("test", 90.0)], &history, &config);
+
+        assert_eq!(report.comparisons[0].previous, 40.0);
+        assert!(report.has_alerts());
+        assert!(report.baseline.is_none());
     }
+
+    #[test]
+    fn test_compare_with_history_rolling() {
+        // A single noisy outlier run should not become the baseline
+        let history = make_history(&[100.0, 104.0, 96.0, 102.0, 40.0]);
+        let config = CompareConfig {
+            alert_threshold: Threshold::Ratio(1.05),
+            baseline: BaselineMode::Rolling {
+                window: 5,
+                mad_multiplier: 3.0,
+            },
+            ..Default::default()
+        };
+
+        let report = compare_with_history(&[make_bench("test", 90.0)], &history, &config);
+        assert_eq!(report.comparisons[0].previous, 100.0);
+        assert!(!report.has_alerts());
+
+        let window = report.baseline.as_ref().unwrap();
+        assert_eq!(window.runs, 5);
+        assert_eq!(window.from_commit, "commit0");
+        assert!(report.summary().contains("last 5 run(s)"));
+
+        // 106 is above the ratio threshold but within 3·MAD (MAD = 4) of the median
+        let report = compare_with_history(&[make_bench("test", 106.0)], &history, &config);
+        assert!(!report.has_alerts());
+
+        let report = compare_with_history(&[make_bench("test", 120.0)], &history, &config);
+        assert!(report.has_alerts());
+    }
+
+    #[test]
+    fn test_compare_with_history_rolling_window() {
+        let history = make_history(&[10.0, 10.0, 10.0, 100.0, 100.0]);
+        let config = CompareConfig {
+            baseline: BaselineMode::Rolling {
+                window: 2,
+                mad_multiplier: 3.0,
+            },
+            ..Default::default()
+        };
+
+        let report = compare_with_history(&[make_bench("test", 100.0)], &history, &config);
+        assert_eq!(report.comparisons[0].previous, 100.0);
+        assert_eq!(report.baseline.unwrap().runs, 2);
+    }
+
+    #[test]
+    fn test_coverage_policy() {
+        let prev = make_run(vec![
+            make_bench("a", 1.0),
+            make_bench("b", 1.0),
+            make_bench("c", 1.0),
+            make_bench("legacy::old", 1.0),
+        ]);
+        let curr = make_run(vec![make_bench("a", 1.0)]);
+
+        // Default policy only lists removed benchmarks
+        let report = compare_runs(&prev, &curr, &CompareConfig::default());
+        let coverage = report.coverage.as_ref().unwrap();
+        assert_eq!((coverage.present, coverage.expected), (1, 4));
+        assert!(!report.has_alerts());
+
+        let mut config = CompareConfig {
+            coverage: CoveragePolicy {
+                missing: MissingBenchmarks::Warn,
+                min_coverage: None,
+                allow_removed: vec![BenchmarkPattern::new("legacy::*").unwrap()],
+            },
+            ..Default::default()
+        };
+        let report = compare_runs(&prev, &curr, &config);
+        let coverage = report.coverage.as_ref().unwrap();
+        assert_eq!(coverage.missing, vec!["b", "c"]);
+        assert!((coverage.percentage - 100.0 / 3.0).abs() < 1e-9);
+        assert!(report.has_alerts());
+        assert!(!report.has_failures());
+        assert!(report.summary().contains("### 🕳️ Missing Benchmarks"));
+
+        config.coverage.missing = MissingBenchmarks::Ignore;
+        config.coverage.min_coverage = Some(50.0);
+        assert!(compare_runs(&prev, &curr, &config).has_failures());
+
+        config.coverage.missing = MissingBenchmarks::Fail;
+        config.coverage.min_coverage = None;
+        let curr = make_run(vec![
+            make_bench("a", 1.0),
+            make_bench("b", 1.0),
+            make_bench("c", 1.0),
+        ]);
+        let report = compare_runs(&prev, &curr, &config);
+        assert!(report.coverage.as_ref().unwrap().missing.is_empty());
+        assert!(!report.has_failures());
+
+        assert_eq!("FAIL".parse(), Ok(MissingBenchmarks::Fail));
+        assert!("sometimes".parse::<MissingBenchmarks>().is_err());
+    }
+
+    #[test]
+    fn test_compare_with_history_budget() {
+        // Ten small steps stay below the per-commit threshold but add up
+        let values: Vec<f64> = (0..10).map(|i| 100.0 * 1.04f64.powi(i)).collect();
+        let history = make_history(&values);
+        let config = CompareConfig {
+            alert_threshold: Threshold::Ratio(1.05),
+            budgets: vec![RegressionBudget::parse("+20%@commit0").unwrap()],
+            ..Default::default()
+        };
+
+        let current = [make_bench("test", values[9] * 1.04)];
+        let report = compare_with_history(&current, &history, &config);
+        assert!(report.alerts.is_empty());
+        assert!(report.has_failures());
+        assert_eq!(report.budgets[0].baseline_commit, "commit0");
+        assert_eq!(report.budgets[0].contributing_commits.len(), 10);
+
+        let summary = report.summary();
+        assert!(summary.contains("### 💸 Regression Budgets"));
+        assert!(summary.contains("Budget **120% since commit0** exceeded"));
+        assert!(report.short_summary().contains("💸 1 budget(s) exceeded"));
+    }
+
+    #[test]
+    fn test_check_budgets_between_stored_runs() {
+        // Comparing commit4 against commit1: the drift builds up in between
+        let values = [100.0, 100.0, 106.0, 112.0, 119.0];
+        let history = make_history(&values);
+        let config = CompareConfig {
+            budgets: vec![RegressionBudget::parse("+10%@commit0").unwrap()],
+            ..Default::default()
+        };
+        let head = &history[4];
+
+        let budgets = check_budgets(&head.benches, &history[..=4], &config, head.date);
+        assert!(budgets[0].is_exceeded);
+        let commits: Vec<_> = budgets[0]
+            .contributing_commits
+            .iter()
+            .map(|c| c.commit_id.as_str())
+            .collect();
+        assert_eq!(commits, vec!["commit4", "commit2", "commit3"]);
+
+        // The base history alone cannot tell which commits drifted
+        let budgets = check_budgets(&head.benches, &history[..=1], &config, head.date);
+        assert!(budgets[0]
+            .contributing_commits
+            .iter()
+            .all(|c| !c.commit_id.starts_with("commit")));
+    }
+
+    #[test]
+    fn test_compare_runs_threshold_rules() {
+        let prev = make_run(vec![
+            make_bench("parser::parse", 100.0),
+            make_bench("io::read", 100.0),
+            make_bench("experimental::new_algo", 100.0),
+            make_bench("other", 100.0),
+        ]);
+        let curr = make_run(vec![
+            make_bench("parser::parse", 115.0),
+            make_bench("io::read", 250.0),
+            make_bench("experimental::new_algo", 900.0),
+            make_bench("other", 250.0),
+        ]);
+
+        let config = CompareConfig {
+            rules: vec![
+                ThresholdRule::parse("parser::*=110%").unwrap(),
+                ThresholdRule::parse("io/**=300%").unwrap(),
+                ThresholdRule::parse("experimental::*=ignore").unwrap(),
+                ThresholdRule::parse("**=1000%").unwrap(),
+            ],
+            ..Default::default()
+        };
+        let report = compare_runs(&prev, &curr, &config);
+
+        let alerted: Vec<_> = report.alerts.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(alerted, vec!["parser::parse"]);
+        assert_eq!(report.alerts[0].rule.as_deref(), Some("parser::*"));
+        assert_eq!(report.comparisons[3].rule.as_deref(), Some("**"));
+        assert!(report.summary().contains("`experimental::*`"));
+    }
+
+    #[test]
+    fn test_compare_config_from_thresholds() {
+        let config = CompareConfig::from_thresholds("1.5x", Some("+100%")).unwrap();
+        assert_eq!(config.alert_threshold, Threshold::Ratio(1.5));
+        assert_eq!(config.effective_fail_threshold(), &Threshold::Ratio(2.0));
+
+        assert!(CompareConfig::from_thresholds("200%", Some("150%")).is_err());
+        assert!(CompareConfig::from_thresholds("200%", Some("+10ms")).is_ok());
+        assert!(CompareConfig::from_thresholds("+1ms", Some("+500us")).is_err());
+        assert!(CompareConfig::from_thresholds("+5% & +1ms", Some("+10% & +2ms")).is_ok());
+        assert!(CompareConfig::from_thresholds("+5% & +1ms", Some("+10% & +500us")).is_err());
+        assert!(CompareConfig::from_thresholds("+5% & +1ms", Some("300% | +2ms")).is_ok());
+        assert!(CompareConfig::from_thresholds("+5% & +1ms", Some("300% | 150us")).is_err());
+        assert!(CompareConfig::from_thresholds("300% | +1ms", Some("200%")).is_err());
+    }
+
 }
 
 
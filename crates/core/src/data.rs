@@ -1,8 +1,13 @@
 //! Data structures for benchmark results and storage
 
+use crate::stats::{welch_t_test, Estimate};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+/// Default significance level for comparisons (5%)
+pub const DEFAULT_SIGNIFICANCE_LEVEL: f64 = 0.05;
 
 /// A single benchmark result
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -21,6 +26,80 @@ pub struct BenchmarkResult {
     pub extra: HashMap<String, String>,
 }
 
+/// Represents a parsed benchmark name hierarchy
+///
+/// `grandparent::parent::test` (or `grandparent/parent/test`) names a
+/// module, a group within it and a single test.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BenchmarkPath {
+    /// Grandparent module (container level)
+    pub grandparent: Option<String>,
+    /// Parent module (chart level)
+    pub parent: Option<String>,
+    /// Test name (line on chart)
+    pub test: String,
+}
+
+impl BenchmarkPath {
+    /// Group key of benchmarks without a grandparent or parent level
+    pub const UNGROUPED: &'static str = "_ungrouped";
+
+    /// Parse a benchmark name into its hierarchical components
+    /// Supports both `::` (Rust style) and `/` (Criterion style) separators
+    pub fn parse(name: &str) -> Self {
+        // Try `::` first (Rust module style), then `/` (Criterion style)
+        let parts: Vec<&str> = if name.contains("::") {
+            name.split("::").collect()
+        } else {
+            name.split('/').collect()
+        };
+
+        match parts.len() {
+            1 => BenchmarkPath {
+                grandparent: None,
+                parent: None,
+                test: parts[0].to_string(),
+            },
+            2 => BenchmarkPath {
+                grandparent: None,
+                parent: Some(parts[0].to_string()),
+                test: parts[1].to_string(),
+            },
+            _ => BenchmarkPath {
+                grandparent: Some(parts[0].to_string()),
+                parent: Some(parts[1..parts.len() - 1].join("/")),
+                test: parts[parts.len() - 1].to_string(),
+            },
+        }
+    }
+
+    /// Get the display name for grouping at grandparent level
+    pub fn grandparent_key(&self) -> String {
+        self.grandparent
+            .clone()
+            .unwrap_or_else(|| Self::UNGROUPED.to_string())
+    }
+
+    /// Get the display name for grouping at parent level
+    pub fn parent_key(&self) -> String {
+        match (&self.grandparent, &self.parent) {
+            (Some(_), Some(p)) => p.clone(),
+            (None, Some(p)) => p.clone(),
+            _ => Self::UNGROUPED.to_string(),
+        }
+    }
+
+    /// Groups this benchmark belongs to, outermost first
+    /// (e.g. `["core", "core/parsing"]` for `core::parsing::json`)
+    pub fn groups(&self) -> Vec<String> {
+        match (&self.grandparent, &self.parent) {
+            (Some(g), Some(p)) => vec![g.clone(), format!("{}/{}", g, p)],
+            (None, Some(p)) => vec![p.clone()],
+            _ => Vec::new(),
+        }
+    }
+}
+
 /// Information about a commit
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CommitInfo {
@@ -64,6 +143,14 @@ pub struct BenchmarkRun {
     pub benches: Vec<BenchmarkResult>,
 }
 
+impl BenchmarkRun {
+    /// Check whether this run was taken at a commit id or a prefix of at least 4 characters
+    pub fn matches_commit(&self, commit_id: &str) -> bool {
+        self.commit.id == commit_id
+            || (commit_id.len() >= 4 && self.commit.id.starts_with(commit_id))
+    }
+}
+
 /// Stored benchmark data for a repository
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct BenchmarkData {
@@ -74,6 +161,12 @@ pub struct BenchmarkData {
     pub repo_url: Option<String>,
     /// Benchmark entries grouped by benchmark suite name
     pub entries: HashMap<String, Vec<BenchmarkRun>>,
+    /// Accepted benchmark renames (old name -> new name)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+    /// Suspicious-speedup threshold each suite was last compared with (`off` when disabled)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub suspicious_speedups: BTreeMap<String, String>,
 }
 
 impl BenchmarkData {
@@ -139,6 +232,76 @@ impl BenchmarkData {
         self.last_update = Some(Utc::now());
     }
 
+    /// Insert a run for a past commit, keeping the suite ordered by commit timestamp.
+    /// Like [`Self::add_run`], existing runs of the same commit are replaced.
+    pub fn insert_run(&mut self, suite_name: &str, run: BenchmarkRun) {
+        let entries = self.entries.entry(suite_name.to_string()).or_default();
+        entries.retain(|r| r.commit.id != run.commit.id);
+
+        let position = entries.partition_point(|r| r.commit.timestamp <= run.commit.timestamp);
+        entries.insert(position, run);
+
+        self.last_update = Some(Utc::now());
+    }
+
+    /// Record that benchmark `from` was renamed to `to`. Stored runs keep the names
+    /// they were recorded with; [`Self::resolved`] applies the aliases when reading.
+    pub fn add_alias(&mut self, from: &str, to: &str) {
+        if from == to {
+            return;
+        }
+        // `to` is a current name again, so it must not be renamed further
+        self.aliases.remove(to);
+        self.aliases.insert(from.to_string(), to.to_string());
+    }
+
+    /// The data with every benchmark under its current name (see [`Self::canonical_name`]),
+    /// for comparing runs across renames. Borrowed when there are no aliases.
+    pub fn resolved(&self) -> Cow<'_, Self> {
+        if self.aliases.is_empty() {
+            return Cow::Borrowed(self);
+        }
+
+        let mut data = self.clone();
+        for run in data.entries.values_mut().flatten() {
+            self.rename_benches(run);
+        }
+        Cow::Owned(data)
+    }
+
+    /// Follow the alias chain of a benchmark name to its current name
+    pub fn canonical_name<'a>(&'a self, name: &'a str) -> &'a str {
+        let mut current = name;
+        // Bounded so that an alias cycle cannot loop forever
+        for _ in 0..self.aliases.len() {
+            match self.aliases.get(current) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        current
+    }
+
+    /// Rename a run's benchmarks to their canonical names, unless that would
+    /// duplicate a benchmark already in the run
+    fn rename_benches(&self, run: &mut BenchmarkRun) {
+        for i in 0..run.benches.len() {
+            let canonical = self.canonical_name(&run.benches[i].name).to_string();
+            if canonical != run.benches[i].name && !run.benches.iter().any(|b| b.name == canonical)
+            {
+                run.benches[i].name = canonical;
+            }
+        }
+    }
+
+    /// Get all stored runs for a suite, oldest first
+    pub fn get_runs(&self, suite_name: &str) -> &[BenchmarkRun] {
+        self.entries
+            .get(suite_name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
     /// Get the most recent run for a suite
     pub fn get_latest_run(&self, suite_name: &str) -> Option<&BenchmarkRun> {
         self.entries.get(suite_name).and_then(|runs| runs.last())
@@ -154,6 +317,15 @@ impl BenchmarkData {
             }
         })
     }
+
+    /// Get the most recent run of a suite for a commit. `commit_id` may be a full
+    /// SHA or an abbreviation of at least 4 characters.
+    pub fn get_run_by_commit(&self, suite_name: &str, commit_id: &str) -> Option<&BenchmarkRun> {
+        self.get_runs(suite_name)
+            .iter()
+            .rev()
+            .find(|run| run.matches_commit(commit_id))
+    }
 }
 
 /// Comparison result between two benchmark values
@@ -173,6 +345,34 @@ pub struct ComparisonResult {
     pub is_regression: bool,
     /// Unit of measurement
     pub unit: String,
+    /// Two-sided p-value of the change (if spread information is available)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p_value: Option<f64>,
+    /// Effect size of the change as Cohen's d (if raw samples are available)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effect_size: Option<f64>,
+    /// Statistical significance verdict
+    #[serde(default)]
+    pub significance: Significance,
+    /// Pattern of the threshold rule that applied to this benchmark, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Flakiness score of the benchmark over its history (0 stable, 1 very noisy)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flakiness: Option<f64>,
+}
+
+/// Statistical significance verdict of a comparison
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Significance {
+    /// The change is statistically significant
+    Significant,
+    /// The change is within the measurement noise
+    NotSignificant,
+    /// No spread information was available to test against
+    #[default]
+    Unknown,
 }
 
 impl ComparisonResult {
@@ -190,7 +390,11 @@ impl ComparisonResult {
         // For time-based benchmarks, higher ratio means regression (slower)
         let is_regression = ratio > 1.0;
 
-        Self {
+        let test = Estimate::from_result(previous)
+            .zip(Estimate::from_result(current))
+            .and_then(|(prev, curr)| welch_t_test(&prev, &curr));
+
+        let mut comparison = Self {
             name: current.name.clone(),
             previous: previous.value,
             current: current.value,
@@ -198,7 +402,28 @@ impl ComparisonResult {
             percentage_change,
             is_regression,
             unit: current.unit.clone(),
-        }
+            p_value: test.map(|t| t.p_value),
+            effect_size: test.and_then(|t| t.effect_size),
+            significance: Significance::Unknown,
+            rule: None,
+            flakiness: None,
+        };
+        comparison.classify_significance(DEFAULT_SIGNIFICANCE_LEVEL);
+        comparison
+    }
+
+    /// Set the significance verdict from the p-value at the given level (e.g. 0.05)
+    pub fn classify_significance(&mut self, significance_level: f64) {
+        self.significance = match self.p_value {
+            Some(p) if p < significance_level => Significance::Significant,
+            Some(_) => Significance::NotSignificant,
+            None => Significance::Unknown,
+        };
+    }
+
+    /// Whether the change may be real (significant, or untestable)
+    pub fn is_plausibly_significant(&self) -> bool {
+        self.significance != Significance::NotSignificant
     }
 }
 
@@ -254,4 +479,165 @@ mod tests {
         assert_eq!(comparison.ratio, 0.8);
         assert!(!comparison.is_regression);
     }
+
+    #[test]
+    fn test_comparison_significance() {
+        let make = |value: f64, range: &str| BenchmarkResult {
+            name: "test_bench".to_string(),
+            value,
+            unit: "ns/iter".to_string(),
+            range: Some(range.to_string()),
+            extra: HashMap::new(),
+        };
+
+        let noisy = ComparisonResult::new(&make(100.0, "+/- 40"), &make(110.0, "+/- 40"));
+        assert_eq!(noisy.significance, Significance::NotSignificant);
+
+        let stable = ComparisonResult::new(&make(100.0, "+/- 1"), &make(105.0, "+/- 1"));
+        assert_eq!(stable.significance, Significance::Significant);
+        assert!(stable.p_value.unwrap() < 0.001);
+    }
+
+    #[test]
+    fn test_get_run_by_commit() {
+        let mut data = BenchmarkData::new();
+        for (id, value) in [
+            ("abc1234def", 1.0),
+            ("fff0000aaa", 2.0),
+            ("abc1234def", 3.0),
+        ] {
+            let run = BenchmarkRun {
+                commit: CommitInfo {
+                    id: id.to_string(),
+                    message: String::new(),
+                    timestamp: Utc::now(),
+                    url: None,
+                    author: None,
+                },
+                date: Utc::now(),
+                tool: "cargo".to_string(),
+                benches: vec![BenchmarkResult {
+                    name: "bench".to_string(),
+                    value,
+                    unit: "ns/iter".to_string(),
+                    range: None,
+                    extra: HashMap::new(),
+                }],
+            };
+            data.add_run("cargo", run, None);
+        }
+
+        // The most recent run wins
+        let run = data.get_run_by_commit("cargo", "abc1234def").unwrap();
+        assert_eq!(run.benches[0].value, 3.0);
+        assert_eq!(
+            data.get_run_by_commit("cargo", "fff0").unwrap().benches[0].value,
+            2.0
+        );
+        assert!(data.get_run_by_commit("cargo", "fff").is_none());
+        assert!(data.get_run_by_commit("other", "abc1234def").is_none());
+    }
+
+    #[test]
+    fn test_insert_run_orders_by_commit_timestamp() {
+        let make_run = |id: &str, days_ago: i64| BenchmarkRun {
+            commit: CommitInfo {
+                id: id.to_string(),
+                message: String::new(),
+                timestamp: Utc::now() - chrono::Duration::days(days_ago),
+                url: None,
+                author: None,
+            },
+            date: Utc::now(),
+            tool: "cargo".to_string(),
+            benches: Vec::new(),
+        };
+
+        let mut data = BenchmarkData::new();
+        data.add_run("cargo", make_run("new", 1), None);
+        data.insert_run("cargo", make_run("old", 10));
+        data.insert_run("cargo", make_run("middle", 5));
+        data.insert_run("cargo", make_run("old", 10));
+
+        let ids: Vec<_> = data
+            .get_runs("cargo")
+            .iter()
+            .map(|r| r.commit.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["old", "middle", "new"]);
+    }
+
+    #[test]
+    fn test_aliases() {
+        let make_run = |names: &[&str]| BenchmarkRun {
+            commit: CommitInfo {
+                id: names.join(","),
+                message: String::new(),
+                timestamp: Utc::now(),
+                url: None,
+                author: None,
+            },
+            date: Utc::now(),
+            tool: "cargo".to_string(),
+            benches: names
+                .iter()
+                .map(|name| BenchmarkResult {
+                    name: name.to_string(),
+                    value: 1.0,
+                    unit: "ns/iter".to_string(),
+                    range: None,
+                    extra: HashMap::new(),
+                })
+                .collect(),
+        };
+
+        let mut data = BenchmarkData::new();
+        data.add_run("cargo", make_run(&["bench_parse", "other"]), None);
+        data.add_run("cargo", make_run(&["bench_parse", "parse"]), None);
+
+        data.add_alias("bench_parse", "parser::parse");
+        data.add_alias("parser::parse", "parser::parse_str");
+        assert_eq!(data.canonical_name("bench_parse"), "parser::parse_str");
+
+        let names = |run: &BenchmarkRun| {
+            run.benches
+                .iter()
+                .map(|b| b.name.clone())
+                .collect::<Vec<_>>()
+        };
+        // The stored history keeps the recorded names
+        assert_eq!(
+            names(&data.get_runs("cargo")[0]),
+            vec!["bench_parse", "other"]
+        );
+
+        let resolved = data.resolved();
+        let runs = resolved.get_runs("cargo");
+        assert_eq!(names(&runs[0]), vec!["parser::parse_str", "other"]);
+        assert_eq!(names(&runs[1]), vec!["parser::parse_str", "parse"]);
+        assert!(matches!(BenchmarkData::new().resolved(), Cow::Borrowed(_)));
+
+        // Renaming back does not create a cycle
+        data.add_alias("parser::parse_str", "bench_parse");
+        assert_eq!(data.canonical_name("bench_parse"), "bench_parse");
+        assert_eq!(data.canonical_name("parser::parse"), "bench_parse");
+    }
+
+    #[test]
+    fn test_benchmark_path() {
+        let path = BenchmarkPath::parse("core::parsing::json");
+        assert_eq!(path.grandparent.as_deref(), Some("core"));
+        assert_eq!(path.parent.as_deref(), Some("parsing"));
+        assert_eq!(path.test, "json");
+        assert_eq!(path.groups(), vec!["core", "core/parsing"]);
+
+        let path = BenchmarkPath::parse("fib/20");
+        assert_eq!(path.grandparent_key(), "_ungrouped");
+        assert_eq!(path.parent_key(), "fib");
+        assert_eq!(path.groups(), vec!["fib"]);
+
+        let path = BenchmarkPath::parse("a/b/c/d");
+        assert_eq!(path.parent.as_deref(), Some("b/c"));
+        assert!(BenchmarkPath::parse("plain").groups().is_empty());
+    }
 }
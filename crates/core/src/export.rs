@@ -0,0 +1,383 @@
+//! Exporting the stored history as flat rows
+//!
+//! Every suite, run and benchmark becomes one row, written as CSV, TSV, JSON
+//! lines or an OpenMetrics text exposition. The OpenMetrics output holds the
+//! latest value of each benchmark, since a scrape (or node_exporter's textfile
+//! collector) takes one sample per series.
+
+use crate::data::BenchmarkData;
+use crate::pattern::BenchmarkPattern;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Output format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+    /// One JSON object per line
+    Jsonl,
+    /// OpenMetrics text exposition of the latest values
+    OpenMetrics,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "tsv" => Ok(Self::Tsv),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            "openmetrics" | "prometheus" => Ok(Self::OpenMetrics),
+            _ => Err(format!(
+                "Unknown export format '{}' (expected csv, tsv, jsonl or openmetrics)",
+                s
+            )),
+        }
+    }
+}
+
+/// Which rows to export
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Only these suites (all when empty)
+    pub suites: Vec<String>,
+    /// Only benchmarks matching one of these patterns (all when empty)
+    pub benchmarks: Vec<BenchmarkPattern>,
+    /// Only runs at or after this date
+    pub since: Option<DateTime<Utc>>,
+    /// Only runs at or before this date
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl ExportFilter {
+    fn includes_suite(&self, suite: &str) -> bool {
+        self.suites.is_empty() || self.suites.iter().any(|s| s == suite)
+    }
+
+    fn includes_date(&self, date: &DateTime<Utc>) -> bool {
+        self.since.is_none_or(|since| *date >= since)
+            && self.until.is_none_or(|until| *date <= until)
+    }
+
+    fn includes_benchmark(&self, name: &str) -> bool {
+        self.benchmarks.is_empty() || self.benchmarks.iter().any(|pattern| pattern.matches(name))
+    }
+}
+
+/// One benchmark result of one run
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ExportRow {
+    /// Suite name
+    pub suite: String,
+    /// Commit the run was taken at
+    pub commit: String,
+    /// When the run was taken
+    pub date: DateTime<Utc>,
+    /// Benchmark name
+    pub benchmark: String,
+    /// Measured value
+    pub value: f64,
+    /// Unit of the value
+    pub unit: String,
+    /// Variance reported with the value, if any
+    pub range: Option<String>,
+}
+
+/// Collect the rows matching a filter, by suite name and then in stored run order
+pub fn export_rows(data: &BenchmarkData, filter: &ExportFilter) -> Vec<ExportRow> {
+    let mut suites: Vec<&String> = data
+        .entries
+        .keys()
+        .filter(|suite| filter.includes_suite(suite))
+        .collect();
+    suites.sort();
+
+    let mut rows = Vec::new();
+    for suite in suites {
+        for run in data.get_runs(suite) {
+            if !filter.includes_date(&run.date) {
+                continue;
+            }
+            for bench in &run.benches {
+                if filter.includes_benchmark(&bench.name) {
+                    rows.push(ExportRow {
+                        suite: suite.clone(),
+                        commit: run.commit.id.clone(),
+                        date: run.date,
+                        benchmark: bench.name.clone(),
+                        value: bench.value,
+                        unit: bench.unit.clone(),
+                        range: bench.range.clone(),
+                    });
+                }
+            }
+        }
+    }
+    rows
+}
+
+const COLUMNS: [&str; 7] = [
+    "suite",
+    "commit",
+    "date",
+    "benchmark",
+    "value",
+    "unit",
+    "range",
+];
+
+/// Render rows in an export format
+pub fn format_export(rows: &[ExportRow], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => delimited(rows, ',', csv_field),
+        ExportFormat::Tsv => delimited(rows, '\t', tsv_field),
+        ExportFormat::Jsonl => rows
+            .iter()
+            .map(|row| serde_json::to_string(row).expect("export rows serialize") + "\n")
+            .collect(),
+        ExportFormat::OpenMetrics => openmetrics(rows),
+    }
+}
+
+fn delimited(rows: &[ExportRow], separator: char, field: fn(&str) -> String) -> String {
+    let mut out = COLUMNS.join(&separator.to_string());
+    out.push('\n');
+    for row in rows {
+        let fields = [
+            row.suite.clone(),
+            row.commit.clone(),
+            row.date.to_rfc3339(),
+            row.benchmark.clone(),
+            row.value.to_string(),
+            row.unit.clone(),
+            row.range.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| field(f)).collect();
+        out.push_str(&fields.join(&separator.to_string()));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quote a CSV field when needed (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// TSV has no quoting, so separators inside a field become spaces
+fn tsv_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+/// OpenMetrics exposition of the latest value of each (suite, benchmark)
+fn openmetrics(rows: &[ExportRow]) -> String {
+    // Rows are in run order, so later runs overwrite earlier ones
+    let mut latest: BTreeMap<(&str, &str), &ExportRow> = BTreeMap::new();
+    for row in rows {
+        latest.insert((&row.suite, &row.benchmark), row);
+    }
+
+    let mut out = String::new();
+    out.push_str("# TYPE git_bench_value gauge\n");
+    out.push_str(
+        "# HELP git_bench_value Latest stored benchmark value, in the unit of the unit label.\n",
+    );
+    for row in latest.values() {
+        out.push_str(&format!(
+            "git_bench_value{{suite=\"{}\",benchmark=\"{}\",unit=\"{}\"}} {}\n",
+            label_value(&row.suite),
+            label_value(&row.benchmark),
+            label_value(&row.unit),
+            metric_value(row.value)
+        ));
+    }
+
+    // The commit changes with every run, so it lives in an info metric to keep
+    // the value series stable
+    out.push_str("# TYPE git_bench_run info\n");
+    out.push_str(
+        "# HELP git_bench_run Commit the latest value of the benchmark was measured at.\n",
+    );
+    for row in latest.values() {
+        out.push_str(&format!(
+            "git_bench_run_info{{suite=\"{}\",benchmark=\"{}\",commit=\"{}\"}} 1\n",
+            label_value(&row.suite),
+            label_value(&row.benchmark),
+            label_value(&row.commit)
+        ));
+    }
+
+    out.push_str("# TYPE git_bench_run_timestamp_seconds gauge\n");
+    out.push_str("# HELP git_bench_run_timestamp_seconds When the latest value of the benchmark was measured.\n");
+    for row in latest.values() {
+        out.push_str(&format!(
+            "git_bench_run_timestamp_seconds{{suite=\"{}\",benchmark=\"{}\"}} {}\n",
+            label_value(&row.suite),
+            label_value(&row.benchmark),
+            row.date.timestamp()
+        ));
+    }
+
+    out.push_str("# EOF\n");
+    out
+}
+
+/// Escape a label value (backslash, double quote and newline)
+fn label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn metric_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{BenchmarkResult, BenchmarkRun, CommitInfo};
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn make_run(id: &str, day: u32, values: &[(&str, f64)]) -> BenchmarkRun {
+        let date = Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap();
+        BenchmarkRun {
+            commit: CommitInfo {
+                id: id.to_string(),
+                message: format!("commit {}", id),
+                timestamp: date,
+                url: None,
+                author: None,
+            },
+            date,
+            tool: "cargo".to_string(),
+            benches: values
+                .iter()
+                .map(|(name, value)| BenchmarkResult {
+                    name: name.to_string(),
+                    value: *value,
+                    unit: "ns/iter".to_string(),
+                    range: Some("+/- 3".to_string()),
+                    extra: HashMap::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn data() -> BenchmarkData {
+        let mut data = BenchmarkData::new();
+        data.entries.insert(
+            "cargo".to_string(),
+            vec![
+                make_run("aaaa", 1, &[("parse::json", 100.0), ("io::read", 50.0)]),
+                make_run("bbbb", 5, &[("parse::json", 110.0), ("io::read", 55.0)]),
+            ],
+        );
+        data.entries.insert(
+            "alloc".to_string(),
+            vec![make_run("cccc", 3, &[("a, \"quoted\"", 7.5)])],
+        );
+        data
+    }
+
+    #[test]
+    fn test_export_rows_filters() {
+        let data = data();
+        assert_eq!(export_rows(&data, &ExportFilter::default()).len(), 5);
+
+        let filter = ExportFilter {
+            suites: vec!["cargo".to_string()],
+            benchmarks: vec![BenchmarkPattern::new("parse::*").unwrap()],
+            since: Some(Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap()),
+            until: None,
+        };
+        let rows = export_rows(&data, &filter);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].commit, "bbbb");
+        assert_eq!(rows[0].value, 110.0);
+
+        let filter = ExportFilter {
+            until: Some(Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap()),
+            ..ExportFilter::default()
+        };
+        assert!(export_rows(&data, &filter)
+            .iter()
+            .all(|r| r.commit != "bbbb"));
+
+        // The upper bound is inclusive
+        let filter = ExportFilter {
+            until: Some(Utc.with_ymd_and_hms(2024, 3, 5, 12, 0, 0).unwrap()),
+            ..ExportFilter::default()
+        };
+        assert!(export_rows(&data, &filter)
+            .iter()
+            .any(|r| r.commit == "bbbb"));
+    }
+
+    #[test]
+    fn test_format_delimited() {
+        let rows = export_rows(&data(), &ExportFilter::default());
+
+        let csv = format_export(&rows, ExportFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "suite,commit,date,benchmark,value,unit,range");
+        assert_eq!(
+            lines[1],
+            "alloc,cccc,2024-03-03T12:00:00+00:00,\"a, \"\"quoted\"\"\",7.5,ns/iter,+/- 3"
+        );
+        assert_eq!(lines.len(), 6);
+
+        let tsv = format_export(&rows, ExportFormat::Tsv);
+        assert!(tsv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .contains("\ta, \"quoted\"\t7.5\t"));
+
+        let jsonl = format_export(&rows, ExportFormat::Jsonl);
+        let first: serde_json::Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(first["benchmark"], "a, \"quoted\"");
+        assert_eq!(first["value"], 7.5);
+    }
+
+    #[test]
+    fn test_format_openmetrics() {
+        let rows = export_rows(&data(), &ExportFilter::default());
+        let text = format_export(&rows, ExportFormat::OpenMetrics);
+
+        assert!(text.starts_with("# TYPE git_bench_value gauge\n"));
+        assert!(text.ends_with("# EOF\n"));
+        // Only the latest value of each benchmark
+        assert!(text.contains(
+            "git_bench_value{suite=\"cargo\",benchmark=\"parse::json\",unit=\"ns/iter\"} 110\n"
+        ));
+        assert!(text.contains(
+            "git_bench_run_info{suite=\"cargo\",benchmark=\"parse::json\",commit=\"bbbb\"} 1\n"
+        ));
+        assert!(!text.contains("commit=\"aaaa\""));
+        assert!(text.contains("benchmark=\"a, \\\"quoted\\\"\""));
+        assert!(text.contains(
+            "git_bench_run_timestamp_seconds{suite=\"cargo\",benchmark=\"io::read\"} 1709640000\n"
+        ));
+        assert_eq!(metric_value(f64::INFINITY), "+Inf");
+    }
+}
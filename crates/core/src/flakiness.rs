@@ -0,0 +1,201 @@
+//! Flakiness scoring over benchmark history
+//!
+//! A benchmark is too noisy to gate on when its values spread widely, when
+//! consecutive runs keep flipping between faster and slower, or when it would
+//! often have alerted against the previous run. Each signal is scaled to 0..1
+//! and the flakiness score is their mean.
+
+use crate::data::{BenchmarkResult, BenchmarkRun, ComparisonResult};
+use crate::threshold::Threshold;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Coefficient of variation that counts as maximally noisy (10%)
+const CV_SCALE: f64 = 0.10;
+
+/// Configuration for flakiness scoring in comparisons
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlakinessConfig {
+    /// Minimum number of stored runs before a benchmark is scored
+    pub min_runs: usize,
+    /// Benchmarks scoring at least this never fail, they only alert (None disables)
+    pub warn_only_score: Option<f64>,
+}
+
+impl Default for FlakinessConfig {
+    fn default() -> Self {
+        Self {
+            min_runs: 5,
+            warn_only_score: None,
+        }
+    }
+}
+
+/// Flakiness of a single benchmark over its history
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Flakiness {
+    /// Benchmark name
+    pub benchmark: String,
+    /// Number of runs the score is based on
+    pub runs: usize,
+    /// Mean value over those runs
+    pub mean: f64,
+    /// Unit of measurement
+    pub unit: String,
+    /// Standard deviation divided by the mean
+    pub coefficient_of_variation: f64,
+    /// Fraction of consecutive changes that reversed the previous change's direction
+    pub sign_flip_rate: f64,
+    /// Fraction of runs that would have alerted against the run before
+    pub alert_rate: f64,
+    /// Combined score between 0 (stable) and 1 (very noisy)
+    pub score: f64,
+}
+
+/// Score every benchmark of a suite with at least `min_runs` stored runs,
+/// most flaky first. Alerts are judged with each benchmark's `alert_threshold`
+/// (e.g. [`crate::CompareConfig::alert_threshold_for`]); `None` never alerts.
+pub fn suite_flakiness<'a>(
+    runs: &[BenchmarkRun],
+    alert_threshold: impl Fn(&str) -> Option<&'a Threshold>,
+    min_runs: usize,
+) -> Vec<Flakiness> {
+    let mut series: BTreeMap<&str, Vec<&BenchmarkResult>> = BTreeMap::new();
+    for run in runs {
+        for bench in &run.benches {
+            series.entry(bench.name.as_str()).or_default().push(bench);
+        }
+    }
+
+    let mut scores: Vec<Flakiness> = series
+        .into_iter()
+        .filter(|(_, results)| results.len() >= min_runs.max(2))
+        .map(|(name, results)| score_series(name, &results, alert_threshold(name)))
+        .collect();
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+    scores
+}
+
+fn score_series(
+    name: &str,
+    results: &[&BenchmarkResult],
+    alert_threshold: Option<&Threshold>,
+) -> Flakiness {
+    let values: Vec<f64> = results.iter().map(|r| r.value).collect();
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let coefficient_of_variation = if mean > 0.0 {
+        variance.sqrt() / mean
+    } else {
+        0.0
+    };
+
+    let deltas: Vec<f64> = values
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|d| *d != 0.0)
+        .collect();
+    let sign_flip_rate = if deltas.len() >= 2 {
+        let flips = deltas
+            .windows(2)
+            .filter(|w| w[0].signum() != w[1].signum())
+            .count();
+        flips as f64 / (deltas.len() - 1) as f64
+    } else {
+        0.0
+    };
+
+    let alerts = results
+        .windows(2)
+        .filter(|w| {
+            alert_threshold.is_some_and(|t| t.is_exceeded(&ComparisonResult::new(w[0], w[1])))
+        })
+        .count();
+    let alert_rate = alerts as f64 / (results.len() - 1) as f64;
+
+    let score =
+        ((coefficient_of_variation / CV_SCALE).min(1.0) + sign_flip_rate + alert_rate) / 3.0;
+
+    Flakiness {
+        benchmark: name.to_string(),
+        runs: results.len(),
+        mean,
+        unit: results[0].unit.clone(),
+        coefficient_of_variation,
+        sign_flip_rate,
+        alert_rate,
+        score,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::CommitInfo;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    fn make_runs(series: &[(&str, &[f64])]) -> Vec<BenchmarkRun> {
+        let len = series.iter().map(|(_, v)| v.len()).max().unwrap_or(0);
+        (0..len)
+            .map(|i| BenchmarkRun {
+                commit: CommitInfo {
+                    id: format!("commit{}", i),
+                    message: String::new(),
+                    timestamp: Utc::now(),
+                    url: None,
+                    author: None,
+                },
+                date: Utc::now(),
+                tool: "cargo".to_string(),
+                benches: series
+                    .iter()
+                    .filter_map(|(name, values)| {
+                        values.get(i).map(|value| BenchmarkResult {
+                            name: name.to_string(),
+                            value: *value,
+                            unit: "ns/iter".to_string(),
+                            range: None,
+                            extra: HashMap::new(),
+                        })
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_suite_flakiness() {
+        let runs = make_runs(&[
+            ("stable", &[100.0, 100.5, 101.0, 101.5, 102.0, 102.5]),
+            ("noisy", &[100.0, 130.0, 90.0, 140.0, 85.0, 135.0]),
+            ("short", &[1.0, 2.0]),
+        ]);
+        let threshold = Threshold::parse("120%").unwrap();
+
+        let scores = suite_flakiness(&runs, |_| Some(&threshold), 5);
+        assert_eq!(scores.len(), 2);
+
+        let noisy = &scores[0];
+        assert_eq!(noisy.benchmark, "noisy");
+        assert_eq!(noisy.runs, 6);
+        assert_eq!(noisy.sign_flip_rate, 1.0);
+        assert_eq!(noisy.alert_rate, 0.6); // 3 of 5 transitions are >= 120%
+        assert!(noisy.coefficient_of_variation > CV_SCALE);
+        assert!(noisy.score > 0.8);
+
+        let stable = &scores[1];
+        assert_eq!(stable.sign_flip_rate, 0.0);
+        assert_eq!(stable.alert_rate, 0.0);
+        assert!(stable.score < 0.1);
+    }
+
+    #[test]
+    fn test_ignored_benchmarks_never_alert() {
+        let runs = make_runs(&[("noisy", &[100.0, 300.0, 100.0, 300.0, 100.0])]);
+        let scores = suite_flakiness(&runs, |_| None, 5);
+        assert_eq!(scores[0].alert_rate, 0.0);
+        assert_eq!(scores[0].sign_flip_rate, 1.0);
+    }
+}
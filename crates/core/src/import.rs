@@ -0,0 +1,283 @@
+//! Importing history from github-action-benchmark
+//!
+//! github-action-benchmark keeps its history in `dev/bench/data.js` on the
+//! gh-pages branch, as a script assigning `window.BENCHMARK_DATA`. Its runs
+//! map onto [`BenchmarkRun`]s, except that dates are millisecond epochs,
+//! `extra` is a single string and some tools measure throughput, where bigger
+//! values are better. git-bench treats smaller values as better, so runs of
+//! those tools are left out.
+
+use crate::data::{AuthorInfo, BenchmarkData, BenchmarkResult, BenchmarkRun, CommitInfo};
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// github-action-benchmark tools whose values grow when performance improves
+const BIGGER_IS_BETTER: [&str; 3] = ["benchmarkjs", "pytest", "customBiggerIsBetter"];
+
+/// History read from a github-action-benchmark data file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Import {
+    /// The runs that could be converted
+    pub data: BenchmarkData,
+    /// Runs left out per suite, with the tool that produced them
+    pub skipped: BTreeMap<String, (String, usize)>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActionData {
+    #[serde(default)]
+    repo_url: Option<String>,
+    entries: BTreeMap<String, Vec<ActionRun>>,
+}
+
+#[derive(Deserialize)]
+struct ActionRun {
+    commit: ActionCommit,
+    /// Milliseconds since the epoch
+    date: i64,
+    tool: String,
+    benches: Vec<ActionBench>,
+}
+
+#[derive(Deserialize)]
+struct ActionCommit {
+    id: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    timestamp: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    author: Option<ActionAuthor>,
+}
+
+#[derive(Deserialize)]
+struct ActionAuthor {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ActionBench {
+    name: String,
+    value: f64,
+    unit: String,
+    #[serde(default)]
+    range: Option<String>,
+    #[serde(default)]
+    extra: Option<String>,
+}
+
+/// Strip the `window.BENCHMARK_DATA = ...;` wrapper, leaving the JSON object
+/// (plain JSON is returned as is)
+pub fn strip_data_js(content: &str) -> Result<&str> {
+    let content = content.trim().trim_start_matches('\u{feff}');
+    if content.starts_with('{') {
+        return Ok(content);
+    }
+
+    content
+        .strip_prefix("window.BENCHMARK_DATA")
+        .and_then(|rest| rest.trim_start().strip_prefix('='))
+        .map(|json| json.trim().trim_end_matches(';').trim_end())
+        .ok_or_else(|| {
+            Error::ParseError(
+                "expected `window.BENCHMARK_DATA = {...}` or a JSON object".to_string(),
+            )
+        })
+}
+
+/// Convert a github-action-benchmark `data.js` (or its JSON) into benchmark data
+pub fn import_github_action_benchmark(content: &str) -> Result<Import> {
+    let source: ActionData = serde_json::from_str(strip_data_js(content)?)?;
+
+    let mut import = Import {
+        data: BenchmarkData {
+            repo_url: source.repo_url,
+            ..BenchmarkData::new()
+        },
+        skipped: BTreeMap::new(),
+    };
+
+    for (suite, runs) in source.entries {
+        for run in runs {
+            if BIGGER_IS_BETTER.contains(&run.tool.as_str()) {
+                import
+                    .skipped
+                    .entry(suite.clone())
+                    .or_insert_with(|| (run.tool.clone(), 0))
+                    .1 += 1;
+                continue;
+            }
+            let run = convert_run(run)?;
+            import.data.insert_run(&suite, run);
+        }
+    }
+
+    Ok(import)
+}
+
+fn convert_run(run: ActionRun) -> Result<BenchmarkRun> {
+    let date = DateTime::from_timestamp_millis(run.date).ok_or_else(|| {
+        Error::ParseError(format!(
+            "invalid date {} of commit {}",
+            run.date, run.commit.id
+        ))
+    })?;
+    // Pull request runs may lack a commit timestamp; the run date is close enough
+    let timestamp = run
+        .commit
+        .timestamp
+        .as_deref()
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        .map_or(date, |ts| ts.with_timezone(&Utc));
+
+    let author = run.commit.author.and_then(|author| {
+        Some(AuthorInfo {
+            name: author.name.or_else(|| author.username.clone())?,
+            email: author.email,
+            username: author.username,
+        })
+    });
+
+    Ok(BenchmarkRun {
+        commit: CommitInfo {
+            message: run.commit.message.lines().next().unwrap_or("").to_string(),
+            id: run.commit.id,
+            timestamp,
+            url: run.commit.url,
+            author,
+        },
+        date,
+        // "customSmallerIsBetter" and the like have no meaning for git-bench
+        tool: if run.tool == "cargo" {
+            run.tool
+        } else {
+            format!("github-action-benchmark:{}", run.tool)
+        },
+        benches: run
+            .benches
+            .into_iter()
+            .map(|bench| BenchmarkResult {
+                name: bench.name,
+                value: bench.value,
+                unit: bench.unit,
+                range: bench.range,
+                extra: bench
+                    .extra
+                    .map(|extra| HashMap::from([("extra".to_string(), extra)]))
+                    .unwrap_or_default(),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA_JS: &str = r#"window.BENCHMARK_DATA = {
+  "lastUpdate": 1700000300000,
+  "repoUrl": "https://github.com/owner/repo",
+  "entries": {
+    "Rust Benchmark": [
+      {
+        "commit": {
+          "author": {"email": "dev@example.com", "name": "Dev", "username": "dev"},
+          "committer": {"email": "dev@example.com", "name": "Dev", "username": "dev"},
+          "distinct": true,
+          "id": "2222222222222222222222222222222222222222",
+          "message": "Speed up parser\n\nDetails",
+          "timestamp": "2023-11-14T23:00:00+01:00",
+          "tree_id": "abc",
+          "url": "https://github.com/owner/repo/commit/2222"
+        },
+        "date": 1700000200000,
+        "tool": "cargo",
+        "benches": [
+          {"name": "bench_parse", "value": 120, "range": "± 4", "unit": "ns/iter"}
+        ]
+      },
+      {
+        "commit": {
+          "id": "1111111111111111111111111111111111111111",
+          "message": "Initial",
+          "timestamp": "2023-11-14T20:00:00Z",
+          "url": "https://github.com/owner/repo/commit/1111"
+        },
+        "date": 1700000100000,
+        "tool": "customSmallerIsBetter",
+        "benches": [
+          {"name": "bench_parse", "value": 150.5, "unit": "ns/iter", "extra": "100 samples"}
+        ]
+      }
+    ],
+    "JS": [
+      {
+        "commit": {"id": "3333", "message": "js", "timestamp": "2023-11-14T20:00:00Z"},
+        "date": 1700000100000,
+        "tool": "benchmarkjs",
+        "benches": [{"name": "fib", "value": 1000, "unit": "ops/sec"}]
+      }
+    ]
+  }
+};
+"#;
+
+    #[test]
+    fn test_strip_data_js() {
+        assert_eq!(
+            strip_data_js("window.BENCHMARK_DATA = {\"a\": 1};\n").unwrap(),
+            "{\"a\": 1}"
+        );
+        assert_eq!(strip_data_js("  {\"a\": 1}").unwrap(), "{\"a\": 1}");
+        assert!(strip_data_js("var x = 1;").is_err());
+    }
+
+    #[test]
+    fn test_import_github_action_benchmark() {
+        let import = import_github_action_benchmark(DATA_JS).unwrap();
+        assert_eq!(
+            import.data.repo_url.as_deref(),
+            Some("https://github.com/owner/repo")
+        );
+
+        // Ordered by commit timestamp, not by position in the file
+        let runs = import.data.get_runs("Rust Benchmark");
+        assert_eq!(runs.len(), 2);
+        assert!(runs[0].commit.id.starts_with("1111"));
+        assert_eq!(
+            runs[0].tool,
+            "github-action-benchmark:customSmallerIsBetter"
+        );
+        assert_eq!(runs[0].benches[0].value, 150.5);
+        assert_eq!(runs[0].benches[0].extra["extra"], "100 samples");
+        assert!(runs[0].commit.author.is_none());
+
+        let latest = &runs[1];
+        assert_eq!(latest.tool, "cargo");
+        assert_eq!(latest.commit.message, "Speed up parser");
+        assert_eq!(latest.date.timestamp_millis(), 1700000200000);
+        assert_eq!(
+            latest.commit.timestamp.to_rfc3339(),
+            "2023-11-14T22:00:00+00:00"
+        );
+        assert_eq!(
+            latest.commit.author.as_ref().unwrap().username.as_deref(),
+            Some("dev")
+        );
+        assert_eq!(latest.benches[0].range.as_deref(), Some("± 4"));
+
+        // Throughput benchmarks cannot be compared as smaller-is-better
+        assert!(import.data.get_runs("JS").is_empty());
+        assert_eq!(import.skipped["JS"], ("benchmarkjs".to_string(), 1));
+    }
+}
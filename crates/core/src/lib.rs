@@ -8,18 +8,51 @@
 //! - Parse cargo bench output (both libtest and Criterion formats)
 //! - Data structures for benchmark results
 //! - Benchmark comparison logic
+//! - Statistical significance testing of changes
+//! - Change-point detection across benchmark history
+//! - Geometric-mean scores per suite and hierarchy group
+//! - Flakiness scoring of benchmarks over their history
+//! - Regression budgets limiting cumulative drift over time
+//! - Exporting the history as CSV, TSV, JSON lines or OpenMetrics
+//! - Importing history from github-action-benchmark
 
+pub mod budget;
+pub mod changepoint;
 pub mod compare;
 pub mod data;
 pub mod error;
+pub mod export;
+pub mod flakiness;
+pub mod import;
 pub mod parser;
+pub mod pattern;
+pub mod stats;
+pub mod threshold;
 
-pub use compare::{compare_runs, compare_with_previous, CompareConfig, CompareReport};
+pub use budget::{
+    check_budget, BudgetBaseline, BudgetContribution, BudgetResult, RegressionBudget,
+};
+pub use changepoint::{detect_suite_change_points, ChangePoint, ChangePointConfig};
+pub use compare::{
+    aggregate_scores, check_budgets, check_coverage, compare_runs, compare_with_history,
+    compare_with_history_at, compare_with_previous, format_speedup, format_value,
+    merge_repetitions, record_suspicious_threshold, recorded_suspicious_threshold, suggest_renames,
+    BaselineMode, BaselineWindow, CompareConfig, CompareReport, Coverage, CoveragePolicy,
+    GroupScore, MissingBenchmarks, RenameSuggestion, DEFAULT_SUSPICIOUS_SPEEDUP,
+};
 pub use data::{
-    AuthorInfo, BenchmarkData, BenchmarkResult, BenchmarkRun, CommitInfo, ComparisonResult,
+    AuthorInfo, BenchmarkData, BenchmarkPath, BenchmarkResult, BenchmarkRun, CommitInfo,
+    ComparisonResult, Significance,
 };
 pub use error::{Error, Result};
-pub use parser::{parse_from_file, parse_from_string, CargoParser};
+pub use export::{export_rows, format_export, ExportFilter, ExportFormat, ExportRow};
+pub use flakiness::{suite_flakiness, Flakiness, FlakinessConfig};
+pub use import::{import_github_action_benchmark, strip_data_js, Import};
+pub use parser::{
+    parse_from_file, parse_from_string, parse_from_string_as, CargoParser, OutputFormat,
+};
+pub use pattern::BenchmarkPattern;
+pub use threshold::{parse_percentage, RuleAction, Threshold, ThresholdRule};
 
 
 
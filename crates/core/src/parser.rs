@@ -14,6 +14,35 @@ use crate::data::BenchmarkResult;
 use crate::error::{Error, Result};
 use regex::Regex;
 use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Which benchmark output format to look for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Accept both libtest and Criterion lines
+    #[default]
+    Auto,
+    /// Only libtest `test name ... bench:` lines
+    Libtest,
+    /// Only Criterion `name time: [...]` lines
+    Criterion,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "libtest" => Ok(Self::Libtest),
+            "criterion" => Ok(Self::Criterion),
+            other => Err(Error::ParseError(format!(
+                "Unknown output format '{}': expected auto, libtest or criterion",
+                other
+            ))),
+        }
+    }
+}
 
 /// Parser for cargo benchmark output
 pub struct CargoParser {
@@ -46,6 +75,11 @@ impl CargoParser {
 
     /// Parse cargo bench output and return benchmark results
     pub fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        self.parse_as(output, OutputFormat::Auto)
+    }
+
+    /// Parse cargo bench output, only accepting lines of the given format
+    pub fn parse_as(&self, output: &str, format: OutputFormat) -> Result<Vec<BenchmarkResult>> {
         let mut results = Vec::new();
 
         // Track which benchmarks we've already parsed (to handle multi-line criterion output)
@@ -60,7 +94,11 @@ impl CargoParser {
             }
 
             // Try libtest format first
-            if let Some(result) = self.parse_libtest_line(line) {
+            let libtest = match format {
+                OutputFormat::Criterion => None,
+                _ => self.parse_libtest_line(line),
+            };
+            if let Some(result) = libtest {
                 if !seen.contains_key(&result.name) {
                     seen.insert(result.name.clone(), true);
                     results.push(result);
@@ -69,7 +107,11 @@ impl CargoParser {
             }
 
             // Try criterion format
-            if let Some(result) = self.parse_criterion_line(line) {
+            let criterion = match format {
+                OutputFormat::Libtest => None,
+                _ => self.parse_criterion_line(line),
+            };
+            if let Some(result) = criterion {
                 if !seen.contains_key(&result.name) {
                     seen.insert(result.name.clone(), true);
                     results.push(result);
@@ -182,6 +224,12 @@ pub fn parse_from_string(output: &str) -> Result<Vec<BenchmarkResult>> {
     parser.parse(output)
 }
 
+/// Parse benchmark output of a specific format from a string
+pub fn parse_from_string_as(output: &str, format: OutputFormat) -> Result<Vec<BenchmarkResult>> {
+    let parser = CargoParser::new()?;
+    parser.parse_as(output, format)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -409,4 +457,35 @@ test result: ok. 0 passed; 0 failed; 0 ignored; 2 measured; 0 filtered out
         // 10.5 ms = 10_500_000 ns
         assert!((results[3].value - 10_500_000.0).abs() < 1000.0);
     }
+
+    #[test]
+    fn test_parse_as_format() {
+        let output = r#"
+test bench_add ... bench:         123 ns/iter (+/- 5)
+parse/large             time:   [2.0000 µs 2.1000 µs 2.2000 µs]
+        "#;
+
+        let parser = CargoParser::new().unwrap();
+        let libtest = parser.parse_as(output, OutputFormat::Libtest).unwrap();
+        assert_eq!(libtest.len(), 1);
+        assert_eq!(libtest[0].name, "bench_add");
+
+        let criterion = parser.parse_as(output, OutputFormat::Criterion).unwrap();
+        assert_eq!(criterion.len(), 1);
+        assert_eq!(criterion[0].name, "parse/large");
+
+        assert_eq!(
+            parser.parse_as(output, OutputFormat::Auto).unwrap().len(),
+            2
+        );
+        assert!(parser
+            .parse_as("test result: ok.", OutputFormat::Criterion)
+            .is_err());
+
+        assert_eq!(
+            "Criterion".parse::<OutputFormat>().unwrap(),
+            OutputFormat::Criterion
+        );
+        assert!("json".parse::<OutputFormat>().is_err());
+    }
 }
@@ -0,0 +1,116 @@
+//! Glob and regex patterns matched against benchmark names
+
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+
+/// A pattern selecting benchmarks by name
+///
+/// Patterns are globs over the name hierarchy, where `::` and `/` are both
+/// separators: `*` matches within one level, `**` across levels, `?` one
+/// character. Patterns prefixed with `re:` are regular expressions matched
+/// against the raw name.
+#[derive(Debug, Clone)]
+pub struct BenchmarkPattern {
+    /// The pattern as written
+    pattern: String,
+    matcher: Regex,
+}
+
+impl BenchmarkPattern {
+    /// Parse a glob (or `re:` regex) pattern
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let regex = match pattern.strip_prefix("re:") {
+            Some(re) => re.to_string(),
+            None => glob_to_regex(pattern),
+        };
+        let matcher =
+            Regex::new(&regex).map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            matcher,
+        })
+    }
+
+    /// The pattern as written
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Check whether a benchmark name matches this pattern
+    pub fn matches(&self, name: &str) -> bool {
+        if self.pattern.starts_with("re:") {
+            self.matcher.is_match(name)
+        } else {
+            self.matcher.is_match(&name.replace("::", "/"))
+        }
+    }
+}
+
+impl fmt::Display for BenchmarkPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl FromStr for BenchmarkPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+/// Translate a hierarchy glob into an anchored regex
+fn glob_to_regex(pattern: &str) -> String {
+    let pattern = pattern.replace("::", "/");
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_patterns() {
+        let pattern = BenchmarkPattern::new("parser::*").unwrap();
+        assert!(pattern.matches("parser::parse"));
+        assert!(pattern.matches("parser/parse"));
+        assert!(!pattern.matches("parser::nested::parse"));
+        assert!(!pattern.matches("lexer::parse"));
+
+        let pattern = BenchmarkPattern::new("io/**").unwrap();
+        assert!(pattern.matches("io::read::small"));
+        assert!(!pattern.matches("net::io"));
+
+        let pattern = BenchmarkPattern::new("bench_?").unwrap();
+        assert!(pattern.matches("bench_a"));
+        assert!(!pattern.matches("bench_ab"));
+        assert_eq!(pattern.to_string(), "bench_?");
+    }
+
+    #[test]
+    fn test_regex_patterns() {
+        let pattern: BenchmarkPattern = "re:^bench_(add|sub)$".parse().unwrap();
+        assert!(pattern.matches("bench_add"));
+        assert!(!pattern.matches("bench_mul"));
+        assert!("re:(".parse::<BenchmarkPattern>().is_err());
+    }
+}
@@ -0,0 +1,408 @@
+//! Statistical helpers for benchmark comparison
+//!
+//! Benchmark outputs rarely carry raw samples, so the spread of a measurement
+//! is estimated from whatever is available:
+//!
+//! - `extra["samples"]`: comma-separated raw sample values (exact)
+//! - `extra["low"]` / `extra["high"]`: Criterion's 95% confidence interval
+//! - `range = "+/- X"`: libtest's spread, the max - min of its 50 samples after
+//!   winsorizing at 5%, i.e. roughly the 5th to 95th percentile span
+//!
+//! Two estimates are compared with Welch's t-test. When the sample counts are
+//! unknown the test degrades to a z-test (infinite degrees of freedom).
+
+use crate::data::BenchmarkResult;
+
+/// z-score of the two-sided 95% confidence interval
+const Z_95: f64 = 1.959_963_984_540_054;
+
+/// Width of the 5th to 95th percentile span of a normal distribution, in
+/// standard deviations
+const LIBTEST_SPREAD_SIGMAS: f64 = 2.0 * 1.644_853_626_951_472;
+
+/// Summary of a single benchmark measurement's distribution
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// Point estimate (mean or median)
+    pub mean: f64,
+    /// Standard error of the point estimate
+    pub std_error: f64,
+    /// Sample standard deviation, when raw samples are known
+    pub std_dev: Option<f64>,
+    /// Number of samples, when raw samples are known
+    pub samples: Option<usize>,
+}
+
+impl Estimate {
+    /// Build an estimate from raw samples (needs at least two values)
+    pub fn from_samples(samples: &[f64]) -> Option<Self> {
+        let n = samples.len();
+        if n < 2 {
+            return None;
+        }
+
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+        let std_dev = variance.sqrt();
+
+        Some(Self {
+            mean,
+            std_error: std_dev / (n as f64).sqrt(),
+            std_dev: Some(std_dev),
+            samples: Some(n),
+        })
+    }
+
+    /// Derive an estimate from a parsed benchmark result, if it carries spread information
+    pub fn from_result(result: &BenchmarkResult) -> Option<Self> {
+        if let Some(samples) = result.extra.get("samples") {
+            let values: Vec<f64> = samples
+                .split(',')
+                .filter_map(|s| s.trim().parse().ok())
+                .collect();
+            if let Some(estimate) = Self::from_samples(&values) {
+                return Some(estimate);
+            }
+        }
+
+        if let (Some(low), Some(high)) = (result.extra.get("low"), result.extra.get("high")) {
+            if let (Ok(low), Ok(high)) = (low.parse::<f64>(), high.parse::<f64>()) {
+                return Some(Self::from_interval(result.value, (high - low) / 2.0));
+            }
+        }
+
+        let spread = result.range.as_deref()?.strip_prefix("+/-")?.trim();
+        let spread: f64 = spread.replace(',', "").parse().ok()?;
+        Some(Self::from_libtest_spread(result.value, spread))
+    }
+
+    /// libtest reports the median of its samples and their winsorized spread.
+    /// The spread gives the samples' standard deviation; it is used as the
+    /// uncertainty of the whole measurement rather than divided by the sample
+    /// count, since noise between runs usually dwarfs the error of the median.
+    fn from_libtest_spread(median: f64, spread: f64) -> Self {
+        let std_dev = spread.abs() / LIBTEST_SPREAD_SIGMAS;
+        Self {
+            mean: median,
+            std_error: std_dev,
+            std_dev: Some(std_dev),
+            samples: None,
+        }
+    }
+
+    fn from_interval(mean: f64, half_width: f64) -> Self {
+        Self {
+            mean,
+            std_error: half_width.abs() / Z_95,
+            std_dev: None,
+            samples: None,
+        }
+    }
+}
+
+/// Outcome of a two-sample significance test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    /// Two-sided p-value
+    pub p_value: f64,
+    /// Cohen's d (only when both standard deviations are known)
+    pub effect_size: Option<f64>,
+}
+
+/// Welch's unequal-variances t-test between a previous and current estimate
+///
+/// Returns `None` when neither estimate has any spread to test against.
+pub fn welch_t_test(previous: &Estimate, current: &Estimate) -> Option<TestResult> {
+    let var_prev = previous.std_error.powi(2);
+    let var_curr = current.std_error.powi(2);
+    let pooled_se = (var_prev + var_curr).sqrt();
+
+    if !pooled_se.is_finite() || pooled_se == 0.0 {
+        return None;
+    }
+
+    let t = (current.mean - previous.mean) / pooled_se;
+
+    let p_value = match (previous.samples, current.samples) {
+        (Some(n_prev), Some(n_curr)) => {
+            let df = (var_prev + var_curr).powi(2)
+                / (var_prev.powi(2) / (n_prev - 1) as f64 + var_curr.powi(2) / (n_curr - 1) as f64);
+            student_t_two_sided(t, df)
+        }
+        _ => normal_two_sided(t),
+    };
+
+    let effect_size = match (previous.std_dev, current.std_dev) {
+        (Some(sd_prev), Some(sd_curr)) => {
+            let pooled_sd = ((sd_prev.powi(2) + sd_curr.powi(2)) / 2.0).sqrt();
+            (pooled_sd > 0.0).then(|| (current.mean - previous.mean) / pooled_sd)
+        }
+        _ => None,
+    };
+
+    Some(TestResult {
+        p_value,
+        effect_size,
+    })
+}
+
+/// Median of a set of values (`None` when empty)
+pub fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+/// Median absolute deviation around the median (`None` when empty)
+pub fn median_absolute_deviation(values: &[f64]) -> Option<f64> {
+    let center = median(values)?;
+    let deviations: Vec<f64> = values.iter().map(|v| (v - center).abs()).collect();
+    median(&deviations)
+}
+
+/// Geometric mean of positive values (`None` when empty or any value is not
+/// positive and finite)
+pub fn geometric_mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() || values.iter().any(|v| !v.is_finite() || *v <= 0.0) {
+        return None;
+    }
+
+    let log_sum: f64 = values.iter().map(|v| v.ln()).sum();
+    Some((log_sum / values.len() as f64).exp())
+}
+
+/// Two-sided p-value of a standard normal statistic
+fn normal_two_sided(z: f64) -> f64 {
+    erfc(z.abs() / std::f64::consts::SQRT_2).clamp(0.0, 1.0)
+}
+
+/// Two-sided p-value of a Student's t statistic with `df` degrees of freedom
+fn student_t_two_sided(t: f64, df: f64) -> f64 {
+    if !df.is_finite() {
+        return normal_two_sided(t);
+    }
+    let x = df / (df + t * t);
+    incomplete_beta(df / 2.0, 0.5, x).clamp(0.0, 1.0)
+}
+
+/// Complementary error function (fractional error below 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let ans = t * poly.exp();
+    if x >= 0.0 {
+        ans
+    } else {
+        2.0 - ans
+    }
+}
+
+/// Natural log of the gamma function (Lanczos approximation)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000_000_000_190_015;
+    let mut y = x;
+    for c in COEFFS {
+        y += 1.0;
+        series += c / y;
+    }
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+/// Regularized incomplete beta function I_x(a, b)
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Continued fraction for the incomplete beta function (modified Lentz's method)
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 200;
+    const EPSILON: f64 = 3.0e-14;
+    const TINY: f64 = 1.0e-300;
+
+    let qab = a + b;
+    let qap = a + 1.0;
+    let qam = a - 1.0;
+    let mut c = 1.0;
+    let mut d = 1.0 - qab * x / qap;
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        h *= d * c;
+
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn make_result(value: f64, range: Option<&str>, extra: &[(&str, &str)]) -> BenchmarkResult {
+        BenchmarkResult {
+            name: "bench".to_string(),
+            value,
+            unit: "ns/iter".to_string(),
+            range: range.map(|r| r.to_string()),
+            extra: extra
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn test_estimate_from_samples() {
+        let estimate = Estimate::from_samples(&[1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+        assert_eq!(estimate.mean, 3.0);
+        assert!((estimate.std_dev.unwrap() - 1.5811).abs() < 1e-4);
+        assert_eq!(estimate.samples, Some(5));
+        assert!(Estimate::from_samples(&[1.0]).is_none());
+    }
+
+    #[test]
+    fn test_estimate_from_result_sources() {
+        let libtest = make_result(100.0, Some("+/- 3,290"), &[]);
+        let estimate = Estimate::from_result(&libtest).unwrap();
+        assert!((estimate.std_error - 1000.0).abs() < 0.1);
+        assert_eq!(estimate.std_dev, Some(estimate.std_error));
+
+        let criterion = make_result(100.0, None, &[("low", "90.0"), ("high", "110.0")]);
+        let estimate = Estimate::from_result(&criterion).unwrap();
+        assert!((estimate.std_error - 10.0 / Z_95).abs() < 1e-9);
+
+        let samples = make_result(2.0, None, &[("samples", "1, 2, 3")]);
+        assert_eq!(Estimate::from_result(&samples).unwrap().samples, Some(3));
+
+        assert!(Estimate::from_result(&make_result(1.0, None, &[])).is_none());
+    }
+
+    #[test]
+    fn test_median_and_mad() {
+        assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&[4.0, 1.0, 2.0, 3.0]), Some(2.5));
+        assert_eq!(median(&[]), None);
+        assert_eq!(
+            median_absolute_deviation(&[1.0, 1.0, 2.0, 2.0, 4.0, 6.0, 9.0]),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn test_geometric_mean() {
+        assert!((geometric_mean(&[2.0, 8.0]).unwrap() - 4.0).abs() < 1e-12);
+        assert!((geometric_mean(&[1.1, 1.0 / 1.1]).unwrap() - 1.0).abs() < 1e-12);
+        assert_eq!(geometric_mean(&[]), None);
+        assert_eq!(geometric_mean(&[1.0, 0.0]), None);
+    }
+
+    #[test]
+    fn test_normal_p_value() {
+        assert!((normal_two_sided(1.96) - 0.05).abs() < 1e-3);
+        assert!((normal_two_sided(0.0) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_student_t_p_value() {
+        // t = 2.228 is the 97.5th percentile for df = 10
+        assert!((student_t_two_sided(2.228, 10.0) - 0.05).abs() < 1e-3);
+        assert!((student_t_two_sided(-2.228, 10.0) - 0.05).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_welch_t_test() {
+        let prev = Estimate::from_samples(&[100.0, 101.0, 99.0, 100.5, 99.5]).unwrap();
+        let same = Estimate::from_samples(&[100.2, 100.8, 99.1, 100.4, 99.6]).unwrap();
+        let slower = Estimate::from_samples(&[110.0, 111.0, 109.0, 110.5, 109.5]).unwrap();
+
+        assert!(welch_t_test(&prev, &same).unwrap().p_value > 0.5);
+
+        let result = welch_t_test(&prev, &slower).unwrap();
+        assert!(result.p_value < 0.001);
+        assert!(result.effect_size.unwrap() > 5.0);
+    }
+
+    #[test]
+    fn test_welch_t_test_without_spread() {
+        let a = Estimate::from_interval(1.0, 0.0);
+        let b = Estimate::from_interval(2.0, 0.0);
+        assert!(welch_t_test(&a, &b).is_none());
+    }
+}
@@ -0,0 +1,521 @@
+//! Regression thresholds and per-benchmark threshold rules
+
+use crate::data::ComparisonResult;
+use crate::pattern::BenchmarkPattern;
+use std::fmt;
+use std::str::FromStr;
+
+/// A regression threshold
+///
+/// Grammar (whitespace is ignored):
+///
+/// - `200%`, `1.5x`: current/previous ratio must reach the value
+/// - `+5%`: current must be at least 5% slower (same as `105%`)
+/// - `+20ns`, `50ms`: current must be slower by at least this absolute amount
+///   (units: `ps`, `ns`, `us`/`µs`, `ms`, `s`, `m`)
+/// - `A & B` (or `A and B`): all terms must be met, e.g. `+5% & +100ns`
+/// - `A | B` (or `A or B`): any term must be met; binds looser than `&`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Threshold {
+    /// Minimum current/previous ratio
+    Ratio(f64),
+    /// Minimum absolute slowdown in nanoseconds
+    AbsoluteDelta(f64),
+    /// All thresholds must be exceeded
+    All(Vec<Threshold>),
+    /// Any threshold must be exceeded
+    Any(Vec<Threshold>),
+}
+
+impl Threshold {
+    /// Parse a threshold expression (see the type-level docs for the grammar)
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("Threshold cannot be empty".to_string());
+        }
+
+        let alternatives = split_keyword(s, '|', " or ");
+        if alternatives.len() > 1 {
+            return alternatives
+                .iter()
+                .map(|alt| Self::parse(alt))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Threshold::Any);
+        }
+
+        let terms = split_keyword(s, '&', " and ");
+        if terms.len() > 1 {
+            return terms
+                .iter()
+                .map(|term| Self::parse_term(term))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Threshold::All);
+        }
+
+        Self::parse_term(s)
+    }
+
+    fn parse_term(term: &str) -> Result<Self, String> {
+        let term = term.trim();
+        let term = term.strip_prefix("at least ").unwrap_or(term).trim();
+        let (relative, body) = match term.strip_prefix('+') {
+            Some(rest) => (true, rest.trim()),
+            None => (false, term),
+        };
+
+        let threshold = if body.ends_with('%') || body.parse::<f64>().is_ok() {
+            let pct = parse_percentage(body)?;
+            Threshold::Ratio(if relative { 1.0 + pct } else { pct })
+        } else if let Some(multiplier) = body.strip_suffix('x') {
+            if relative {
+                return Err(format!(
+                    "Invalid threshold '{}': use '1.5x' or '+50%'",
+                    term
+                ));
+            }
+            let value: f64 = multiplier
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid multiplier: {}", body))?;
+            Threshold::Ratio(value)
+        } else {
+            Threshold::AbsoluteDelta(parse_duration_ns(body)?)
+        };
+
+        let positive = match threshold {
+            Threshold::Ratio(r) => r > 0.0 && (!relative || r > 1.0),
+            Threshold::AbsoluteDelta(d) => d > 0.0,
+            _ => true,
+        };
+        if !positive {
+            return Err(format!("Threshold '{}' must be greater than 0", term));
+        }
+
+        Ok(threshold)
+    }
+
+    /// Check whether a comparison's slowdown reaches this threshold
+    pub fn is_exceeded(&self, comparison: &ComparisonResult) -> bool {
+        self.is_exceeded_by(
+            comparison.ratio,
+            comparison.current - comparison.previous,
+            &comparison.unit,
+        )
+    }
+
+    /// Check whether a comparison's speedup reaches this threshold, read in the
+    /// opposite direction: `10x` means ten times faster, `+50ms` means 50ms faster
+    pub fn is_exceeded_by_improvement(&self, comparison: &ComparisonResult) -> bool {
+        self.is_exceeded_by_speedup(comparison.previous, comparison.current, &comparison.unit)
+    }
+
+    /// Like [`Self::is_exceeded_by_improvement`], for a change from `previous` to `current`
+    pub fn is_exceeded_by_speedup(&self, previous: f64, current: f64, unit: &str) -> bool {
+        self.is_exceeded_by(speedup(previous, current), previous - current, unit)
+    }
+
+    pub(crate) fn is_exceeded_by(&self, ratio: f64, delta: f64, unit: &str) -> bool {
+        match self {
+            Threshold::Ratio(min_ratio) => ratio >= *min_ratio,
+            Threshold::AbsoluteDelta(delta_ns) => {
+                unit_to_ns(unit).is_some_and(|scale| delta * scale >= *delta_ns)
+            }
+            Threshold::All(all) => all.iter().all(|t| t.is_exceeded_by(ratio, delta, unit)),
+            Threshold::Any(any) => any.iter().any(|t| t.is_exceeded_by(ratio, delta, unit)),
+        }
+    }
+
+    /// The ratio this threshold requires, if it is a pure ratio
+    pub fn as_ratio(&self) -> Option<f64> {
+        match self {
+            Threshold::Ratio(ratio) => Some(*ratio),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Threshold {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Threshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Threshold::Ratio(ratio) => write!(f, "{}%", round_display(ratio * 100.0)),
+            Threshold::AbsoluteDelta(delta_ns) => write!(f, "+{}ns", round_display(*delta_ns)),
+            Threshold::All(all) => {
+                let parts: Vec<_> = all.iter().map(|t| t.to_string()).collect();
+                write!(f, "{}", parts.join(" & "))
+            }
+            Threshold::Any(any) => {
+                let parts: Vec<_> = any.iter().map(|t| t.to_string()).collect();
+                write!(f, "{}", parts.join(" | "))
+            }
+        }
+    }
+}
+
+/// previous/current, infinite when the value dropped to zero
+pub(crate) fn speedup(previous: f64, current: f64) -> f64 {
+    if current > 0.0 {
+        previous / current
+    } else if previous > 0.0 {
+        f64::INFINITY
+    } else {
+        1.0
+    }
+}
+
+/// Round away floating point noise (e.g. 105.00000000000001) for display
+fn round_display(value: f64) -> f64 {
+    (value * 1e6).round() / 1e6
+}
+
+/// Split on a symbol or a case-insensitive ASCII keyword (e.g. `&` / ` and `)
+fn split_keyword(s: &str, symbol: char, keyword: &str) -> Vec<String> {
+    let normalized = s.replace(symbol, keyword);
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (i, _) in normalized.char_indices() {
+        let at_keyword = normalized
+            .get(i..i + keyword.len())
+            .is_some_and(|word| word.eq_ignore_ascii_case(keyword));
+        if i >= start && at_keyword {
+            parts.push(normalized[start..i].to_string());
+            start = i + keyword.len();
+        }
+    }
+    parts.push(normalized[start..].to_string());
+    parts
+}
+
+/// Nanoseconds per unit for time units (`ns/iter` counts as `ns`)
+pub(crate) fn unit_to_ns(unit: &str) -> Option<f64> {
+    let unit = unit.trim();
+    let unit = unit.strip_suffix("/iter").unwrap_or(unit);
+    match unit {
+        "ps" => Some(0.001),
+        "ns" => Some(1.0),
+        "us" | "µs" => Some(1_000.0),
+        "ms" => Some(1_000_000.0),
+        "s" => Some(1_000_000_000.0),
+        "m" | "min" => Some(60_000_000_000.0),
+        _ => None,
+    }
+}
+
+/// Parse an absolute duration like "20ns" or "1.5ms" into nanoseconds
+fn parse_duration_ns(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .ok_or_else(|| format!("Invalid threshold '{}': missing unit", s))?;
+    let (value, unit) = s.split_at(split);
+    let value: f64 = value
+        .parse()
+        .map_err(|_| format!("Invalid threshold '{}'", s))?;
+    let scale = unit_to_ns(unit).ok_or_else(|| {
+        format!(
+            "Invalid threshold '{}'. Expected formats: '200%', '1.5x', '+5%', '+20ns', '50ms'",
+            s
+        )
+    })?;
+    Ok(value * scale)
+}
+
+/// What a threshold rule does to the benchmarks it matches
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleAction {
+    /// Use these thresholds instead of the global ones
+    Thresholds {
+        alert: Threshold,
+        fail: Option<Threshold>,
+    },
+    /// Never alert or fail on matching benchmarks
+    Ignore,
+}
+
+/// Per-benchmark threshold override matched against benchmark names
+/// (see [`BenchmarkPattern`] for the pattern syntax)
+#[derive(Debug, Clone)]
+pub struct ThresholdRule {
+    /// Benchmarks the rule applies to
+    pub pattern: BenchmarkPattern,
+    /// What to do with matching benchmarks
+    pub action: RuleAction,
+}
+
+impl ThresholdRule {
+    /// Create a rule from a glob (or `re:` regex) pattern
+    pub fn new(pattern: &str, action: RuleAction) -> Result<Self, String> {
+        Ok(Self {
+            pattern: BenchmarkPattern::new(pattern)?,
+            action,
+        })
+    }
+
+    /// Parse a rule spec: `PATTERN=ALERT[:FAIL]` (e.g. `parser::*=110%`) or `PATTERN=ignore`.
+    /// Thresholds use the [`Threshold`] grammar.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (pattern, value) = spec
+            .rsplit_once('=')
+            .ok_or_else(|| format!("Invalid threshold rule '{}': expected PATTERN=VALUE", spec))?;
+        let (pattern, value) = (pattern.trim(), value.trim());
+
+        let action = if value.eq_ignore_ascii_case("ignore") {
+            RuleAction::Ignore
+        } else {
+            let (alert, fail) = match value.split_once(':') {
+                Some((alert, fail)) => (alert, Some(fail)),
+                None => (value, None),
+            };
+            let alert = Threshold::parse(alert)?;
+            let fail = fail.map(Threshold::parse).transpose()?;
+            if !fail_at_least_alert(&alert, fail.as_ref()) {
+                return Err(format!(
+                    "Invalid threshold rule '{}': fail threshold must be >= alert threshold",
+                    spec
+                ));
+            }
+            RuleAction::Thresholds { alert, fail }
+        };
+
+        Self::new(pattern, action)
+    }
+
+    /// Parse a group rule spec like [`Self::parse`], accepting only ratio thresholds:
+    /// a group's geometric-mean score has no absolute delta to compare against
+    pub fn parse_group(spec: &str) -> Result<Self, String> {
+        let rule = Self::parse(spec)?;
+        if let RuleAction::Thresholds { alert, fail } = &rule.action {
+            if alert.as_ratio().is_none() || fail.as_ref().is_some_and(|f| f.as_ratio().is_none()) {
+                return Err(format!(
+                    "Invalid group threshold '{}': groups only take ratio thresholds like '105%' or '1.05x'",
+                    spec
+                ));
+            }
+        }
+        Ok(rule)
+    }
+
+    /// Check whether a benchmark name matches this rule
+    pub fn matches(&self, name: &str) -> bool {
+        self.pattern.matches(name)
+    }
+}
+
+/// A fail threshold must not be looser than the alert threshold
+pub(crate) fn fail_at_least_alert(alert: &Threshold, fail: Option<&Threshold>) -> bool {
+    fail.is_none_or(|fail| implies(fail, alert) != Some(false))
+}
+
+/// Whether every slowdown reaching `fail` also reaches `alert`. A ratio and an
+/// absolute delta cannot be ordered, so only terms of the same kind are
+/// compared; `None` when there are none (`+10ms` against `200%`).
+fn implies(fail: &Threshold, alert: &Threshold) -> Option<bool> {
+    match (fail, alert) {
+        (_, Threshold::All(alerts)) => all_known(alerts.iter().map(|alert| implies(fail, alert))),
+        (Threshold::Any(fails), _) => all_known(fails.iter().map(|fail| implies(fail, alert))),
+        (Threshold::All(fails), _) => any_known(fails.iter().map(|fail| implies(fail, alert))),
+        (_, Threshold::Any(alerts)) => any_known(alerts.iter().map(|alert| implies(fail, alert))),
+        (Threshold::Ratio(fail), Threshold::Ratio(alert)) => Some(fail >= alert),
+        (Threshold::AbsoluteDelta(fail), Threshold::AbsoluteDelta(alert)) => Some(fail >= alert),
+        _ => None,
+    }
+}
+
+/// `false` if any known result is false, ignoring unknown ones
+fn all_known(results: impl Iterator<Item = Option<bool>>) -> Option<bool> {
+    results.flatten().reduce(|a, b| a && b)
+}
+
+/// `true` if any known result is true, ignoring unknown ones
+fn any_known(results: impl Iterator<Item = Option<bool>>) -> Option<bool> {
+    results.flatten().reduce(|a, b| a || b)
+}
+
+/// Parse a percentage string like "150%" to a ratio (1.5)
+pub fn parse_percentage(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let s = s.strip_suffix('%').unwrap_or(s);
+    let value: f64 = s
+        .parse()
+        .map_err(|_| format!("Invalid percentage: {}", s))?;
+    Ok(value / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::BenchmarkResult;
+    use std::collections::HashMap;
+
+    fn make_bench(name: &str, value: f64) -> BenchmarkResult {
+        BenchmarkResult {
+            name: name.to_string(),
+            value,
+            unit: "ns/iter".to_string(),
+            range: None,
+            extra: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_group_rules_take_only_ratios() {
+        assert!(ThresholdRule::parse_group("core/**=105%:1.2x").is_ok());
+        assert!(ThresholdRule::parse_group("core/**=ignore").is_ok());
+        assert!(ThresholdRule::parse_group("core/**=+5ns").is_err());
+        assert!(ThresholdRule::parse_group("core/**=105%:105% & +5ns").is_err());
+    }
+
+    #[test]
+    fn test_threshold_is_exceeded_by_improvement() {
+        let prev = make_bench("test", 100.0);
+        let curr = make_bench("test", 10.0);
+        let comparison = ComparisonResult::new(&prev, &curr);
+
+        assert!(Threshold::Ratio(10.0).is_exceeded_by_improvement(&comparison));
+        assert!(!Threshold::Ratio(11.0).is_exceeded_by_improvement(&comparison));
+        assert!(Threshold::parse("+90ns")
+            .unwrap()
+            .is_exceeded_by_improvement(&comparison));
+        assert!(!Threshold::Ratio(1.1).is_exceeded(&comparison));
+    }
+
+    #[test]
+    fn test_threshold_rule_matching() {
+        let rule = ThresholdRule::parse("parser::*=110%").unwrap();
+        assert!(rule.matches("parser::parse"));
+        assert!(rule.matches("parser/parse"));
+        assert!(!rule.matches("parser::nested::parse"));
+        assert!(!rule.matches("lexer::parse"));
+        assert_eq!(
+            rule.action,
+            RuleAction::Thresholds {
+                alert: Threshold::Ratio(1.1),
+                fail: None
+            }
+        );
+
+        let rule = ThresholdRule::parse("io/**=300%:400%").unwrap();
+        assert!(rule.matches("io::read::small"));
+        assert!(rule.matches("io/write"));
+        assert!(!rule.matches("net::io"));
+
+        let rule = ThresholdRule::parse("re:^bench_(add|sub)$=ignore").unwrap();
+        assert_eq!(rule.action, RuleAction::Ignore);
+        assert!(rule.matches("bench_add"));
+        assert!(!rule.matches("bench_mul"));
+
+        assert!(ThresholdRule::parse("no_value").is_err());
+        assert!(ThresholdRule::parse("a=300%:200%").is_err());
+        assert!(ThresholdRule::parse("re:(=ignore").is_err());
+    }
+
+    #[test]
+    fn test_threshold_parse() {
+        assert_eq!(Threshold::parse("200%").unwrap(), Threshold::Ratio(2.0));
+        assert_eq!(Threshold::parse("1.5x").unwrap(), Threshold::Ratio(1.5));
+        assert_eq!(Threshold::parse("+5%").unwrap(), Threshold::Ratio(1.05));
+        assert_eq!(Threshold::parse("150").unwrap(), Threshold::Ratio(1.5));
+        assert_eq!(
+            Threshold::parse("+20ns").unwrap(),
+            Threshold::AbsoluteDelta(20.0)
+        );
+        assert_eq!(
+            Threshold::parse("50ms").unwrap(),
+            Threshold::AbsoluteDelta(50_000_000.0)
+        );
+        assert_eq!(
+            Threshold::parse("1.5 µs").unwrap(),
+            Threshold::AbsoluteDelta(1500.0)
+        );
+        assert_eq!(
+            Threshold::parse("at least +5% and at least +100ns").unwrap(),
+            Threshold::All(vec![
+                Threshold::Ratio(1.05),
+                Threshold::AbsoluteDelta(100.0)
+            ])
+        );
+        assert_eq!(
+            Threshold::parse("+5% AND +100ns").unwrap(),
+            Threshold::parse("+5% & +100ns").unwrap()
+        );
+        assert_eq!(
+            Threshold::parse("300% | +5% & +1us").unwrap(),
+            Threshold::Any(vec![
+                Threshold::Ratio(3.0),
+                Threshold::All(vec![
+                    Threshold::Ratio(1.05),
+                    Threshold::AbsoluteDelta(1000.0)
+                ])
+            ])
+        );
+
+        for invalid in [
+            "",
+            "0%",
+            "-100%",
+            "0x",
+            "-1x",
+            "0ms",
+            "+1.5x",
+            "+0%",
+            "10 parsecs",
+            "+5% and İİ +1ns",
+        ] {
+            assert!(
+                Threshold::parse(invalid).is_err(),
+                "{} should be invalid",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_threshold_display_roundtrip() {
+        for spec in ["200%", "+5% & +100ns", "300% | 50ms"] {
+            let threshold = Threshold::parse(spec).unwrap();
+            assert_eq!(Threshold::parse(&threshold.to_string()).unwrap(), threshold);
+        }
+        assert_eq!(Threshold::parse("+5%").unwrap().to_string(), "105%");
+    }
+
+    #[test]
+    fn test_threshold_is_exceeded() {
+        let comparison = |prev: f64, curr: f64, unit: &str| {
+            let mut prev = make_bench("test", prev);
+            let mut curr = make_bench("test", curr);
+            prev.unit = unit.to_string();
+            curr.unit = unit.to_string();
+            ComparisonResult::new(&prev, &curr)
+        };
+
+        let combined = Threshold::parse("+5% & +100ns").unwrap();
+        // 10% slower but only 10ns
+        assert!(!combined.is_exceeded(&comparison(100.0, 110.0, "ns/iter")));
+        // 10% slower and 1000ns
+        assert!(combined.is_exceeded(&comparison(10_000.0, 11_000.0, "ns/iter")));
+        // 1µs slower, in µs units
+        assert!(combined.is_exceeded(&comparison(10.0, 11.0, "µs")));
+        // absolute deltas never apply to non-time units
+        assert!(!Threshold::parse("+1ns")
+            .unwrap()
+            .is_exceeded(&comparison(1.0, 100.0, "bytes")));
+    }
+
+    #[test]
+    fn test_parse_percentage() {
+        assert_eq!(parse_percentage("150%").unwrap(), 1.5);
+        assert_eq!(parse_percentage("200%").unwrap(), 2.0);
+        assert_eq!(parse_percentage("100%").unwrap(), 1.0);
+        assert_eq!(parse_percentage("50%").unwrap(), 0.5);
+        assert_eq!(parse_percentage("150").unwrap(), 1.5);
+    }
+}
@@ -85,6 +85,7 @@ fn make_benchmark_data(entries: HashMap<String, Vec<BenchmarkRun>>) -> Benchmark
         last_update: Some(Utc::now()),
         repo_url: Some("https://github.com/user/repo".to_string()),
         entries,
+        ..Default::default()
     }
 }
 
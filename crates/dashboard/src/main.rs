@@ -14,7 +14,10 @@
 
 use dioxus::prelude::*;
 use dioxus_web::{Config, WebHistory};
-use git_bench_core::{BenchmarkData, BenchmarkRun};
+use git_bench_core::stats::geometric_mean;
+use git_bench_core::{
+    recorded_suspicious_threshold, BenchmarkData, BenchmarkPath, BenchmarkRun, Threshold,
+};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::rc::Rc;
 use wasm_bindgen::JsCast;
@@ -32,6 +35,10 @@ struct ThemeCtx(Signal<bool>);
 #[derive(Clone)]
 struct BenchNameCtx(Signal<String>);
 
+/// Suspicious-speedup threshold the suite was compared with (`None` when disabled)
+#[derive(Clone)]
+struct SuspiciousThresholdCtx(Option<Threshold>);
+
 /// Global selection context for from/to commit comparison
 #[derive(Clone, Copy)]
 struct SelectionCtx {
@@ -73,64 +80,6 @@ fn get_data_url() -> String {
     format!("{}data.json", BASE_PATH.get().unwrap_or(&"/".to_string()))
 }
 
-/// Represents a parsed benchmark name hierarchy
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct BenchmarkPath {
-    /// Grandparent module (container level)
-    grandparent: Option<String>,
-    /// Parent module (chart level)
-    parent: Option<String>,
-    /// Test name (line on chart)
-    test: String,
-}
-
-impl BenchmarkPath {
-    /// Parse a benchmark name into its hierarchical components
-    /// Supports both `::` (Rust style) and `/` (Criterion style) separators
-    fn parse(name: &str) -> Self {
-        // Try `::` first (Rust module style), then `/` (Criterion style)
-        let parts: Vec<&str> = if name.contains("::") {
-            name.split("::").collect()
-        } else {
-            name.split('/').collect()
-        };
-
-        match parts.len() {
-            1 => BenchmarkPath {
-                grandparent: None,
-                parent: None,
-                test: parts[0].to_string(),
-            },
-            2 => BenchmarkPath {
-                grandparent: None,
-                parent: Some(parts[0].to_string()),
-                test: parts[1].to_string(),
-            },
-            _ => BenchmarkPath {
-                grandparent: Some(parts[0].to_string()),
-                parent: Some(parts[1..parts.len() - 1].join("/")),
-                test: parts[parts.len() - 1].to_string(),
-            },
-        }
-    }
-
-    /// Get the display name for grouping at grandparent level
-    fn grandparent_key(&self) -> String {
-        self.grandparent
-            .clone()
-            .unwrap_or_else(|| "_ungrouped".to_string())
-    }
-
-    /// Get the display name for grouping at parent level
-    fn parent_key(&self) -> String {
-        match (&self.grandparent, &self.parent) {
-            (Some(_), Some(p)) => p.clone(),
-            (None, Some(p)) => p.clone(),
-            _ => "_ungrouped".to_string(),
-        }
-    }
-}
-
 /// Hierarchical data structure for benchmarks
 /// grandparent -> parent -> test -> data points
 type HierarchicalData = BTreeMap<String, BTreeMap<String, Vec<BenchmarkDataPoint>>>;
@@ -267,7 +216,8 @@ fn App() -> Element {
                     if let Some(name) = benchmark_data.entries.keys().next() {
                         bench_name.set(name.clone());
                     }
-                    data.set(Some(benchmark_data));
+                    // Follow renamed benchmarks across the history
+                    data.set(Some(benchmark_data.resolved().into_owned()));
                     loading.set(false);
                 }
                 Err(e) => {
@@ -489,7 +439,8 @@ fn Dashboard(data: BenchmarkData) -> Element {
                 SuiteSection {
                     key: "{suite_name}",
                     suite_name: suite_name.clone(),
-                    runs: runs.clone()
+                    runs: runs.clone(),
+                    suspicious_threshold: recorded_suspicious_threshold(&data, suite_name)
                 }
             }
         }
@@ -683,8 +634,13 @@ fn RunsSidebar(runs: Vec<RunInfo>) -> Element {
 
 /// Suite section - contains the overall suite header and hierarchical module containers
 #[component]
-fn SuiteSection(suite_name: String, runs: Vec<BenchmarkRun>) -> Element {
+fn SuiteSection(
+    suite_name: String,
+    runs: Vec<BenchmarkRun>,
+    suspicious_threshold: Option<Threshold>,
+) -> Element {
     let ThemeCtx(dark_mode) = use_context::<ThemeCtx>();
+    use_context_provider(|| SuspiciousThresholdCtx(suspicious_threshold.clone()));
     let dark = *dark_mode.read();
     let mut expanded = use_signal(|| true);
 
@@ -692,10 +648,10 @@ fn SuiteSection(suite_name: String, runs: Vec<BenchmarkRun>) -> Element {
     let runs_info = extract_runs(&runs);
 
     // Determine if we have hierarchical benchmarks
-    let has_hierarchy = hierarchy.keys().any(|k| k != "_ungrouped")
+    let has_hierarchy = hierarchy.keys().any(|k| k != BenchmarkPath::UNGROUPED)
         || hierarchy
-            .get("_ungrouped")
-            .map_or(false, |m| m.keys().any(|k| k != "_ungrouped"));
+            .get(BenchmarkPath::UNGROUPED)
+            .map_or(false, |m| m.keys().any(|k| k != BenchmarkPath::UNGROUPED));
 
     let is_expanded = *expanded.read();
 
@@ -705,7 +661,7 @@ fn SuiteSection(suite_name: String, runs: Vec<BenchmarkRun>) -> Element {
 
             if has_hierarchy {
                 for (grandparent, parents) in hierarchy.iter() {
-                    if grandparent != "_ungrouped" {
+                    if grandparent != BenchmarkPath::UNGROUPED {
                         ModuleContainer {
                             key: "{grandparent}",
                             name: grandparent.clone(),
@@ -716,9 +672,9 @@ fn SuiteSection(suite_name: String, runs: Vec<BenchmarkRun>) -> Element {
                     }
                 }
                 // Handle 2-level hierarchy (parent/test) - render directly as charts
-                if let Some(ungrouped) = hierarchy.get("_ungrouped") {
+                if let Some(ungrouped) = hierarchy.get(BenchmarkPath::UNGROUPED) {
                     for (parent_name, points) in ungrouped.iter() {
-                        if parent_name != "_ungrouped" {
+                        if parent_name != BenchmarkPath::UNGROUPED {
                             CollapsibleChart {
                                 key: "{parent_name}",
                                 name: parent_name.clone(),
@@ -728,7 +684,7 @@ fn SuiteSection(suite_name: String, runs: Vec<BenchmarkRun>) -> Element {
                         }
                     }
                     // Truly ungrouped (single-level names)
-                    if let Some(truly_ungrouped) = ungrouped.get("_ungrouped") {
+                    if let Some(truly_ungrouped) = ungrouped.get(BenchmarkPath::UNGROUPED) {
                         CollapsibleChart {
                             name: "other".to_string(),
                             data_points: truly_ungrouped.clone(),
@@ -742,7 +698,7 @@ fn SuiteSection(suite_name: String, runs: Vec<BenchmarkRun>) -> Element {
                     for (parent_name, points) in parents.iter() {
                         CollapsibleChart {
                             key: "{parent_name}",
-                            name: if parent_name == "_ungrouped" { "benchmarks".to_string() } else { parent_name.clone() },
+                            name: if parent_name == BenchmarkPath::UNGROUPED { "benchmarks".to_string() } else { parent_name.clone() },
                             data_points: points.clone(),
                             runs_info: runs_info.clone()
                         }
@@ -762,10 +718,24 @@ fn ModuleContainer(
     runs_info: Vec<RunInfo>,
 ) -> Element {
     let ThemeCtx(dark_mode) = use_context::<ThemeCtx>();
+    let SelectionCtx { from_idx, to_idx } = use_context::<SelectionCtx>();
     let dark = *dark_mode.read();
     let mut expanded = use_signal(|| true);
     let is_expanded = *expanded.read();
 
+    // Aggregate score of the whole module between the selected runs
+    let commit_for = |selection: Option<usize>| {
+        selection.and_then(|run_i| {
+            runs_info
+                .iter()
+                .find(|r| r.run_idx == run_i)
+                .map(|r| r.commit_id.clone())
+        })
+    };
+    let from_commit_id = commit_for(*from_idx.read());
+    let to_commit_id = commit_for(*to_idx.read());
+    let score = module_score(&charts, from_commit_id.as_deref(), to_commit_id.as_deref());
+
     rsx! {
         div { style: "{container_card_style(dark)}",
             // Collapsible container header
@@ -776,12 +746,24 @@ fn ModuleContainer(
                     if is_expanded { "▼" } else { "▶" }
                 }
                 span { style: "{container_title_style(dark)}", "{name}" }
+                if let Some((ratio, count)) = score {
+                    {
+                        let change_pct = (ratio - 1.0) * 100.0;
+                        rsx! {
+                            span {
+                                style: "margin-left: auto; font-size: 0.75rem; font-weight: 500; color: {change_color(dark, change_pct)};",
+                                title: "Geometric mean of {count} benchmark ratio(s)",
+                                "{format_change(change_pct)} overall"
+                            }
+                        }
+                    }
+                }
             }
 
             if is_expanded {
                 div { style: "padding: 1rem;",
                     for (parent_name, points) in charts.iter() {
-                        if parent_name != "_ungrouped" {
+                        if parent_name != BenchmarkPath::UNGROUPED {
                             CollapsibleChart {
                                 key: "{parent_name}",
                                 name: parent_name.clone(),
@@ -790,7 +772,7 @@ fn ModuleContainer(
                             }
                         }
                     }
-                    if let Some(ungrouped_points) = charts.get("_ungrouped") {
+                    if let Some(ungrouped_points) = charts.get(BenchmarkPath::UNGROUPED) {
                         CollapsibleChart {
                             name: "other".to_string(),
                             data_points: ungrouped_points.clone(),
@@ -874,6 +856,7 @@ fn BenchmarkChart(
 ) -> Element {
     let ThemeCtx(dark_mode) = use_context::<ThemeCtx>();
     let SelectionCtx { from_idx, to_idx } = use_context::<SelectionCtx>();
+    let SuspiciousThresholdCtx(suspicious_threshold) = use_context::<SuspiciousThresholdCtx>();
     let dark = *dark_mode.read();
 
     // Track hovered commit
@@ -1255,11 +1238,28 @@ fn BenchmarkChart(
                                     for (test_name, from_value, to_value, change_pct, color) in sorted_metrics.iter() {
                                         {
                                             let pct_color = change_color(dark, *change_pct);
+                                            let row_unit = data_points
+                                                .iter()
+                                                .find(|p| &p.test_name == test_name)
+                                                .map_or("", |p| p.unit.as_str());
+                                            let suspicious = is_suspicious_speedup(
+                                                suspicious_threshold.as_ref(),
+                                                *from_value,
+                                                *to_value,
+                                                row_unit,
+                                            );
                                             rsx! {
                                                 div { style: "{metrics_table_row_style(dark)}",
                                                     div { style: "flex: 2; display: flex; align-items: center; gap: 0.4rem;",
                                                         span { style: "width: 8px; height: 8px; border-radius: 50%; background: {color};" }
                                                         span { "{test_name}" }
+                                                        if suspicious {
+                                                            span {
+                                                                style: "{suspicious_badge_style(dark)}",
+                                                                title: "Suspicious improvement: check that the benchmark still does its work",
+                                                                "suspicious"
+                                                            }
+                                                        }
                                                     }
                                                     span {
                                                         style: "flex: 1; text-align: right; {muted_style(dark)}",
@@ -1389,6 +1389,61 @@ fn calculate_metrics_comparison(
 
 // change_color is now in styles.rs as change_color(dark, change_pct)
 
+/// Geometric mean of the to/from ratios of every benchmark in a module,
+/// with the number of benchmarks it covers
+fn module_score(
+    charts: &BTreeMap<String, Vec<BenchmarkDataPoint>>,
+    from_commit_id: Option<&str>,
+    to_commit_id: Option<&str>,
+) -> Option<(f64, usize)> {
+    let mut ratios = Vec::new();
+    for points in charts.values() {
+        let mut seen = HashSet::new();
+        let test_names: Vec<String> = points
+            .iter()
+            .filter(|p| seen.insert(p.test_name.clone()))
+            .map(|p| p.test_name.clone())
+            .collect();
+        let mut seen = HashSet::new();
+        let chart_commits: Vec<String> = points
+            .iter()
+            .filter(|p| seen.insert(p.commit_id.clone()))
+            .map(|p| p.commit_id.clone())
+            .collect();
+
+        let metrics = calculate_metrics_comparison(
+            points,
+            &test_names,
+            &chart_commits,
+            from_commit_id,
+            to_commit_id,
+            &HashMap::new(),
+        );
+        ratios.extend(metrics.iter().filter_map(|(_, from, to, _, _)| match from {
+            Some(from) if *from > 0.0 && *to > 0.0 => Some(to / from),
+            _ => None,
+        }));
+    }
+
+    geometric_mean(&ratios).map(|ratio| (ratio, ratios.len()))
+}
+
+/// A speedup reaching the suite's suspicious threshold usually means the benchmark
+/// was optimized away
+fn is_suspicious_speedup(
+    threshold: Option<&Threshold>,
+    from_value: Option<f64>,
+    to_value: f64,
+    unit: &str,
+) -> bool {
+    match (threshold, from_value) {
+        (Some(threshold), Some(from)) if from > 0.0 => {
+            threshold.is_exceeded_by_speedup(from, to_value, unit)
+        }
+        _ => false,
+    }
+}
+
 fn format_change(change_pct: f64) -> String {
     if change_pct >= 0.0 {
         format!("+{:.1}%", change_pct)
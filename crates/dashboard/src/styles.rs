@@ -268,6 +268,19 @@ pub fn badge_baseline_style(dark: bool) -> String {
     )
 }
 
+/// Badge style for suspicious improvements (speedups worth a second look)
+pub fn suspicious_badge_style(dark: bool) -> String {
+    let fg = if dark { "#ffff00" } else { "#cc9900" };
+    format!(
+        "border: 1px solid {fg}; \
+         color: {fg}; \
+         padding: 0 0.3rem; \
+         font-size: 0.6rem; \
+         text-transform: uppercase; \
+         cursor: help;"
+    )
+}
+
 /// Icon button style
 pub fn icon_btn_style(dark: bool, active: bool) -> String {
     let (bg, fg) = if active {